@@ -0,0 +1,308 @@
+//! Complete state-space enumeration for small boards.
+//!
+//! A breadth-first search from the goal visits every reachable position and
+//! records its distance, i.e. its optimal solution length. The largest
+//! distance is the board's "God's number" and the positions at that distance
+//! are its antipodes.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::packed::{Grid, PackedState};
+use crate::rank::{RANKED_MAX_CELLS, factorial, rank, rank_packed, unrank};
+use crate::{Cell, State, validate_board_size};
+
+/// Distance stored for positions that can't reach the goal.
+pub const UNREACHABLE: u8 = u8::MAX;
+
+const MAGIC: &[u8; 4] = b"SPDT";
+const VERSION: u8 = 1;
+
+#[derive(Debug)]
+pub enum AnalysisError {
+    TooLarge { rows: usize, cols: usize },
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnalysisError::TooLarge { rows, cols } => write!(
+                f,
+                "{}x{} board is too large to enumerate (at most {} cells)",
+                rows, cols, RANKED_MAX_CELLS
+            ),
+            AnalysisError::Io(e) => e.fmt(f),
+            AnalysisError::Format(message) => write!(f, "invalid distance table: {}", message),
+        }
+    }
+}
+
+impl Error for AnalysisError {}
+
+impl From<io::Error> for AnalysisError {
+    fn from(e: io::Error) -> Self {
+        AnalysisError::Io(e)
+    }
+}
+
+/// Optimal solution length of every position of a small board, indexed by
+/// permutation rank.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistanceTable {
+    rows: usize,
+    cols: usize,
+    distances: Vec<u8>,
+}
+
+impl DistanceTable {
+    /// Enumerates every position reachable from the goal of a `rows` x `cols`
+    /// board.
+    pub fn build(rows: usize, cols: usize) -> Result<Self, AnalysisError> {
+        if validate_board_size(rows, cols).is_err() || rows * cols > RANKED_MAX_CELLS {
+            return Err(AnalysisError::TooLarge { rows, cols });
+        }
+
+        let n = rows * cols;
+        let goal = State::new(rows, cols);
+        let grid = Grid::new(&goal);
+
+        let mut distances = vec![UNREACHABLE; factorial(n)];
+        let goal = PackedState::<u64>::pack(&goal);
+        distances[rank_packed(&goal, n)] = 0;
+
+        let mut frontier = vec![goal];
+        let mut depth = 0;
+        while !frontier.is_empty() {
+            depth += 1;
+            let mut next_frontier = vec![];
+            for state in frontier {
+                for &next in grid.neighbors(state.blank()) {
+                    let next_state = state.slide(next);
+                    let r = rank_packed(&next_state, n);
+                    if distances[r] == UNREACHABLE {
+                        distances[r] = depth;
+                        next_frontier.push(next_state);
+                    }
+                }
+            }
+            frontier = next_frontier;
+        }
+
+        Ok(DistanceTable {
+            rows,
+            cols,
+            distances,
+        })
+    }
+
+    /// Returns the table for `rows` x `cols`, building it on first use.
+    ///
+    /// Tables are kept for the lifetime of the process; [`install`] adds one
+    /// loaded from disk so it doesn't have to be rebuilt.
+    ///
+    /// [`install`]: DistanceTable::install
+    pub fn shared(rows: usize, cols: usize) -> Result<Arc<Self>, AnalysisError> {
+        if let Some(table) = shared_tables().lock().unwrap().get(&(rows, cols)) {
+            return Ok(table.clone());
+        }
+        let table = Arc::new(DistanceTable::build(rows, cols)?);
+        Ok(DistanceTable::install(table))
+    }
+
+    /// Makes `table` the shared table for its board size.
+    pub fn install(table: Arc<Self>) -> Arc<Self> {
+        shared_tables()
+            .lock()
+            .unwrap()
+            .insert((table.rows, table.cols), table.clone());
+        table
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Optimal solution length of `state`, or `None` if it is unsolvable.
+    pub fn distance(&self, state: &State) -> Option<usize> {
+        assert_eq!(
+            (state.rows(), state.cols()),
+            (self.rows, self.cols),
+            "board size mismatch"
+        );
+        match self.distances[rank(&state.to_numbers())] {
+            UNREACHABLE => None,
+            d => Some(d as usize),
+        }
+    }
+
+    /// A shortest sequence of blank moves from `state` to the goal, found by
+    /// always stepping to a neighbour one move closer, or `None` if `state`
+    /// is unsolvable.
+    pub fn solve(&self, state: &State) -> Option<Vec<Cell>> {
+        let mut distance = self.distance(state)?;
+        let mut state = state.clone();
+        let mut moves = vec![];
+        while distance > 0 {
+            let next = state
+                .neighbors(state.blank_cell())
+                .into_iter()
+                .find(|&cell| {
+                    let mut next = state.clone();
+                    next.try_move(cell);
+                    self.distance(&next) == Some(distance - 1)
+                })?;
+            state.try_move(next);
+            moves.push(next);
+            distance -= 1;
+        }
+        Some(moves)
+    }
+
+    /// The largest distance of any position (God's number for this board).
+    pub fn max_distance(&self) -> usize {
+        self.distances
+            .iter()
+            .filter(|&&d| d != UNREACHABLE)
+            .max()
+            .map_or(0, |&d| d as usize)
+    }
+
+    /// Number of positions at each distance, starting with the goal.
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.max_distance() + 1];
+        for &d in self.distances.iter().filter(|&&d| d != UNREACHABLE) {
+            counts[d as usize] += 1;
+        }
+        counts
+    }
+
+    /// Number of positions that can reach the goal.
+    pub fn reachable(&self) -> usize {
+        self.distances.iter().filter(|&&d| d != UNREACHABLE).count()
+    }
+
+    /// Every position at `distance` moves from the goal.
+    pub fn positions_at(&self, distance: usize) -> Vec<State> {
+        let n = self.rows * self.cols;
+        self.distances
+            .iter()
+            .enumerate()
+            .filter(|&(_, &d)| d as usize == distance)
+            .map(|(r, _)| State::from_numbers(self.rows, self.cols, &unrank(r, n)).unwrap())
+            .collect()
+    }
+
+    /// The hardest positions.
+    pub fn antipodes(&self) -> Vec<State> {
+        self.positions_at(self.max_distance())
+    }
+
+    /// Writes the table: a small header (`SPDT`, version, rows, cols)
+    /// followed by one distance byte per permutation rank.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION, self.rows as u8, self.cols as u8])?;
+        w.write_all(&self.distances)
+    }
+
+    pub fn read_from<R: Read>(mut r: R) -> Result<Self, AnalysisError> {
+        let mut header = [0; 7];
+        r.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(AnalysisError::Format("bad magic".to_string()));
+        }
+        if header[4] != VERSION {
+            return Err(AnalysisError::Format(format!(
+                "unsupported version {}",
+                header[4]
+            )));
+        }
+
+        let rows = header[5] as usize;
+        let cols = header[6] as usize;
+        if validate_board_size(rows, cols).is_err() || rows * cols > RANKED_MAX_CELLS {
+            return Err(AnalysisError::TooLarge { rows, cols });
+        }
+
+        let mut distances = vec![];
+        r.read_to_end(&mut distances)?;
+        if distances.len() != factorial(rows * cols) {
+            return Err(AnalysisError::Format(format!(
+                "expected {} entries, found {}",
+                factorial(rows * cols),
+                distances.len()
+            )));
+        }
+        let table = DistanceTable {
+            rows,
+            cols,
+            distances,
+        };
+        table.check()?;
+        Ok(table)
+    }
+
+    /// Checks that the goal is at distance 0 and every other reachable
+    /// position has a neighbour one move closer, which `solve` relies on.
+    fn check(&self) -> Result<(), AnalysisError> {
+        let n = self.rows * self.cols;
+        let goal = State::new(self.rows, self.cols);
+        let grid = Grid::new(&goal);
+        let goal_rank = rank(&goal.to_numbers());
+        if self.distances[goal_rank] != 0 {
+            return Err(AnalysisError::Format(
+                "the goal is not at distance 0".to_string(),
+            ));
+        }
+
+        for (r, &d) in self.distances.iter().enumerate() {
+            if d == UNREACHABLE || r == goal_rank {
+                continue;
+            }
+            let mut perm = unrank(r, n);
+            let blank = perm.iter().position(|&t| t == 0).unwrap();
+            let closer = d > 0
+                && grid.neighbors(blank).iter().any(|&next| {
+                    perm.swap(blank, next);
+                    let closer = self.distances[rank(&perm)] == d - 1;
+                    perm.swap(blank, next);
+                    closer
+                });
+            if !closer {
+                return Err(AnalysisError::Format(format!(
+                    "position {} at distance {} has no neighbour one move closer",
+                    r, d
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AnalysisError> {
+        DistanceTable::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+type SharedTables = Mutex<HashMap<(usize, usize), Arc<DistanceTable>>>;
+
+fn shared_tables() -> &'static SharedTables {
+    static TABLES: OnceLock<SharedTables> = OnceLock::new();
+    TABLES.get_or_init(Default::default)
+}
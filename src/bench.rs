@@ -0,0 +1,285 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Serialize;
+
+use crate::{SolverKind, State, StateError};
+
+/// Global allocator wrapper that keeps track of the peak heap usage.
+///
+/// The binary installs it with `#[global_allocator]` when built with the
+/// `memory-stats` feature; without it `peak_memory_bytes` is `None`.
+pub struct TrackingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                let grown = new_size - layout.size();
+                let current = CURRENT.fetch_add(grown, Ordering::Relaxed) + grown;
+                PEAK.fetch_max(current, Ordering::Relaxed);
+            } else {
+                CURRENT.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// Restarts peak tracking and returns the bytes currently allocated.
+fn reset_peak() -> usize {
+    let current = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(current, Ordering::Relaxed);
+    current
+}
+
+/// A named position to benchmark.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub name: String,
+    pub state: State,
+    /// Known optimal solution length, if any.
+    pub optimal: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorpusError {
+    Syntax { line: usize, message: String },
+    State { line: usize, error: StateError },
+}
+
+impl fmt::Display for CorpusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorpusError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            CorpusError::State { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl Error for CorpusError {}
+
+/// Parses a corpus file.
+///
+/// `size ROWS COLS` sets the board size for the following instance lines. An
+/// instance line is a name and the tiles in row-major order with `0` as the
+/// blank, optionally followed by `; OPTIMAL_LENGTH`. Blank lines and lines
+/// starting with `#` are ignored.
+pub fn parse_corpus(text: &str) -> Result<Vec<Instance>, CorpusError> {
+    let mut instances = vec![];
+    let mut size = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let syntax = |message: String| CorpusError::Syntax {
+            line: line_no,
+            message,
+        };
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (body, optimal) = match line.split_once(';') {
+            Some((body, optimal)) => {
+                let optimal = optimal
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|e| syntax(format!("invalid optimal length: {}", e)))?;
+                (body, Some(optimal))
+            }
+            None => (line, None),
+        };
+
+        let mut words = body.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err(syntax("expected a name before `;`".to_string()));
+        };
+        let numbers = words
+            .map(|w| w.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| syntax(format!("invalid number: {}", e)))?;
+
+        if name == "size" {
+            let [rows, cols] = numbers[..] else {
+                return Err(syntax("expected `size ROWS COLS`".to_string()));
+            };
+            size = Some((rows, cols));
+            continue;
+        }
+
+        let (rows, cols) = size.ok_or_else(|| syntax("instance before `size`".to_string()))?;
+        let state =
+            State::from_numbers(rows, cols, &numbers).map_err(|error| CorpusError::State {
+                line: line_no,
+                error,
+            })?;
+        if !state.is_solvable() {
+            return Err(syntax(format!("instance `{}` is not solvable", name)));
+        }
+        instances.push(Instance {
+            name: name.to_string(),
+            state,
+            optimal,
+        });
+    }
+    Ok(instances)
+}
+
+/// The two hardest 8-puzzle positions (31 moves).
+pub fn hardest_8_puzzle() -> Vec<Instance> {
+    parse_corpus(include_str!("../assets/corpus/hardest-8.txt")).unwrap()
+}
+
+/// Korf's 100 random 15-puzzle positions, with their optimal lengths.
+pub fn korf_100() -> Vec<Instance> {
+    parse_corpus(include_str!("../assets/corpus/korf100.txt")).unwrap()
+}
+
+/// `count` shuffled positions derived from `seed`.
+pub fn random_instances(rows: usize, cols: usize, count: usize, seed: u64) -> Vec<Instance> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count)
+        .map(|i| {
+            let mut state = State::new(rows, cols);
+            while state.is_finished() {
+                state.shuffle_with(&mut rng);
+            }
+            Instance {
+                name: format!("random-{}x{}-{}-{}", rows, cols, seed, i),
+                state,
+                optimal: None,
+            }
+        })
+        .collect()
+}
+
+/// One solver run on one instance.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchRecord {
+    pub instance: String,
+    pub rows: usize,
+    pub cols: usize,
+    pub solver: String,
+    /// The moves were all legal and ended at the goal.
+    pub solved: bool,
+    pub solution_length: usize,
+    pub optimal_length: Option<usize>,
+    pub optimality_gap: Option<usize>,
+    pub nodes_expanded: usize,
+    /// Only measured with the `memory-stats` feature.
+    pub peak_memory_bytes: Option<usize>,
+    pub time_ms: f64,
+}
+
+/// Runs every solver in `kinds` that supports the board size on every
+/// instance.
+///
+/// Instances without a known optimal length use the BFS solution length when
+/// BFS is among `kinds` and solved it.
+pub fn run(instances: &[Instance], kinds: &[SolverKind]) -> Vec<BenchRecord> {
+    let mut records = vec![];
+    for instance in instances {
+        let rows = instance.state.rows();
+        let cols = instance.state.cols();
+
+        let first = records.len();
+        for kind in kinds.iter().filter(|kind| kind.supports(rows, cols)) {
+            let mut solver = kind.build(instance.state.clone());
+
+            let baseline = reset_peak();
+            let start = Instant::now();
+            let moves = solver.solve();
+            let time_ms = start.elapsed().as_secs_f64() * 1000.0;
+            let peak_memory_bytes = cfg!(feature = "memory-stats")
+                .then(|| PEAK.load(Ordering::Relaxed).saturating_sub(baseline));
+
+            let mut state = instance.state.clone();
+            let solved = moves.iter().all(|&mv| state.try_move(mv)) && state.is_finished();
+
+            records.push(BenchRecord {
+                instance: instance.name.clone(),
+                rows,
+                cols,
+                solver: solver.name().to_string(),
+                solved,
+                solution_length: moves.len(),
+                optimal_length: instance.optimal,
+                optimality_gap: None,
+                nodes_expanded: solver.nodes_expanded(),
+                peak_memory_bytes,
+                time_ms,
+            });
+        }
+
+        let records = &mut records[first..];
+        let optimal = instance.optimal.or_else(|| {
+            records
+                .iter()
+                .find(|r| r.solver == SolverKind::Bfs.name() && r.solved)
+                .map(|r| r.solution_length)
+        });
+        for record in records.iter_mut() {
+            record.optimal_length = optimal;
+            if record.solved {
+                record.optimality_gap = optimal.map(|o| record.solution_length.saturating_sub(o));
+            }
+        }
+    }
+    records
+}
+
+pub fn write_csv<W: Write>(records: &[BenchRecord], mut w: W) -> io::Result<()> {
+    writeln!(
+        w,
+        "instance,rows,cols,solver,solved,solution_length,optimal_length,optimality_gap,nodes_expanded,peak_memory_bytes,time_ms"
+    )?;
+    let opt = |v: Option<usize>| v.map(|v| v.to_string()).unwrap_or_default();
+    for r in records {
+        writeln!(
+            w,
+            "{},{},{},{},{},{},{},{},{},{},{:.3}",
+            r.instance,
+            r.rows,
+            r.cols,
+            r.solver,
+            r.solved,
+            r.solution_length,
+            opt(r.optimal_length),
+            opt(r.optimality_gap),
+            r.nodes_expanded,
+            opt(r.peak_memory_bytes),
+            r.time_ms
+        )?;
+    }
+    Ok(())
+}
+
+pub fn write_json<W: Write>(records: &[BenchRecord], w: W) -> io::Result<()> {
+    serde_json::to_writer_pretty(w, records).map_err(io::Error::other)
+}
@@ -0,0 +1,585 @@
+//! The classic game: one board, played in a window or the terminal, or
+//! watched while the AI solves it.
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use ::rand::SeedableRng;
+use ::rand::rngs::StdRng;
+use ::rand::seq::IndexedRandom;
+
+use clap::Args;
+use macroquad::prelude::*;
+
+use slide_puzzle::analysis::DistanceTable;
+use slide_puzzle::config::BoardConfig;
+use slide_puzzle::daily::{self, DailyResult, DailyStats, Date};
+use slide_puzzle::generate::{self, Target};
+use slide_puzzle::replay::Replay;
+use slide_puzzle::scene::{Choice, GameEnd, GameResult, GameSetup, Menu, Mode};
+use slide_puzzle::{
+    Board, Cell, Drag, Geometry, MAX_BOARD_SIZE, MIN_BOARD_SIZE, MacroquadRenderer, MySolver,
+    SolverKind, State, drag_board, pointer_events,
+};
+
+use crate::ui::{
+    Pacer, continue_pressed, draw_continue_hint, draw_panel_lines, leave, new_board, pause_overlay,
+    quit_requested, status_text, update_layout,
+};
+use crate::{
+    ai_solver, config, parse_board_dimension, parse_cell, parse_geometry, parse_solver_kind,
+    parse_target,
+};
+
+// Options of the classic game, given on the command line or picked in the
+// menus. Not a doc comment, which clap would show as the program's about.
+#[derive(Debug, Args)]
+pub struct GameArgs {
+    #[clap(
+        default_value_t = BoardConfig::default().rows,
+        value_parser = parse_board_dimension,
+        help = format!("Number of rows ({}..={})", MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+    )]
+    pub rows: usize,
+
+    #[clap(
+        default_value_t = BoardConfig::default().cols,
+        value_parser = parse_board_dimension,
+        help = format!("Number of columns ({}..={})", MIN_BOARD_SIZE, MAX_BOARD_SIZE),
+    )]
+    pub cols: usize,
+
+    #[clap(long)]
+    pub ai: bool,
+
+    /// Solver the AI plays with (default: picked by board size)
+    #[clap(long, value_parser = parse_solver_kind)]
+    pub solver: Option<SolverKind>,
+
+    /// Start from one of the hardest positions of the board (at most 10 cells)
+    #[clap(long)]
+    pub hardest: bool,
+
+    /// Load a distance table written by `analyze --output` instead of
+    /// enumerating the board on startup
+    #[clap(long)]
+    pub table: Option<PathBuf>,
+
+    /// Generate a position of this difficulty: easy, medium, hard, expert, or
+    /// an exact optimal solution length (boards of at most 10 cells)
+    #[clap(long, value_parser = parse_target)]
+    pub difficulty: Option<Target>,
+
+    /// Seed for shuffling or generating the position
+    #[clap(long)]
+    pub seed: Option<u64>,
+
+    /// Play the daily puzzle, the same position for everyone with this board
+    /// size
+    #[clap(long, conflicts_with_all = ["ai", "hardest", "difficulty", "seed"])]
+    pub daily: bool,
+
+    /// Play the daily puzzle of another date (YYYY-MM-DD, UTC)
+    #[clap(long, requires = "daily")]
+    pub date: Option<Date>,
+
+    /// Write the replay of this game to this file instead of the replays
+    /// directory
+    #[clap(long)]
+    pub record: Option<PathBuf>,
+
+    /// Don't save a replay of this game
+    #[clap(long, conflicts_with = "record")]
+    pub no_record: bool,
+
+    /// Play in the terminal instead of opening a window
+    #[clap(long)]
+    pub tui: bool,
+
+    /// Start with the reduction solver's next step drawn over the board
+    #[clap(long, conflicts_with_all = ["tui", "walls", "blanks", "wrap", "grid"])]
+    pub show_path: bool,
+
+    /// Make a cell an immovable wall, given as `X,Y` counted from 0 at the
+    /// top left; may be repeated
+    #[clap(
+        long = "wall",
+        value_parser = parse_cell,
+        conflicts_with_all = ["daily", "hardest", "difficulty", "tui", "record"]
+    )]
+    pub walls: Vec<Cell>,
+
+    /// Number of blanks
+    #[clap(
+        long,
+        default_value_t = 1,
+        conflicts_with_all = ["daily", "hardest", "difficulty", "tui", "record"]
+    )]
+    pub blanks: usize,
+
+    /// Wrap rows and columns around, so tiles can slide across the edges
+    #[clap(
+        long,
+        conflicts_with_all = ["daily", "hardest", "difficulty", "tui", "record"]
+    )]
+    pub wrap: bool,
+
+    /// Shape of the cells: square, hex or triangle
+    #[clap(
+        long,
+        default_value = "square",
+        value_parser = parse_geometry,
+        conflicts_with_all = ["daily", "hardest", "difficulty", "tui", "record", "wrap"]
+    )]
+    pub grid: Geometry,
+}
+
+impl GameArgs {
+    /// The game picked in the menus, with every other option at its default.
+    pub fn from_setup(setup: GameSetup) -> Self {
+        GameArgs {
+            rows: setup.rows,
+            cols: setup.cols,
+            ai: setup.mode == Mode::Watch,
+            solver: None,
+            hardest: false,
+            table: None,
+            difficulty: None,
+            seed: None,
+            daily: setup.mode == Mode::Daily,
+            date: None,
+            record: None,
+            no_record: false,
+            tui: false,
+            show_path: false,
+            walls: vec![],
+            blanks: 1,
+            wrap: false,
+            grid: Geometry::Square,
+        }
+    }
+
+    /// The menu setup matching a game started from the command line.
+    pub fn setup(&self) -> GameSetup {
+        let mode = if self.ai {
+            Mode::Watch
+        } else if self.daily {
+            Mode::Daily
+        } else {
+            Mode::Play
+        };
+        GameSetup {
+            mode,
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+async fn play_with_ai(args: &GameArgs, mut board: Board, start: Start) -> GameEnd {
+    let mut pacer = Pacer::new(1.0);
+
+    let mut state = start.state.clone();
+    let mut recording = Recording::new(args, &state, start.seed);
+    let kind = ai_solver(&state);
+    let solution = kind.build(state.clone()).solve();
+    let move_count = solution.len();
+    let mut moves = solution.clone().into_iter();
+    let mut finished: Option<f64> = None;
+    let mut paused: Option<Menu> = None;
+    // the plan is the reduction's, so it only matches that solver's moves
+    let mut show_path = args.show_path && kind == SolverKind::My;
+    loop {
+        clear_background(config().theme.background);
+        let layout = update_layout(&mut board, state.rows(), state.cols());
+
+        let txt = format!("{} moves", move_count);
+        draw_text(
+            &txt,
+            layout.header.x,
+            layout.header.bottom(),
+            40.0,
+            config().theme.accent,
+        );
+
+        if quit_requested() {
+            recording.finish(state.is_finished(), recording.seconds());
+            return GameEnd::Quit;
+        }
+
+        board.apply_state(&state);
+        board.set_plan(show_path.then(|| MySolver::new(state.clone()).plan()));
+        board.draw(&mut MacroquadRenderer, layout.board.point());
+
+        if let Some(menu) = paused.as_mut() {
+            match pause_overlay(menu) {
+                Some(Choice::Resume) => {
+                    recording.resume();
+                    paused = None;
+                }
+                Some(Choice::Restart) => {
+                    recording.finish(false, recording.seconds());
+                    state = start.state.clone();
+                    recording = Recording::new(args, &state, start.seed);
+                    moves = solution.clone().into_iter();
+                    finished = None;
+                    paused = None;
+                }
+                Some(choice @ (Choice::MainMenu | Choice::Quit)) => {
+                    recording.finish(false, recording.seconds());
+                    return leave(choice);
+                }
+                _ => {}
+            }
+            next_frame().await;
+            continue;
+        }
+        if config().keys.pause.is_pressed() {
+            recording.pause();
+            paused = Some(Menu::pause());
+        }
+        if config().keys.path.is_pressed() && kind == SolverKind::My {
+            show_path = !show_path;
+        }
+
+        if state.is_finished() {
+            let seconds = *finished.get_or_insert_with(|| {
+                let seconds = recording.seconds();
+                recording.finish(true, seconds);
+                seconds
+            });
+            draw_continue_hint(&layout);
+            if continue_pressed() {
+                return GameEnd::Finished(GameResult {
+                    moves: recording.moves(),
+                    seconds,
+                    optimal: None,
+                    share: None,
+                });
+            }
+        } else {
+            if pacer.tick() {
+                match moves.next() {
+                    Some(mv) => {
+                        state.swap(state.blank_cell(), mv);
+                        recording.push(mv);
+                    }
+                    // the solver gave up before the end
+                    None => recording.finish(false, recording.seconds()),
+                }
+            }
+        }
+
+        next_frame().await;
+    }
+}
+
+/// The daily puzzle being played.
+pub struct DailyGame {
+    date: Date,
+    optimal: Option<usize>,
+}
+
+/// The position the game starts from and the seed it was made with.
+pub struct Start {
+    pub state: State,
+    pub seed: u64,
+    pub daily: Option<DailyGame>,
+}
+
+/// The position the game starts from.
+pub fn initial_state(args: &GameArgs) -> Result<Start, Box<dyn Error>> {
+    if let Some(path) = &args.table {
+        let table = DistanceTable::load(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if (table.rows(), table.cols()) != (args.rows, args.cols) {
+            return Err(format!(
+                "{}: the table is for {}x{} boards, not {}x{}",
+                path.display(),
+                table.rows(),
+                table.cols(),
+                args.rows,
+                args.cols
+            )
+            .into());
+        }
+        DistanceTable::install(Arc::new(table));
+    }
+
+    if args.daily {
+        let date = args.date.unwrap_or_else(Date::today);
+        let puzzle = daily::puzzle(date, args.rows, args.cols)?;
+        println!("daily puzzle {} ({})", date, puzzle.difficulty);
+        let game = DailyGame {
+            date,
+            optimal: puzzle.optimal,
+        };
+        return Ok(Start {
+            state: puzzle.state,
+            seed: daily::seed(date, args.rows, args.cols),
+            daily: Some(game),
+        });
+    }
+
+    // an unseeded game still gets a seed so that its replay records it
+    let seed = args.seed.unwrap_or_else(::rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let start = |state| {
+        Ok(Start {
+            state,
+            seed,
+            daily: None,
+        })
+    };
+
+    let mut state = State::new(args.rows, args.cols);
+    if args.hardest {
+        let antipodes = DistanceTable::shared(args.rows, args.cols)?.antipodes();
+        return start(antipodes.choose(&mut rng).unwrap().clone());
+    }
+
+    if let Some(target) = args.difficulty {
+        let puzzle = generate::generate(args.rows, args.cols, target, &mut rng)?;
+        match puzzle.optimal {
+            Some(optimal) => println!("difficulty: {} ({} moves)", puzzle.difficulty, optimal),
+            None => println!("difficulty: {}", puzzle.difficulty),
+        }
+        return start(puzzle.state);
+    }
+
+    if state.is_line() {
+        println!("single-line board: tiles keep their order, only the blank moves");
+    }
+    while state.is_finished() {
+        state.shuffle_with(&mut rng);
+    }
+    start(state)
+}
+
+/// The replay being recorded for the current game.
+pub struct Recording {
+    replay: Replay,
+    start: Instant,
+    /// When the game was paused, while it is.
+    paused_at: Option<Instant>,
+    /// Where the replay is saved; `None` if it isn't.
+    path: Option<PathBuf>,
+    saved: bool,
+}
+
+impl Recording {
+    pub fn new(args: &GameArgs, state: &State, seed: u64) -> Self {
+        let replay = Replay::new(state, Some(seed));
+        let path = if args.no_record {
+            None
+        } else {
+            args.record
+                .clone()
+                .or_else(|| Replay::default_dir().map(|dir| dir.join(replay.file_name())))
+        };
+        Recording {
+            replay,
+            start: Instant::now(),
+            paused_at: None,
+            path,
+            saved: false,
+        }
+    }
+
+    /// Seconds played, not counting pauses.
+    pub fn seconds(&self) -> f64 {
+        let now = self.paused_at.unwrap_or_else(Instant::now);
+        now.duration_since(self.start).as_secs_f64()
+    }
+
+    /// Stops the clock until [`Recording::resume`].
+    fn pause(&mut self) {
+        self.paused_at.get_or_insert_with(Instant::now);
+    }
+
+    fn resume(&mut self) {
+        if let Some(at) = self.paused_at.take() {
+            self.start += at.elapsed();
+        }
+    }
+
+    pub fn moves(&self) -> usize {
+        self.replay.moves.len()
+    }
+
+    pub fn push(&mut self, cell: Cell) {
+        let time_ms = self.start.elapsed().as_millis() as u64;
+        self.replay.push(time_ms, cell);
+    }
+
+    /// Records the result and saves the replay; later calls do nothing.
+    pub fn finish(&mut self, solved: bool, seconds: f64) {
+        if self.saved {
+            return;
+        }
+        self.saved = true;
+        self.replay.finish(solved, seconds);
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        match self.replay.save(path) {
+            Ok(()) => println!("replay saved to {}", path.display()),
+            Err(e) => eprintln!("could not save {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Saves the result of a finished daily puzzle and returns the text to share.
+pub fn finish_daily(game: &DailyGame, state: &State, moves: usize, seconds: f64) -> String {
+    let result = DailyResult {
+        date: game.date,
+        rows: state.rows(),
+        cols: state.cols(),
+        moves,
+        seconds,
+        optimal: game.optimal,
+    };
+
+    let Some(path) = DailyStats::default_path() else {
+        return daily::share_text(&result, 1);
+    };
+    let mut stats = DailyStats::load(&path).unwrap_or_else(|e| {
+        eprintln!("could not read {}: {}", path.display(), e);
+        DailyStats::default()
+    });
+    if stats.record(result.clone())
+        && let Err(e) = stats.save(&path)
+    {
+        eprintln!("could not save {}: {}", path.display(), e);
+    }
+
+    // a repeated solve is shared with the first result, which is what counts
+    let recorded = stats.get(game.date, result.rows, result.cols).unwrap();
+    let streak = stats.streak(game.date, result.rows, result.cols);
+    daily::share_text(recorded, streak)
+}
+
+/// Plays a classic game until it is solved or left.
+pub async fn run(args: GameArgs, start: Start) -> GameEnd {
+    let rows = args.rows;
+    let cols = args.cols;
+
+    println!("(rows, cols) = ({}, {})", rows, cols);
+
+    let mut board = new_board(rows, cols);
+    // closing the window goes through the quit check below, which saves the
+    // replay first
+    prevent_quit();
+
+    if args.ai {
+        return play_with_ai(&args, board, start).await;
+    }
+
+    let mut state = start.state.clone();
+    let mut recording = Recording::new(&args, &state, start.seed);
+    let mut drag: Option<Drag> = None;
+    let mut finished: Option<GameResult> = None;
+    let mut paused: Option<Menu> = None;
+    let mut show_path = args.show_path;
+    loop {
+        clear_background(config().theme.background);
+        let layout = update_layout(&mut board, state.rows(), state.cols());
+        let board_relative_path = layout.board.point();
+        let moves = recording.moves();
+        board.set_plan(show_path.then(|| MySolver::new(state.clone()).plan()));
+
+        if quit_requested() {
+            recording.finish(false, recording.seconds());
+            return GameEnd::Quit;
+        }
+
+        if state.is_finished() {
+            let result = finished.get_or_insert_with(|| {
+                let seconds = recording.seconds();
+                recording.finish(true, seconds);
+                let share = start
+                    .daily
+                    .as_ref()
+                    .map(|game| finish_daily(game, &state, moves, seconds));
+                if let Some(share) = &share {
+                    println!("{}", share);
+                }
+                GameResult {
+                    moves,
+                    seconds,
+                    optimal: start.daily.as_ref().and_then(|game| game.optimal),
+                    share,
+                }
+            });
+
+            board.draw(&mut MacroquadRenderer, board_relative_path);
+            let txt = format!("GAME CLEAR  {}", status_text(moves, result.seconds));
+            draw_text(&txt, layout.header.x, layout.header.bottom(), 40.0, RED);
+            if let Some(share) = &result.share {
+                draw_panel_lines(&layout, &share.lines().collect::<Vec<_>>());
+            }
+            draw_continue_hint(&layout);
+            if continue_pressed() {
+                return GameEnd::Finished(result.clone());
+            }
+            next_frame().await;
+            continue;
+        }
+
+        let txt = status_text(moves, recording.seconds());
+        draw_text(
+            &txt,
+            layout.header.x,
+            layout.header.bottom(),
+            40.0,
+            config().theme.accent,
+        );
+
+        if let Some(menu) = paused.as_mut() {
+            board.draw(&mut MacroquadRenderer, board_relative_path);
+            match pause_overlay(menu) {
+                Some(Choice::Resume) => {
+                    recording.resume();
+                    paused = None;
+                }
+                Some(Choice::Restart) => {
+                    recording.finish(false, recording.seconds());
+                    state = start.state.clone();
+                    recording = Recording::new(&args, &state, start.seed);
+                    paused = None;
+                }
+                Some(choice @ (Choice::MainMenu | Choice::Quit)) => {
+                    recording.finish(false, recording.seconds());
+                    return leave(choice);
+                }
+                _ => {}
+            }
+            next_frame().await;
+            continue;
+        }
+        if config().keys.pause.is_pressed() {
+            recording.pause();
+            paused = Some(Menu::pause());
+            drag = None;
+        }
+        if config().keys.path.is_pressed() {
+            show_path = !show_path;
+        }
+
+        let blank = state.blank_cell();
+        let events = pointer_events();
+        if let Some(cell) = drag_board(&mut drag, &events, &mut board, board_relative_path, blank) {
+            for cell in state.slide_line(cell) {
+                recording.push(cell);
+            }
+        }
+
+        board.apply_state(&state);
+        board.show_drag(drag.as_ref());
+        board.draw(&mut MacroquadRenderer, board_relative_path);
+
+        next_frame().await
+    }
+}
@@ -0,0 +1,211 @@
+//! The `klotski` subcommand: sliding blocks of several cells.
+
+use std::error::Error;
+use std::time::Instant;
+
+use clap::Args;
+use macroquad::prelude::*;
+
+use slide_puzzle::klotski::{self, KlotskiMove, Level};
+use slide_puzzle::{
+    Cell, Direction, Layout, LayoutConfig, MacroquadRenderer, PointerEvent, cell, pointer_events,
+};
+
+use crate::ui::{Pacer, draw_panel_lines, quit_requested, status_text};
+use crate::{config, window_conf};
+
+#[derive(Debug, Args)]
+pub struct KlotskiArgs {
+    /// Level number in the built-in pack
+    #[arg(default_value_t = 1)]
+    level: usize,
+
+    /// List the built-in levels and exit
+    #[arg(long)]
+    list: bool,
+
+    /// Let the solver play
+    #[arg(long)]
+    ai: bool,
+}
+
+pub fn run_klotski(args: KlotskiArgs) -> Result<(), Box<dyn Error>> {
+    let levels = klotski::builtin_levels();
+    if args.list {
+        for (i, level) in levels.iter().enumerate() {
+            let optimal = level
+                .optimal
+                .map_or(String::new(), |moves| format!("  ({} moves)", moves));
+            println!("{:>2}. {}{}", i + 1, level.name, optimal);
+        }
+        return Ok(());
+    }
+    if !(1..=levels.len()).contains(&args.level) {
+        return Err(format!("level must be between 1 and {}", levels.len()).into());
+    }
+    macroquad::Window::from_config(window_conf(), play_klotski(levels, args.level - 1, args.ai));
+    Ok(())
+}
+
+/// Plays the built-in Klotski levels starting from `levels[index]`.
+async fn play_klotski(levels: Vec<Level>, mut index: usize, ai: bool) {
+    let keys = &config().keys;
+    let controls = [
+        "drag: slide a block".to_string(),
+        format!(
+            "{} / {} / {} / {}: move the selected block",
+            keys.up, keys.down, keys.left, keys.right
+        ),
+        format!("{}: restart", keys.restart),
+        format!("{}: next level", keys.next),
+        format!("{}: quit", keys.quit),
+    ];
+
+    let mut puzzle = levels[index].puzzle.clone();
+    let mut solution = vec![].into_iter();
+    let mut selected: Option<usize> = None;
+    let mut pressed: Option<Vec2> = None;
+    let mut moves = 0;
+    let mut pacer = Pacer::new(1.0);
+    let mut start = Instant::now();
+    let mut finished: Option<f64> = None;
+    let mut restart = true;
+    loop {
+        if restart {
+            restart = false;
+            puzzle = levels[index].puzzle.clone();
+            solution = if ai {
+                puzzle.solve().unwrap_or_else(|| {
+                    eprintln!("no solution found within {} positions", klotski::NODE_LIMIT);
+                    vec![]
+                })
+            } else {
+                vec![]
+            }
+            .into_iter();
+            selected = None;
+            moves = 0;
+            start = Instant::now();
+            finished = None;
+        }
+
+        clear_background(config().theme.background);
+        let layout = Layout::compute(
+            vec2(screen_width(), screen_height()),
+            puzzle.rows(),
+            puzzle.cols(),
+            &LayoutConfig::default(),
+        );
+        let cell_size = layout.board.w / puzzle.cols() as f32;
+        let cell_at = |pos: Vec2| {
+            let rel = (pos - layout.board.point()) / cell_size;
+            (rel.x >= 0.0 && rel.y >= 0.0)
+                .then(|| cell!(rel.x as usize, rel.y as usize))
+                .filter(|cell| cell.x < puzzle.cols() && cell.y < puzzle.rows())
+        };
+
+        if quit_requested() {
+            break;
+        }
+        if config().keys.restart.is_pressed() {
+            restart = true;
+        }
+        if config().keys.next.is_pressed() {
+            index = (index + 1) % levels.len();
+            restart = true;
+        }
+
+        let level = &levels[index];
+        let mut header = format!("{}. {}", index + 1, level.name);
+        if puzzle.is_solved() {
+            let seconds = *finished.get_or_insert_with(|| start.elapsed().as_secs_f64());
+            header += &format!("  CLEAR  {}", status_text(moves, seconds));
+            draw_text(&header, layout.header.x, layout.header.bottom(), 40.0, RED);
+        } else {
+            header += &format!("  {}", status_text(moves, start.elapsed().as_secs_f64()));
+            draw_text(
+                &header,
+                layout.header.x,
+                layout.header.bottom(),
+                40.0,
+                config().theme.accent,
+            );
+
+            let mut wanted: Vec<KlotskiMove> = vec![];
+            if ai && pacer.tick() {
+                wanted.extend(solution.next());
+            }
+            for event in pointer_events() {
+                match event {
+                    PointerEvent::Pressed(pos) => {
+                        selected = cell_at(pos).and_then(|cell| puzzle.block_at(cell));
+                        pressed = Some(pos);
+                    }
+                    PointerEvent::Moved(_) => {}
+                    PointerEvent::Released(pos) => {
+                        if let (Some(block), Some(origin)) = (selected, pressed.take())
+                            && !ai
+                        {
+                            let delta = (pos - origin) / cell_size;
+                            let (direction, cells) = if delta.x.abs() > delta.y.abs() {
+                                let dir = if delta.x > 0.0 {
+                                    Direction::Right
+                                } else {
+                                    Direction::Left
+                                };
+                                (dir, delta.x.abs().round() as usize)
+                            } else {
+                                let dir = if delta.y > 0.0 {
+                                    Direction::Down
+                                } else {
+                                    Direction::Up
+                                };
+                                (dir, delta.y.abs().round() as usize)
+                            };
+                            wanted.extend((0..cells).map(|_| KlotskiMove { block, direction }));
+                        }
+                    }
+                }
+            }
+            if let Some(block) = selected
+                && !ai
+            {
+                let directions = [
+                    (keys.up, Direction::Up),
+                    (keys.down, Direction::Down),
+                    (keys.left, Direction::Left),
+                    (keys.right, Direction::Right),
+                ];
+                for (key, direction) in directions {
+                    if key.is_pressed() {
+                        wanted.push(KlotskiMove { block, direction });
+                    }
+                }
+            }
+            // a drag slides the block as far as it goes, stopping at the
+            // first obstacle
+            for mv in wanted {
+                if !puzzle.apply(mv) {
+                    break;
+                }
+                moves += 1;
+            }
+        }
+
+        let mut lines = controls.to_vec();
+        if let Some(optimal) = level.optimal {
+            lines.push(String::new());
+            lines.push(format!("best: {} moves", optimal));
+        }
+        draw_panel_lines(&layout, &lines);
+
+        puzzle.draw(
+            &mut MacroquadRenderer,
+            layout.board.point(),
+            layout.board.size(),
+            selected,
+        );
+
+        next_frame().await
+    }
+}
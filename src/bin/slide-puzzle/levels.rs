@@ -0,0 +1,363 @@
+//! The `levels` subcommand and the level select.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use clap::Args;
+use macroquad::prelude::*;
+
+use slide_puzzle::levels::{Completion, LevelPack, Progress};
+use slide_puzzle::scene::{GameEnd, Scene};
+use slide_puzzle::{Board, Drag, MacroquadRenderer, PointerEvent, drag_board, pointer_events};
+
+use crate::menu::run_scenes;
+use crate::ui::{
+    draw_panel_board, draw_panel_lines, new_board, quit_requested, status_text, update_layout,
+};
+use crate::{config, window_conf};
+
+#[derive(Debug, Args)]
+pub struct LevelsArgs {
+    /// Start this level instead of opening the level select
+    level: Option<usize>,
+
+    /// Play the levels in this file instead of the built-in pack
+    #[arg(long)]
+    pack: Option<PathBuf>,
+
+    /// List the levels and your progress, and exit
+    #[arg(long)]
+    list: bool,
+}
+
+pub fn run_levels(args: LevelsArgs) -> Result<(), Box<dyn Error>> {
+    let pack = match &args.pack {
+        Some(path) => {
+            let text =
+                fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let name = path
+                .file_stem()
+                .map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+            LevelPack::parse(&name, &text).map_err(|e| format!("{}: {}", path.display(), e))?
+        }
+        None => LevelPack::builtin(),
+    };
+    let (progress, path) = load_progress();
+
+    if args.list {
+        for (i, level) in pack.levels.iter().enumerate() {
+            let mut line = format!(
+                "{:>2}. {}  {}x{}",
+                i + 1,
+                level.name,
+                level.start.rows(),
+                level.start.cols()
+            );
+            if let Some(par) = level.par {
+                line += &format!("  par {}", par);
+            }
+            if let Some(best) = progress.get(&pack.name, &level.name) {
+                line += &format!("  best {}", best.moves);
+            } else if !progress.is_unlocked(&pack, i) {
+                line += "  locked";
+            }
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+    if let Some(level) = args.level {
+        if !(1..=pack.levels.len()).contains(&level) {
+            return Err(format!("level must be between 1 and {}", pack.levels.len()).into());
+        }
+        if !progress.is_unlocked(&pack, level - 1) {
+            return Err(format!(
+                "level {} is locked; finish level {} first",
+                level,
+                level - 1
+            )
+            .into());
+        }
+    }
+    let start = args.level.map(|level| level - 1);
+    macroquad::Window::from_config(window_conf(), async move {
+        if play_levels(pack, progress, path, start).await == GameEnd::Menu {
+            run_scenes(Scene::MainMenu, None).await;
+        }
+    });
+    Ok(())
+}
+
+/// The saved level progress and where it is kept.
+pub fn load_progress() -> (Progress, Option<PathBuf>) {
+    let path = Progress::default_path();
+    let progress = match &path {
+        Some(path) => Progress::load(path).unwrap_or_else(|e| {
+            eprintln!("could not read {}: {}", path.display(), e);
+            Progress::default()
+        }),
+        None => Progress::default(),
+    };
+    (progress, path)
+}
+
+/// How a level ended.
+enum LevelEnd {
+    Quit,
+    /// Back to the level select.
+    Select,
+    /// On to the next level.
+    Next,
+}
+
+/// Shows the level select until a level is picked, starting at `cursor`.
+/// Returns how the select was left otherwise.
+async fn select_level(
+    pack: &LevelPack,
+    progress: &Progress,
+    mut cursor: usize,
+) -> Result<usize, GameEnd> {
+    const ROW_HEIGHT: f32 = 44.0;
+    const TOP: f32 = 120.0;
+    const LEFT: f32 = 40.0;
+
+    simulate_mouse_with_touch(false);
+    loop {
+        clear_background(config().theme.background);
+        if quit_requested() {
+            return Err(GameEnd::Quit);
+        }
+        if config().keys.pause.is_pressed() {
+            return Err(GameEnd::Menu);
+        }
+        if config().keys.up.is_pressed() {
+            cursor = cursor.saturating_sub(1);
+        }
+        if config().keys.down.is_pressed() {
+            cursor = (cursor + 1).min(pack.levels.len() - 1);
+        }
+        let mut picked = config().keys.confirm.is_pressed();
+        for event in pointer_events() {
+            if let PointerEvent::Pressed(pos) = event {
+                let row = ((pos.y - TOP) / ROW_HEIGHT).floor();
+                if row >= 0.0 && (row as usize) < pack.levels.len() {
+                    cursor = row as usize;
+                    picked = true;
+                }
+            }
+        }
+        if picked && progress.is_unlocked(pack, cursor) {
+            return Ok(cursor);
+        }
+
+        draw_text("LEVELS", LEFT, 60.0, 48.0, config().theme.accent);
+        let keys = &config().keys;
+        let hint = format!(
+            "{} / {}: choose   {} or click: play   {}: menu   {}: quit",
+            keys.up, keys.down, keys.confirm, keys.pause, keys.quit
+        );
+        draw_text(&hint, LEFT, 95.0, 24.0, config().theme.muted);
+        for (i, level) in pack.levels.iter().enumerate() {
+            let y = TOP + ROW_HEIGHT * i as f32;
+            if i == cursor {
+                draw_rectangle(
+                    LEFT - 10.0,
+                    y,
+                    screen_width() - LEFT * 2.0,
+                    ROW_HEIGHT,
+                    config().theme.highlight,
+                );
+            }
+            let best = progress.get(&pack.name, &level.name);
+            let unlocked = progress.is_unlocked(pack, i);
+            let mut line = format!(
+                "{:>2}. {}  {}x{}",
+                i + 1,
+                level.name,
+                level.start.rows(),
+                level.start.cols()
+            );
+            if let Some(par) = level.par {
+                line += &format!("  par {}", par);
+            }
+            let color = match best {
+                Some(best) => {
+                    line += &format!("  best {}", best.moves);
+                    DARKGREEN
+                }
+                None if unlocked => config().theme.text,
+                None => {
+                    line += "  locked";
+                    config().theme.grid
+                }
+            };
+            draw_text(&line, LEFT, y + ROW_HEIGHT * 0.75, 32.0, color);
+        }
+
+        next_frame().await
+    }
+}
+
+/// Plays one level, recording it in `progress` when it's finished.
+async fn play_level(
+    pack: &LevelPack,
+    index: usize,
+    progress: &mut Progress,
+    path: Option<&Path>,
+) -> LevelEnd {
+    let keys = &config().keys;
+    let controls = [
+        "drag: slide tiles".to_string(),
+        format!("{}: restart", keys.restart),
+        format!("{}: next level", keys.next),
+        format!("{}: level select", keys.pause),
+        format!("{}: quit", keys.quit),
+    ];
+
+    let level = &pack.levels[index];
+    let (rows, cols) = (level.start.rows(), level.start.cols());
+    let mut board = new_board(rows, cols);
+    board.set_goal(&level.goal);
+    let mut preview = Board::new(vec2(1.0, 1.0), config().board.border / 2.0, (rows, cols));
+    preview.set_theme(config().theme);
+    preview.set_goal(&level.goal);
+    preview.apply_state(&level.goal);
+
+    simulate_mouse_with_touch(false);
+    let mut state = level.start.clone();
+    let mut drag: Option<Drag> = None;
+    let mut moves = 0;
+    let mut start = Instant::now();
+    let mut finished: Option<f64> = None;
+    loop {
+        clear_background(config().theme.background);
+        let layout = update_layout(&mut board, rows, cols);
+        let board_relative_path = layout.board.point();
+
+        if quit_requested() {
+            return LevelEnd::Quit;
+        }
+        if config().keys.pause.is_pressed() {
+            return LevelEnd::Select;
+        }
+        if config().keys.next.is_pressed()
+            && index + 1 < pack.levels.len()
+            && progress.is_unlocked(pack, index + 1)
+        {
+            return LevelEnd::Next;
+        }
+        if config().keys.restart.is_pressed() {
+            state = level.start.clone();
+            drag = None;
+            moves = 0;
+            start = Instant::now();
+            finished = None;
+        }
+
+        let mut header = format!("{}. {}", index + 1, level.name);
+        if state == level.goal {
+            let seconds = *finished.get_or_insert_with(|| {
+                let seconds = start.elapsed().as_secs_f64();
+                finish_level(pack, index, progress, path, moves, seconds);
+                seconds
+            });
+            header += &format!("  CLEAR  {}", status_text(moves, seconds));
+            draw_text(&header, layout.header.x, layout.header.bottom(), 40.0, RED);
+        } else {
+            header += &format!("  {}", status_text(moves, start.elapsed().as_secs_f64()));
+            draw_text(
+                &header,
+                layout.header.x,
+                layout.header.bottom(),
+                40.0,
+                config().theme.accent,
+            );
+
+            let blank = state.blank_cell();
+            let events = pointer_events();
+            if let Some(cell) =
+                drag_board(&mut drag, &events, &mut board, board_relative_path, blank)
+            {
+                moves += state.slide_line(cell).len();
+            }
+        }
+
+        let mut lines = controls.to_vec();
+        lines.push(String::new());
+        if let Some(par) = level.par {
+            lines.push(format!("par: {} moves", par));
+        }
+        if let Some(best) = progress.get(&pack.name, &level.name) {
+            lines.push(format!("your best: {} moves", best.moves));
+        }
+        lines.push("goal:".to_string());
+        draw_panel_lines(&layout, &lines);
+        draw_panel_board(&layout, lines.len(), &mut preview, rows, cols);
+
+        board.apply_state(&state);
+        board.show_drag(drag.as_ref());
+        board.draw(&mut MacroquadRenderer, board_relative_path);
+
+        next_frame().await
+    }
+}
+
+/// Records a finished level and saves the progress.
+fn finish_level(
+    pack: &LevelPack,
+    index: usize,
+    progress: &mut Progress,
+    path: Option<&Path>,
+    moves: usize,
+    seconds: f64,
+) {
+    let completion = Completion {
+        pack: pack.name.clone(),
+        level: pack.levels[index].name.clone(),
+        moves,
+        seconds,
+    };
+    if progress.record(completion)
+        && let Some(path) = path
+        && let Err(e) = progress.save(path)
+    {
+        eprintln!("could not save {}: {}", path.display(), e);
+    }
+}
+
+/// Plays a level pack, from `start` or from the level select.
+pub async fn play_levels(
+    pack: LevelPack,
+    mut progress: Progress,
+    path: Option<PathBuf>,
+    start: Option<usize>,
+) -> GameEnd {
+    // the select opens on the first level that isn't finished yet
+    let mut cursor = start.unwrap_or_else(|| {
+        (0..pack.levels.len())
+            .find(|&i| progress.get(&pack.name, &pack.levels[i].name).is_none())
+            .unwrap_or(0)
+    });
+    let mut playing = start.is_some();
+    // every screen starts on a fresh frame, so the key or click that opened
+    // it isn't handled twice
+    loop {
+        if !playing {
+            match select_level(&pack, &progress, cursor).await {
+                Ok(index) => cursor = index,
+                Err(end) => return end,
+            }
+            next_frame().await;
+        }
+        match play_level(&pack, cursor, &mut progress, path.as_deref()).await {
+            LevelEnd::Quit => return GameEnd::Quit,
+            LevelEnd::Select => playing = false,
+            LevelEnd::Next => {
+                cursor += 1;
+                playing = true;
+            }
+        }
+        next_frame().await;
+    }
+}
@@ -0,0 +1,134 @@
+//! The `loopover` subcommand: rotating whole rows and columns.
+
+use std::error::Error;
+use std::time::Instant;
+
+use ::rand::SeedableRng;
+use ::rand::rngs::StdRng;
+
+use clap::Args;
+use macroquad::prelude::*;
+
+use slide_puzzle::loopover::{Line, Loopover, Rotation};
+use slide_puzzle::{Cell, MacroquadRenderer, PointerEvent, pointer_events};
+
+use crate::ui::{Pacer, new_board, quit_requested, status_text, update_layout};
+use crate::{config, parse_board_size, window_conf};
+
+#[derive(Debug, Args)]
+pub struct LoopoverArgs {
+    /// Board size, e.g. `4x4`
+    #[arg(value_parser = parse_board_size, default_value = "4x4")]
+    size: (usize, usize),
+
+    /// Seed for the shuffled position
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Let the solver play
+    #[arg(long)]
+    ai: bool,
+}
+
+pub fn run_loopover(args: LoopoverArgs) -> Result<(), Box<dyn Error>> {
+    let (rows, cols) = args.size;
+    let mut board = Loopover::new(rows, cols)?;
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut ::rand::rng()),
+    };
+    board.shuffle_with(&mut rng);
+    macroquad::Window::from_config(window_conf(), play_loopover(board, args.ai));
+    Ok(())
+}
+
+/// Plays Loopover: dragging a tile rotates its row or column by as many
+/// cells as it was dragged.
+async fn play_loopover(mut puzzle: Loopover, ai: bool) {
+    let (rows, cols) = (puzzle.rows(), puzzle.cols());
+    let mut board = new_board(rows, cols);
+    board.set_wrap(true);
+    let mut solution = if ai {
+        puzzle.solve().unwrap_or_default()
+    } else {
+        vec![]
+    }
+    .into_iter();
+
+    simulate_mouse_with_touch(false);
+    let mut pressed: Option<(Cell, Vec2)> = None;
+    let mut moves = 0;
+    // a rotation is a smaller step than a slide, so they go twice as fast
+    let mut pacer = Pacer::new(2.0);
+    let start = Instant::now();
+    let mut finished: Option<f64> = None;
+    loop {
+        clear_background(config().theme.background);
+        let layout = update_layout(&mut board, rows, cols);
+        let board_relative_path = layout.board.point();
+
+        if quit_requested() {
+            break;
+        }
+
+        if puzzle.is_finished() {
+            let seconds = *finished.get_or_insert_with(|| start.elapsed().as_secs_f64());
+            let txt = format!("GAME CLEAR  {}", status_text(moves, seconds));
+            draw_text(&txt, layout.header.x, layout.header.bottom(), 40.0, RED);
+        } else {
+            let txt = status_text(moves, start.elapsed().as_secs_f64());
+            draw_text(
+                &txt,
+                layout.header.x,
+                layout.header.bottom(),
+                40.0,
+                config().theme.accent,
+            );
+
+            if ai
+                && pacer.tick()
+                && let Some(rotation) = solution.next()
+            {
+                puzzle.rotate(rotation);
+                moves += 1;
+            }
+            for event in pointer_events() {
+                match event {
+                    PointerEvent::Pressed(pos) => {
+                        pressed = board
+                            .get_cell(pos - board_relative_path)
+                            .map(|cell| (cell, pos));
+                    }
+                    PointerEvent::Moved(_) => {}
+                    PointerEvent::Released(pos) => {
+                        let Some((cell, origin)) = pressed.take() else {
+                            continue;
+                        };
+                        if ai {
+                            continue;
+                        }
+                        let cells = (pos - origin) / board.cell_size();
+                        let (line, steps) = if cells.x.abs() > cells.y.abs() {
+                            (Line::Row(cell.y), cells.x)
+                        } else {
+                            (Line::Col(cell.x), cells.y)
+                        };
+                        let rotation = Rotation {
+                            line,
+                            forward: steps > 0.0,
+                        };
+                        for _ in 0..steps.abs().round() as usize {
+                            puzzle.rotate(rotation);
+                            moves += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        board.apply_loopover(&puzzle);
+        board.draw(&mut MacroquadRenderer, board_relative_path);
+
+        next_frame().await
+    }
+}
@@ -0,0 +1,344 @@
+mod classic;
+mod klotski;
+mod levels;
+mod loopover;
+mod menu;
+mod race;
+mod replay;
+mod terminal;
+mod tools;
+mod ui;
+mod variant;
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use macroquad::prelude::*;
+
+use slide_puzzle::config::{Config, ConfigError};
+use slide_puzzle::generate::{Difficulty, Target};
+use slide_puzzle::scene::Scene;
+use slide_puzzle::variant::AI_MAX_CELLS;
+use slide_puzzle::{
+    Cell, Geometry, SolverKind, State, Theme, validate_board_dimension, validate_board_size,
+};
+
+use classic::{GameArgs, initial_state};
+use klotski::{KlotskiArgs, run_klotski};
+use levels::{LevelsArgs, run_levels};
+use loopover::{LoopoverArgs, run_loopover};
+use menu::run_scenes;
+use race::{HostArgs, JoinArgs, RaceArgs, run_host, run_join, run_race};
+use replay::{ReplayArgs, run_replay};
+use terminal::run_tui;
+use tools::{AnalyzeArgs, BenchArgs, ExportArgs, run_analyze, run_bench, run_export};
+use variant::{run_variant, variant_state};
+
+#[cfg(feature = "memory-stats")]
+#[global_allocator]
+static ALLOCATOR: slide_puzzle::bench::TrackingAllocator = slide_puzzle::bench::TrackingAllocator;
+
+/// The settings in effect, set once at startup.
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+fn parse_board_dimension(s: &str) -> Result<usize, String> {
+    let value = s
+        .parse::<usize>()
+        .map_err(|e| format!("`{}` is not a number: {}", s, e))?;
+    validate_board_dimension(value).map_err(|e| e.to_string())
+}
+
+/// Parses `ROWSxCOLS`, e.g. `3x3`.
+fn parse_board_size(s: &str) -> Result<(usize, usize), String> {
+    let (rows, cols) = s
+        .split_once('x')
+        .ok_or_else(|| format!("`{}` is not of the form ROWSxCOLS", s))?;
+    let rows = parse_board_dimension(rows)?;
+    let cols = parse_board_dimension(cols)?;
+    validate_board_size(rows, cols).map_err(|e| e.to_string())?;
+    Ok((rows, cols))
+}
+
+fn parse_solver_kind(s: &str) -> Result<SolverKind, String> {
+    SolverKind::from_name(s).ok_or_else(|| {
+        let names = SolverKind::ALL.map(|kind| kind.name()).join(", ");
+        format!("unknown solver `{}` (available: {})", s, names)
+    })
+}
+
+fn parse_theme(s: &str) -> Result<Theme, String> {
+    Theme::from_name(s).ok_or_else(|| {
+        let names = Theme::ALL.map(|theme| theme.name).join(", ");
+        format!("unknown theme `{}` (available: {})", s, names)
+    })
+}
+
+/// Parses `WIDTHxHEIGHT`, e.g. `1280x720`.
+fn parse_window_size(s: &str) -> Result<(i32, i32), String> {
+    let invalid = || format!("`{}` is not of the form WIDTHxHEIGHT", s);
+    let (width, height) = s.split_once('x').ok_or_else(invalid)?;
+    let width = width.parse::<i32>().map_err(|_| invalid())?;
+    let height = height.parse::<i32>().map_err(|_| invalid())?;
+    if width <= 0 || height <= 0 {
+        return Err(format!("window size `{}` must be positive", s));
+    }
+    Ok((width, height))
+}
+
+fn parse_geometry(s: &str) -> Result<Geometry, String> {
+    Geometry::from_name(s).ok_or_else(|| {
+        let names = Geometry::ALL.map(|g| g.name()).join(", ");
+        format!("unknown grid `{}` (available: {})", s, names)
+    })
+}
+
+/// Parses `X,Y`, e.g. `1,2`.
+fn parse_cell(s: &str) -> Result<Cell, String> {
+    let invalid = || format!("`{}` is not of the form X,Y", s);
+    let (x, y) = s.split_once(',').ok_or_else(invalid)?;
+    let x = x.trim().parse::<usize>().map_err(|_| invalid())?;
+    let y = y.trim().parse::<usize>().map_err(|_| invalid())?;
+    Ok(Cell::new(x, y))
+}
+
+fn parse_target(s: &str) -> Result<Target, String> {
+    if let Some(difficulty) = Difficulty::from_name(s) {
+        return Ok(Target::Tier(difficulty));
+    }
+    s.parse::<usize>().map(Target::Length).map_err(|_| {
+        let names = Difficulty::ALL.map(|d| d.name()).join(", ");
+        format!("expected one of {} or a number of moves", names)
+    })
+}
+
+fn parse_speed(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("`{}` is not a positive number", s)),
+    }
+}
+
+#[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Read the settings from this file instead of config.toml in the config
+    /// directory
+    #[clap(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Color theme: light or dark
+    #[clap(long, global = true, value_parser = parse_theme)]
+    theme: Option<Theme>,
+
+    /// Moves per second of the AI and other automatic moves
+    #[clap(long, global = true, value_parser = parse_speed)]
+    ai_speed: Option<f64>,
+
+    /// Window size as WIDTHxHEIGHT
+    #[clap(long, global = true, value_parser = parse_window_size)]
+    window: Option<(i32, i32)>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Open the main menu, as running without arguments does
+    Menu,
+    /// Run every solver on a set of positions and report the results
+    Bench(BenchArgs),
+    /// Enumerate every position of a small board and report its distances
+    Analyze(AnalyzeArgs),
+    /// Play back a recorded game
+    Replay(ReplayArgs),
+    /// Render a solution as an animated GIF or numbered PNG frames
+    Export(ExportArgs),
+    /// Play a Klotski (Huarong Dao) level with blocks of several cells
+    Klotski(KlotskiArgs),
+    /// Play Loopover, where moves rotate whole rows and columns
+    Loopover(LoopoverArgs),
+    /// Play through a level pack, unlocking each level by finishing the one
+    /// before it
+    Levels(LevelsArgs),
+    /// Race a second player, or the AI, on the same position side by side
+    Race(RaceArgs),
+    /// Host a race over the local network and wait for a player to join
+    Host(HostArgs),
+    /// Join a race hosted over the local network
+    Join(JoinArgs),
+    /// Print the settings in effect, as a config file
+    Config,
+}
+
+/// The solver the AI plays `state` with: the configured one if it can handle
+/// the board.
+fn ai_solver(state: &State) -> SolverKind {
+    match config().solver {
+        Some(kind) if kind.supports(state.rows(), state.cols()) => kind,
+        Some(kind) => {
+            eprintln!(
+                "the {} solver can't solve a {}x{} board, using the {} solver",
+                kind.name(),
+                state.rows(),
+                state.cols(),
+                SolverKind::default_for(state).name()
+            );
+            SolverKind::default_for(state)
+        }
+        None => SolverKind::default_for(state),
+    }
+}
+
+/// Reads the config file and applies the overrides given on the command
+/// line. Exits if the file can't be used.
+fn load_config(cli: &Cli, matches: &clap::ArgMatches) -> Config {
+    let loaded = match &cli.config {
+        // a file asked for by name has to be there
+        Some(path) => fs::read_to_string(path)
+            .map_err(ConfigError::from)
+            .and_then(|text| Config::parse(&text))
+            .map_err(|e| (path.clone(), e)),
+        None => match Config::default_path() {
+            Some(path) => Config::load(&path).map_err(|e| (path, e)),
+            None => Ok(Config::default()),
+        },
+    };
+    let mut config = loaded.unwrap_or_else(|(path, e)| {
+        eprintln!("error: {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+
+    let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    if given("rows") {
+        config.board.rows = cli.game.rows;
+    }
+    if given("cols") {
+        config.board.cols = cli.game.cols;
+    }
+    if let Some(theme) = cli.theme {
+        config.theme = theme;
+    }
+    if let Some(solver) = cli.game.solver {
+        config.solver = Some(solver);
+    }
+    if let Some(speed) = cli.ai_speed {
+        config.animation.moves_per_second = speed;
+    }
+    if let Some((width, height)) = cli.window {
+        config.window.width = width;
+        config.window.height = height;
+    }
+    config
+}
+
+fn run_config(path: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    match path.map(Path::to_path_buf).or_else(Config::default_path) {
+        Some(path) => println!("# {}", path.display()),
+        None => println!("# no config directory"),
+    }
+    print!("{}", config().to_toml());
+    Ok(())
+}
+
+fn main() {
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // with no arguments at all the game opens on the main menu
+    if std::env::args_os().len() == 1 {
+        cli.command = Some(Command::Menu);
+    }
+    let config = load_config(&cli, &matches);
+    // a board size not given on the command line comes from the config
+    let mut game = cli.game;
+    game.rows = config.board.rows;
+    game.cols = config.board.cols;
+    CONFIG.set(config).expect("the config is only set once");
+
+    if let Some(command) = cli.command {
+        let result = match command {
+            Command::Menu => {
+                macroquad::Window::from_config(window_conf(), run_scenes(Scene::MainMenu, None));
+                Ok(())
+            }
+            Command::Bench(args) => run_bench(args),
+            Command::Analyze(args) => run_analyze(args),
+            Command::Replay(args) => run_replay(args),
+            Command::Export(args) => run_export(args),
+            Command::Klotski(args) => run_klotski(args),
+            Command::Loopover(args) => run_loopover(args),
+            Command::Levels(args) => run_levels(args),
+            Command::Race(args) => run_race(args),
+            Command::Host(args) => run_host(args),
+            Command::Join(args) => run_join(args),
+            Command::Config => run_config(cli.config.as_deref()),
+        };
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Err(e) = validate_board_size(game.rows, game.cols) {
+        Cli::command().error(ErrorKind::ValueValidation, e).exit();
+    }
+
+    // arguments are validated before the window is opened so that errors are
+    // reported even when no display is available
+    if !game.walls.is_empty() || game.blanks != 1 || game.wrap || game.grid != Geometry::Square {
+        let state = match variant_state(&game) {
+            Ok(state) => state,
+            Err(e) => Cli::command().error(ErrorKind::ValueValidation, e).exit(),
+        };
+        if game.ai && state.open_cells() > AI_MAX_CELLS {
+            let message = format!(
+                "--ai plays boards of up to {} open cells, this one has {}",
+                AI_MAX_CELLS,
+                state.open_cells()
+            );
+            Cli::command()
+                .error(ErrorKind::ArgumentConflict, message)
+                .exit();
+        }
+        macroquad::Window::from_config(window_conf(), run_variant(game, state));
+        return;
+    }
+
+    let start = match initial_state(&game) {
+        Ok(start) => start,
+        Err(e) => Cli::command().error(ErrorKind::ValueValidation, e).exit(),
+    };
+    if game.tui {
+        if let Err(e) = run_tui(game, start) {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+    let scene = Scene::Game(game.setup());
+    macroquad::Window::from_config(window_conf(), run_scenes(scene, Some((game, start))));
+}
+
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "Slide Puzzle".to_string(),
+        window_width: config().window.width,
+        window_height: config().window.height,
+        window_resizable: config().window.resizable,
+        fullscreen: config().window.fullscreen,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,98 @@
+//! The main menu and the screens it leads to.
+
+use std::mem;
+
+use macroquad::prelude::*;
+
+use slide_puzzle::MacroquadRenderer;
+use slide_puzzle::levels::LevelPack;
+use slide_puzzle::race::Race;
+use slide_puzzle::scene::{GameEnd, Mode, Scene};
+
+use crate::classic::{GameArgs, Start, initial_state, run};
+use crate::config;
+use crate::levels::{load_progress, play_levels};
+use crate::race::{play_race, race_start};
+use crate::ui::{menu_input, menu_origin};
+
+/// Shows the menu of `scene` until something is picked, and returns the
+/// screen it leads to.
+async fn show_menu(mut scene: Scene) -> Scene {
+    let mut cursor = 0;
+    while let Some(mut menu) = scene.menu() {
+        clear_background(config().theme.background);
+        menu.cursor = cursor.min(menu.items.len() - 1);
+        let origin = menu_origin(&menu);
+        let choice = menu_input(&mut menu, origin);
+        menu.draw(&mut MacroquadRenderer, origin, &config().theme);
+        cursor = menu.cursor;
+
+        // left and right step the size picker
+        if config().keys.left.is_pressed() {
+            scene = scene.adjust(menu.selected(), -1);
+        }
+        if config().keys.right.is_pressed() {
+            scene = scene.adjust(menu.selected(), 1);
+        }
+        if let Some(choice) = choice {
+            let next = scene.clone().choose(choice);
+            // choices that change the size stay on the picker
+            if mem::discriminant(&next) != mem::discriminant(&scene) {
+                return next;
+            }
+            scene = next;
+        }
+        next_frame().await;
+    }
+    scene
+}
+
+/// Moves between the menus and the games picked in them, starting at
+/// `scene`. `first` is a game already set up from the command line.
+pub async fn run_scenes(mut scene: Scene, mut first: Option<(GameArgs, Start)>) {
+    simulate_mouse_with_touch(false);
+    loop {
+        scene = match scene {
+            Scene::Quit => break,
+            Scene::Game(setup) if setup.mode == Mode::Race => {
+                let race = Race::new(race_start(setup.rows, setup.cols, None));
+                Scene::after_game(setup, play_race(race, false).await)
+            }
+            Scene::Game(setup) => {
+                let game = first.take().map_or_else(
+                    || {
+                        let args = GameArgs::from_setup(setup);
+                        initial_state(&args).map(|start| (args, start))
+                    },
+                    Ok,
+                );
+                match game {
+                    Ok((args, start)) => Scene::after_game(setup, run(args, start).await),
+                    Err(e) => {
+                        eprintln!("error: {}", e);
+                        Scene::MainMenu
+                    }
+                }
+            }
+            Scene::Levels => {
+                let (progress, path) = load_progress();
+                match play_levels(LevelPack::builtin(), progress, path, None).await {
+                    GameEnd::Quit => Scene::Quit,
+                    _ => Scene::MainMenu,
+                }
+            }
+            scene => match show_menu(scene).await {
+                // the size picker starts at the configured size
+                Scene::SizePicker { mode, .. } => Scene::SizePicker {
+                    mode,
+                    rows: config().board.rows,
+                    cols: config().board.cols,
+                },
+                next => next,
+            },
+        };
+        // every screen starts on a fresh frame, so the key or click that
+        // opened it isn't handled twice
+        next_frame().await;
+    }
+}
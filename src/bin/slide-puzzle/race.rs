@@ -0,0 +1,459 @@
+//! Races on a split screen and over the network.
+
+use std::error::Error;
+
+use ::rand::SeedableRng;
+use ::rand::rngs::StdRng;
+
+use clap::Args;
+use macroquad::prelude::*;
+
+use slide_puzzle::net::{self, Connection, Host, Message, RaceStart};
+use slide_puzzle::race::{self, Outcome, Race};
+use slide_puzzle::scene::{Choice, GameEnd, Menu, Scene};
+use slide_puzzle::{
+    Board, Direction, Drag, Layout, LayoutConfig, MacroquadRenderer, State, drag_board,
+    pointer_events,
+};
+
+use crate::menu::run_scenes;
+use crate::ui::{
+    Pacer, continue_pressed, draw_continue_hint, draw_panel_board, draw_panel_lines, leave,
+    new_board, pause_overlay, quit_requested, status_text, update_layout,
+};
+use crate::{ai_solver, config, parse_board_size, window_conf};
+
+#[derive(Debug, Args)]
+pub struct RaceArgs {
+    /// Board size, e.g. `4x4` (default: the configured size)
+    #[arg(value_parser = parse_board_size)]
+    size: Option<(usize, usize)>,
+
+    /// Seed for the shuffled position, to race it again later
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Race against the solver, which takes the left board
+    #[arg(long)]
+    ai: bool,
+}
+
+pub fn run_race(args: RaceArgs) -> Result<(), Box<dyn Error>> {
+    let (rows, cols) = args
+        .size
+        .unwrap_or((config().board.rows, config().board.cols));
+    let race = Race::new(race_start(rows, cols, args.seed));
+    macroquad::Window::from_config(window_conf(), async move {
+        if play_race(race, args.ai).await == GameEnd::Menu {
+            run_scenes(Scene::MainMenu, None).await;
+        }
+    });
+    Ok(())
+}
+
+/// The position both players of a race start from.
+pub fn race_start(rows: usize, cols: usize, seed: Option<u64>) -> State {
+    let seed = seed.unwrap_or_else(::rand::random);
+    println!("race seed {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut state = State::new(rows, cols);
+    // a tiny board can shuffle back to the solved one
+    while state.is_finished() {
+        state.shuffle_with(&mut rng);
+    }
+    state
+}
+
+#[derive(Debug, Args)]
+pub struct HostArgs {
+    /// Board size, e.g. `4x4` (default: the configured size)
+    #[arg(value_parser = parse_board_size)]
+    size: Option<(usize, usize)>,
+
+    /// Seed for the shuffled position
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Port to wait for the other player on
+    #[arg(long, default_value_t = net::DEFAULT_PORT)]
+    port: u16,
+}
+
+pub fn run_host(args: HostArgs) -> Result<(), Box<dyn Error>> {
+    let (rows, cols) = args
+        .size
+        .unwrap_or((config().board.rows, config().board.cols));
+    let host = Host::bind(("0.0.0.0", args.port))?;
+    let seed = args.seed.unwrap_or_else(::rand::random);
+    let start = RaceStart {
+        state: race_start(rows, cols, Some(seed)),
+        seed,
+    };
+    println!(
+        "hosting a {}x{} race on port {}, waiting for a player to join",
+        rows,
+        cols,
+        host.local_addr()?.port()
+    );
+    let connection = host.accept(&start)?;
+    println!("{} joined", connection.peer_addr()?);
+    open_online_race(connection, start);
+    Ok(())
+}
+
+#[derive(Debug, Args)]
+pub struct JoinArgs {
+    /// Address of the host, as HOST or HOST:PORT
+    address: String,
+}
+
+pub fn run_join(args: JoinArgs) -> Result<(), Box<dyn Error>> {
+    let address = if args.address.contains(':') {
+        args.address
+    } else {
+        format!("{}:{}", args.address, net::DEFAULT_PORT)
+    };
+    let (connection, start) =
+        net::join(address.as_str()).map_err(|e| format!("could not join {}: {}", address, e))?;
+    println!(
+        "joined a {}x{} race (seed {})",
+        start.state.rows(),
+        start.state.cols(),
+        start.seed
+    );
+    open_online_race(connection, start);
+    Ok(())
+}
+
+fn open_online_race(connection: Connection, start: RaceStart) {
+    macroquad::Window::from_config(window_conf(), async move {
+        if play_online(connection, start).await == GameEnd::Menu {
+            run_scenes(Scene::MainMenu, None).await;
+        }
+    });
+}
+
+/// Keys of the player on the left board; the one on the right plays with the
+/// configured arrow keys and the mouse.
+const LEFT_PLAYER_KEYS: [(KeyCode, Direction); 4] = [
+    (KeyCode::W, Direction::Up),
+    (KeyCode::S, Direction::Down),
+    (KeyCode::A, Direction::Left),
+    (KeyCode::D, Direction::Right),
+];
+
+/// Height kept free below the boards of a race for the key hints.
+const RACE_FOOTER: f32 = 40.0;
+
+/// The two halves of the window, each with a header and a board.
+fn race_layouts(rows: usize, cols: usize) -> [Layout; 2] {
+    let half = vec2(screen_width() / 2.0, screen_height() - RACE_FOOTER);
+    let layout_config = LayoutConfig {
+        side_panel_width: 0.0,
+        ..LayoutConfig::default()
+    };
+    [0.0, half.x].map(|shift| {
+        let mut layout = Layout::compute(half, rows, cols, &layout_config);
+        for rect in [
+            &mut layout.header,
+            &mut layout.board,
+            &mut layout.left_panel,
+            &mut layout.right_panel,
+        ] {
+            rect.x += shift;
+        }
+        layout
+    })
+}
+
+/// Draws both boards of `race`, with each player's moves and time above
+/// them. `seconds` is the time into the race.
+fn draw_race(
+    race: &Race,
+    boards: &mut [Board; 2],
+    layouts: &[Layout; 2],
+    names: [&str; 2],
+    seconds: f64,
+) {
+    let outcome = race.outcome();
+    for (player, board) in boards.iter_mut().enumerate() {
+        let racer = race.racer(player);
+        let layout = &layouts[player];
+        let seconds = racer.finished_ms.map_or(seconds, |ms| ms as f64 / 1000.0);
+        let status = status_text(racer.moves, seconds);
+        let (text, color) = match outcome {
+            Some(Outcome::Winner(winner)) if winner == player => {
+                (format!("{}  WINS  {}", names[player], status), RED)
+            }
+            Some(Outcome::Draw) => (format!("{}  DRAW  {}", names[player], status), RED),
+            _ => (
+                format!("{}  {}", names[player], status),
+                config().theme.accent,
+            ),
+        };
+        draw_text(&text, layout.header.x, layout.header.bottom(), 40.0, color);
+
+        board.apply_state(&racer.state);
+        board.draw(&mut MacroquadRenderer, layout.board.point());
+    }
+}
+
+/// Plays a race on a split screen until it is left. With `ai` the solver
+/// plays the left board at the configured speed.
+pub async fn play_race(mut race: Race, ai: bool) -> GameEnd {
+    let (rows, cols) = (race.start().rows(), race.start().cols());
+    let mut boards = [new_board(rows, cols), new_board(rows, cols)];
+    let names = [if ai { "AI" } else { "WASD" }, "arrows / mouse"];
+    let solution = if ai {
+        ai_solver(race.start()).build(race.start().clone()).solve()
+    } else {
+        vec![]
+    };
+
+    simulate_mouse_with_touch(false);
+    let mut solver_moves = solution.clone().into_iter();
+    let mut pacer = Pacer::new(1.0);
+    // time into the race; it stops while paused and once someone has won
+    let mut seconds = 0.0;
+    let mut drag: Option<Drag> = None;
+    let mut paused: Option<Menu> = None;
+    loop {
+        clear_background(config().theme.background);
+        let layouts = race_layouts(rows, cols);
+        for (board, layout) in boards.iter_mut().zip(&layouts) {
+            board.resize(layout.board.size());
+        }
+        let keys = &config().keys;
+        if quit_requested() {
+            return GameEnd::Quit;
+        }
+
+        let over = race.outcome().is_some();
+        let hint = if over {
+            format!(
+                "{}: rematch   {} or click: menu   {}: quit",
+                keys.restart, keys.confirm, keys.quit
+            )
+        } else {
+            let left = if ai { "" } else { "WASD: left board   " };
+            format!(
+                "{}{} / {} / {} / {} or drag: right board   {}: pause   {}: quit",
+                left, keys.up, keys.down, keys.left, keys.right, keys.pause, keys.quit
+            )
+        };
+        draw_text(
+            &hint,
+            LayoutConfig::default().margin,
+            screen_height() - RACE_FOOTER / 2.0,
+            28.0,
+            config().theme.muted,
+        );
+
+        let mut restart = false;
+        if let Some(menu) = paused.as_mut() {
+            draw_race(&race, &mut boards, &layouts, names, seconds);
+            match pause_overlay(menu) {
+                Some(Choice::Resume) => paused = None,
+                Some(Choice::Restart) => restart = true,
+                Some(choice @ (Choice::MainMenu | Choice::Quit)) => return leave(choice),
+                _ => {}
+            }
+        } else if over {
+            if keys.restart.is_pressed() {
+                restart = true;
+            } else if continue_pressed() {
+                return GameEnd::Menu;
+            }
+        } else {
+            if keys.pause.is_pressed() {
+                paused = Some(Menu::pause());
+                drag = None;
+            }
+
+            let ms = (seconds * 1000.0) as u64;
+            if ai {
+                if pacer.tick()
+                    && let Some(cell) = solver_moves.next()
+                {
+                    race.slide(0, cell, ms);
+                }
+            } else {
+                for (key, direction) in LEFT_PLAYER_KEYS {
+                    if is_key_pressed(key) {
+                        race.push(0, direction, ms);
+                    }
+                }
+            }
+            let right_keys = [
+                (keys.up, Direction::Up),
+                (keys.down, Direction::Down),
+                (keys.left, Direction::Left),
+                (keys.right, Direction::Right),
+            ];
+            for (key, direction) in right_keys {
+                if key.is_pressed() {
+                    race.push(1, direction, ms);
+                }
+            }
+
+            let origin = layouts[1].board.point();
+            let blank = race.racer(1).state.blank_cell();
+            let events = pointer_events();
+            if let Some(cell) = drag_board(&mut drag, &events, &mut boards[1], origin, blank) {
+                race.slide(1, cell, ms);
+            }
+            if race.outcome().is_none() {
+                seconds += get_frame_time() as f64;
+            }
+        }
+
+        if restart {
+            race.restart();
+            solver_moves = solution.clone().into_iter();
+            pacer = Pacer::new(1.0);
+            seconds = 0.0;
+            drag = None;
+            paused = None;
+        }
+        if paused.is_none() {
+            boards[1].show_drag(drag.as_ref());
+            draw_race(&race, &mut boards, &layouts, names, seconds);
+        }
+
+        next_frame().await
+    }
+}
+
+/// Plays a race against a player on the network: this player's board in the
+/// middle, the other's in the right panel.
+async fn play_online(mut connection: Connection, start: RaceStart) -> GameEnd {
+    let (rows, cols) = (start.state.rows(), start.state.cols());
+    let mut race = Race::new(start.state);
+    let mut board = new_board(rows, cols);
+    let mut opponent = Board::new(vec2(1.0, 1.0), config().board.border / 2.0, (rows, cols));
+    opponent.set_theme(config().theme);
+
+    simulate_mouse_with_touch(false);
+    // time into the race; it stops once the race is decided
+    let mut seconds = 0.0;
+    let mut drag: Option<Drag> = None;
+    // why the other player is gone, if they are
+    let mut gone: Option<String> = None;
+    loop {
+        clear_background(config().theme.background);
+        let layout = update_layout(&mut board, rows, cols);
+        let board_relative_path = layout.board.point();
+        let keys = &config().keys;
+
+        let quit = quit_requested();
+        if quit || keys.pause.is_pressed() {
+            // the other side finds out when the connection closes, if the
+            // message doesn't make it
+            let _ = connection.send(&Message::Leave);
+            return if quit { GameEnd::Quit } else { GameEnd::Menu };
+        }
+
+        match connection.poll() {
+            Ok(messages) => {
+                for message in messages {
+                    match message {
+                        Message::Slide { cell, ms } if cell.x < cols && cell.y < rows => {
+                            race.slide(1, cell, ms);
+                        }
+                        Message::Leave => gone = Some("the other player left".to_string()),
+                        message => {
+                            gone = Some(format!("the other player sent {:?}", message));
+                        }
+                    }
+                }
+            }
+            Err(e) => gone = Some(format!("connection lost: {}", e)),
+        }
+
+        let ms = (seconds * 1000.0) as u64;
+        let mut slides = vec![];
+        if race.outcome().is_none() && gone.is_none() {
+            let directions = [
+                (keys.up, Direction::Up),
+                (keys.down, Direction::Down),
+                (keys.left, Direction::Left),
+                (keys.right, Direction::Right),
+            ];
+            for (key, direction) in directions {
+                if key.is_pressed() {
+                    slides.extend(race::tile_toward(&race.racer(0).state, direction));
+                }
+            }
+            let blank = race.racer(0).state.blank_cell();
+            let events = pointer_events();
+            slides.extend(drag_board(
+                &mut drag,
+                &events,
+                &mut board,
+                board_relative_path,
+                blank,
+            ));
+        } else {
+            drag = None;
+        }
+        for cell in slides {
+            if race.slide(0, cell, ms) > 0
+                && let Err(e) = connection.send(&Message::Slide { cell, ms })
+            {
+                gone = Some(format!("connection lost: {}", e));
+            }
+        }
+
+        let me = race.racer(0);
+        let status = status_text(
+            me.moves,
+            me.finished_ms.map_or(seconds, |ms| ms as f64 / 1000.0),
+        );
+        let (header, color) = match (race.outcome(), &gone) {
+            (Some(Outcome::Winner(0)), _) => (format!("YOU WIN  {}", status), RED),
+            (Some(Outcome::Winner(_)), _) => (format!("YOU LOSE  {}", status), RED),
+            (Some(Outcome::Draw), _) => (format!("DRAW  {}", status), RED),
+            (None, Some(reason)) => (format!("{}  {}", reason, status), RED),
+            (None, None) => (status, config().theme.accent),
+        };
+        draw_text(
+            &header,
+            layout.header.x,
+            layout.header.bottom(),
+            40.0,
+            color,
+        );
+
+        let them = race.racer(1);
+        let mut lines = vec![
+            "drag or arrows: slide".to_string(),
+            format!("{}: leave", keys.pause),
+            format!("{}: quit", keys.quit),
+            String::new(),
+            "opponent:".to_string(),
+            status_text(
+                them.moves,
+                them.finished_ms.map_or(seconds, |ms| ms as f64 / 1000.0),
+            ),
+        ];
+        if race.outcome().is_some() || gone.is_some() {
+            lines.insert(0, format!("{} or click: menu", keys.confirm));
+            draw_continue_hint(&layout);
+            if continue_pressed() {
+                let _ = connection.send(&Message::Leave);
+                return GameEnd::Menu;
+            }
+        } else {
+            seconds += get_frame_time() as f64;
+        }
+        draw_panel_lines(&layout, &lines);
+        opponent.apply_state(&them.state);
+        draw_panel_board(&layout, lines.len(), &mut opponent, rows, cols);
+
+        board.apply_state(&race.racer(0).state);
+        board.show_drag(drag.as_ref());
+        board.draw(&mut MacroquadRenderer, board_relative_path);
+
+        next_frame().await
+    }
+}
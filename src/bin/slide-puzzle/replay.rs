@@ -0,0 +1,133 @@
+//! The `replay` subcommand: watching a recorded game.
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Args;
+use macroquad::prelude::*;
+
+use slide_puzzle::MacroquadRenderer;
+use slide_puzzle::replay::Replay;
+
+use crate::ui::{draw_panel_lines, new_board, quit_requested, status_text, update_layout};
+use crate::{config, parse_speed, window_conf};
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    /// Replay file, e.g. one from the replays directory
+    path: PathBuf,
+
+    /// Playback speed relative to the recorded game
+    #[arg(long, default_value_t = 1.0, value_parser = parse_speed)]
+    speed: f64,
+}
+
+pub fn run_replay(args: ReplayArgs) -> Result<(), Box<dyn Error>> {
+    let replay = Replay::load(&args.path).map_err(|e| format!("{}: {}", args.path.display(), e))?;
+    macroquad::Window::from_config(window_conf(), play_replay(replay, args.speed));
+    Ok(())
+}
+
+/// Shows a recorded game, moving at the recorded pace scaled by `speed`.
+async fn play_replay(replay: Replay, mut speed: f64) {
+    let keys = &config().keys;
+    let controls = [
+        "space: pause / resume".to_string(),
+        format!("{} / {}: step", keys.left, keys.right),
+        format!("{} / {}: speed", keys.up, keys.down),
+        format!("{}: restart", keys.restart),
+        format!("{}: quit", keys.quit),
+    ];
+
+    let mut state = replay.state_at(0).unwrap();
+    let mut board = new_board(replay.rows, replay.cols);
+
+    // `position` moves of the replay have been applied; `clock` is the time
+    // of the recorded game being shown, in milliseconds
+    let mut position = 0;
+    let mut clock = 0.0;
+    let mut paused = false;
+    loop {
+        clear_background(config().theme.background);
+        let layout = update_layout(&mut board, state.rows(), state.cols());
+
+        if quit_requested() {
+            break;
+        }
+        if is_key_pressed(KeyCode::Space) {
+            paused = !paused;
+        }
+        if config().keys.up.is_pressed() {
+            speed = (speed * 2.0).min(64.0);
+        }
+        if config().keys.down.is_pressed() {
+            speed = (speed / 2.0).max(1.0 / 64.0);
+        }
+        if config().keys.restart.is_pressed() {
+            position = 0;
+            clock = 0.0;
+            state = replay.state_at(0).unwrap();
+        }
+        if config().keys.right.is_pressed() && position < replay.moves.len() {
+            paused = true;
+            state.try_move(replay.moves[position].cell);
+            position += 1;
+            clock = replay.moves[position - 1].time_ms as f64;
+        }
+        if config().keys.left.is_pressed() && position > 0 {
+            paused = true;
+            position -= 1;
+            state = replay.state_at(position).unwrap();
+            clock = position
+                .checked_sub(1)
+                .map_or(0.0, |i| replay.moves[i].time_ms as f64);
+        }
+
+        if !paused {
+            clock += get_frame_time() as f64 * 1000.0 * speed;
+            while position < replay.moves.len() && replay.moves[position].time_ms as f64 <= clock {
+                state.try_move(replay.moves[position].cell);
+                position += 1;
+            }
+        }
+
+        let seconds = (clock / 1000.0) as u64;
+        let mut txt = format!(
+            "REPLAY  move {}/{}  {}:{:02}  x{}",
+            position,
+            replay.moves.len(),
+            seconds / 60,
+            seconds % 60,
+            speed
+        );
+        if paused {
+            txt += "  (paused)";
+        }
+        draw_text(
+            &txt,
+            layout.header.x,
+            layout.header.bottom(),
+            40.0,
+            config().theme.accent,
+        );
+
+        let mut lines = controls.to_vec();
+        if let Some(result) = &replay.result {
+            lines.push(String::new());
+            lines.push(format!(
+                "{}  {}",
+                if result.solved { "solved" } else { "gave up" },
+                status_text(result.moves, result.seconds)
+            ));
+        }
+        if let Some(seed) = replay.seed {
+            lines.push(format!("seed {}", seed));
+        }
+        draw_panel_lines(&layout, &lines);
+
+        board.apply_state(&state);
+        board.draw(&mut MacroquadRenderer, layout.board.point());
+
+        next_frame().await
+    }
+}
@@ -0,0 +1,103 @@
+//! The classic game in the terminal, for `--tui`.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use slide_puzzle::Cell;
+use slide_puzzle::tui;
+
+use crate::classic::{GameArgs, Recording, Start, finish_daily};
+use crate::ui::status_text;
+use crate::{ai_solver, config};
+
+/// Plays in the terminal. With `--ai` the solver's moves are shown one at a
+/// time instead.
+pub fn run_tui(args: GameArgs, start: Start) -> io::Result<()> {
+    const CONTROLS: &str = "arrows/WASD/hjkl: slide  u: undo  q: quit";
+
+    let Start {
+        mut state,
+        seed,
+        daily,
+    } = start;
+    let mut recording = Recording::new(&args, &state, seed);
+
+    let ai_step = Duration::from_secs_f64(1.0 / config().animation.moves_per_second);
+    let mut solution = if args.ai {
+        ai_solver(&state).build(state.clone()).solve().into_iter()
+    } else {
+        vec![].into_iter()
+    };
+    // the blank cell before each move, for undo; undoing counts as a move
+    let mut history: Vec<Cell> = vec![];
+    let mut finished: Option<(f64, Option<String>)> = None;
+    let mut last_step = Instant::now();
+
+    let mut terminal = tui::Terminal::enter()?;
+    loop {
+        if state.is_finished() && finished.is_none() {
+            let seconds = recording.seconds();
+            let share = daily
+                .as_ref()
+                .map(|game| finish_daily(game, &state, recording.moves(), seconds));
+            finished = Some((seconds, share));
+        }
+
+        let mut text = match &finished {
+            Some((seconds, _)) => {
+                format!("GAME CLEAR  {}\n", status_text(recording.moves(), *seconds))
+            }
+            None => format!("{}\n", status_text(recording.moves(), recording.seconds())),
+        };
+        text += &tui::render(&state, true);
+        text += CONTROLS;
+        if let Some((_, Some(share))) = &finished {
+            text += "\n\n";
+            text += share;
+        }
+        terminal.draw(&text)?;
+
+        let key = terminal.next_key(Duration::from_millis(100))?;
+        match key.and_then(|key| tui::key_action(&state, key)) {
+            Some(tui::Action::Quit) => break,
+            _ if args.ai || finished.is_some() => {}
+            Some(tui::Action::Slide(cell)) => {
+                let blank = state.blank_cell();
+                if state.try_move(cell) {
+                    history.push(blank);
+                    recording.push(cell);
+                }
+            }
+            Some(tui::Action::Undo) => {
+                if let Some(blank) = history.pop() {
+                    state.try_move(blank);
+                    recording.push(blank);
+                }
+            }
+            None => {}
+        }
+
+        if args.ai && last_step.elapsed() >= ai_step {
+            last_step = Instant::now();
+            if let Some(mv) = solution.next() {
+                let blank = state.blank_cell();
+                state.try_move(mv);
+                history.push(blank);
+                recording.push(mv);
+            }
+        }
+    }
+    drop(terminal);
+
+    // messages are printed once the terminal is back to normal
+    match &finished {
+        Some((seconds, share)) => {
+            recording.finish(true, *seconds);
+            if let Some(share) = share {
+                println!("{}", share);
+            }
+        }
+        None => recording.finish(false, recording.seconds()),
+    }
+    Ok(())
+}
@@ -0,0 +1,250 @@
+//! Subcommands that print or write results instead of opening a window.
+
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use clap::Args;
+
+use slide_puzzle::analysis::DistanceTable;
+use slide_puzzle::bench;
+use slide_puzzle::export;
+use slide_puzzle::{SolverKind, State};
+
+use crate::{parse_board_size, parse_solver_kind};
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Board size, e.g. `3x3`
+    #[arg(value_parser = parse_board_size)]
+    size: (usize, usize),
+
+    /// Starting position: the tiles in row-major order, `0` being the blank
+    /// (default: a shuffled position)
+    #[arg(long, num_args = 1..)]
+    tiles: Vec<usize>,
+
+    /// Seed for the shuffled position
+    #[arg(long, conflicts_with = "tiles")]
+    seed: Option<u64>,
+
+    /// Solver to use (default: the one the game uses for this board)
+    #[arg(long, value_parser = parse_solver_kind)]
+    solver: Option<SolverKind>,
+
+    /// A `.gif` file, or a directory to write PNG frames to
+    #[arg(long, short)]
+    output: PathBuf,
+
+    /// Size of a cell in pixels
+    #[arg(long, default_value_t = 64)]
+    cell_size: u32,
+
+    /// Time each move is shown, in milliseconds
+    #[arg(long, default_value_t = 200)]
+    delay: u32,
+}
+
+pub fn run_export(args: ExportArgs) -> Result<(), Box<dyn Error>> {
+    let (rows, cols) = args.size;
+    let state = if args.tiles.is_empty() {
+        bench::random_instances(rows, cols, 1, args.seed.unwrap_or_else(::rand::random))
+            .remove(0)
+            .state
+    } else {
+        let state = State::from_numbers(rows, cols, &args.tiles)?;
+        if !state.is_solvable() {
+            return Err("the position is not solvable".into());
+        }
+        state
+    };
+
+    let kind = args
+        .solver
+        .unwrap_or_else(|| SolverKind::default_for(&state));
+    if !kind.supports(rows, cols) {
+        return Err(format!(
+            "solver `{}` does not support {}x{} boards",
+            kind.name(),
+            rows,
+            cols
+        )
+        .into());
+    }
+    let moves = kind.build(state.clone()).solve();
+
+    let mut end = state.clone();
+    if !moves.iter().all(|&mv| end.try_move(mv)) || !end.is_finished() {
+        return Err(format!(
+            "solver `{}` did not reach the goal; try another `--solver`",
+            kind.name()
+        )
+        .into());
+    }
+
+    let frames = export::solution_frames(&state, &moves, args.cell_size as f32);
+    let is_gif = args
+        .output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gif"));
+    if is_gif {
+        let mut w = BufWriter::new(File::create(&args.output)?);
+        export::write_gif(&frames, args.delay, &mut w)?;
+        w.flush()?;
+    } else {
+        export::write_png_frames(&frames, &args.output)?;
+    }
+    println!(
+        "{} moves ({} frames) written to {}",
+        moves.len(),
+        frames.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+#[derive(Debug, Args)]
+pub struct AnalyzeArgs {
+    /// Board size, e.g. `3x3` (at most 10 cells)
+    #[arg(value_parser = parse_board_size)]
+    size: (usize, usize),
+
+    /// Write the distance table to this file
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Number of antipodes to print
+    #[arg(long, default_value_t = 10)]
+    show: usize,
+}
+
+pub fn run_analyze(args: AnalyzeArgs) -> Result<(), Box<dyn Error>> {
+    let (rows, cols) = args.size;
+    let table = DistanceTable::build(rows, cols)?;
+
+    let histogram = table.histogram();
+    println!(
+        "{}x{} board: {} reachable positions",
+        rows,
+        cols,
+        table.reachable()
+    );
+    println!("{:>8} {:>10}", "distance", "positions");
+    for (distance, count) in histogram.iter().enumerate() {
+        println!("{:>8} {:>10}", distance, count);
+    }
+    println!("God's number: {}", table.max_distance());
+
+    let antipodes = table.antipodes();
+    println!("{} antipodes:", antipodes.len());
+    for state in antipodes.iter().take(args.show) {
+        let numbers = state
+            .to_numbers()
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>();
+        println!("  {}", numbers.join(" "));
+    }
+
+    if let Some(path) = &args.output {
+        table.save(path)?;
+        println!("distance table written to {}", path.display());
+    }
+    Ok(())
+}
+
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// Board sizes for random positions, e.g. `3x3`
+    #[arg(long = "size", value_parser = parse_board_size, default_values = ["3x3", "4x4"])]
+    sizes: Vec<(usize, usize)>,
+
+    /// Number of random positions per size
+    #[arg(long, default_value_t = 10)]
+    random: usize,
+
+    /// Seed for the random positions
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+
+    /// Extra corpus files, in the format of `assets/corpus/hardest-8.txt`
+    #[arg(long)]
+    corpus: Vec<PathBuf>,
+
+    /// Skip the built-in hardest 8-puzzle positions
+    #[arg(long)]
+    no_builtin: bool,
+
+    /// Also run Korf's 100 15-puzzle positions (slow)
+    #[arg(long)]
+    korf: bool,
+
+    /// Solvers to run (default: all)
+    #[arg(long = "solver", value_parser = parse_solver_kind)]
+    solvers: Vec<SolverKind>,
+
+    /// Write the report as CSV
+    #[arg(long)]
+    csv: Option<PathBuf>,
+
+    /// Write the report as JSON
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+pub fn run_bench(args: BenchArgs) -> Result<(), Box<dyn Error>> {
+    let mut instances = vec![];
+    if !args.no_builtin {
+        instances.extend(bench::hardest_8_puzzle());
+    }
+    if args.korf {
+        instances.extend(bench::korf_100());
+    }
+    for path in &args.corpus {
+        let text = fs::read_to_string(path)?;
+        let corpus =
+            bench::parse_corpus(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        instances.extend(corpus);
+    }
+    for &(rows, cols) in &args.sizes {
+        instances.extend(bench::random_instances(rows, cols, args.random, args.seed));
+    }
+
+    let kinds = if args.solvers.is_empty() {
+        SolverKind::ALL.to_vec()
+    } else {
+        args.solvers
+    };
+    let records = bench::run(&instances, &kinds);
+
+    println!(
+        "{:<28} {:<6} {:>6} {:>6} {:>5} {:>10} {:>12} {:>10}",
+        "instance", "solver", "solved", "length", "gap", "nodes", "memory", "time(ms)"
+    );
+    for r in &records {
+        println!(
+            "{:<28} {:<6} {:>6} {:>6} {:>5} {:>10} {:>12} {:>10.2}",
+            r.instance,
+            r.solver,
+            r.solved,
+            r.solution_length,
+            r.optimality_gap
+                .map(|g| g.to_string())
+                .unwrap_or("-".to_string()),
+            r.nodes_expanded,
+            r.peak_memory_bytes
+                .map(|m| m.to_string())
+                .unwrap_or("-".to_string()),
+            r.time_ms
+        );
+    }
+
+    if let Some(path) = &args.csv {
+        bench::write_csv(&records, BufWriter::new(File::create(path)?))?;
+    }
+    if let Some(path) = &args.json {
+        bench::write_json(&records, BufWriter::new(File::create(path)?))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,175 @@
+//! Drawing and input helpers shared by the game loops.
+
+use macroquad::prelude::*;
+
+use slide_puzzle::scene::{Choice, GameEnd, Menu};
+use slide_puzzle::{Board, Layout, LayoutConfig, MacroquadRenderer, PointerEvent, pointer_events};
+
+use crate::config;
+
+/// Recomputes the layout for the current window size and fits `board` to it.
+pub fn update_layout(board: &mut Board, rows: usize, cols: usize) -> Layout {
+    let layout = Layout::compute(
+        vec2(screen_width(), screen_height()),
+        rows,
+        cols,
+        &LayoutConfig::default(),
+    );
+    board.resize(layout.board.size());
+    layout
+}
+
+/// A board of `rows` x `cols` drawn with the configured border and theme.
+pub fn new_board(rows: usize, cols: usize) -> Board {
+    // the real size is set from the layout every frame
+    let mut board = Board::new(vec2(1.0, 1.0), config().board.border, (rows, cols));
+    board.set_theme(config().theme);
+    board
+}
+
+/// Paces automatic moves at the configured animation speed.
+pub struct Pacer {
+    interval: f32,
+    elapsed: f32,
+}
+
+impl Pacer {
+    /// `speed` scales the configured moves per second.
+    pub fn new(speed: f64) -> Self {
+        Pacer {
+            interval: (1.0 / (config().animation.moves_per_second * speed)) as f32,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Whether the next move is due this frame.
+    pub fn tick(&mut self) -> bool {
+        self.elapsed += get_frame_time();
+        if self.elapsed < self.interval {
+            return false;
+        }
+        // a slow frame doesn't make up for every move it missed
+        self.elapsed = (self.elapsed - self.interval).min(self.interval);
+        true
+    }
+}
+
+/// The quit key is held, or the window is being closed. Closing only shows
+/// up here once [`prevent_quit`] has been called, so a game can save first.
+pub fn quit_requested() -> bool {
+    config().keys.quit.is_down() || is_quit_requested()
+}
+
+/// Draws `menu` over the frame, centered, and handles its input.
+pub fn pause_overlay(menu: &mut Menu) -> Option<Choice> {
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color {
+            a: 0.8,
+            ..config().theme.background
+        },
+    );
+    let origin = menu_origin(menu);
+    let choice = menu_input(menu, origin);
+    menu.draw(&mut MacroquadRenderer, origin, &config().theme);
+    choice
+}
+
+/// Where `menu` is drawn: centered in the window.
+pub fn menu_origin(menu: &Menu) -> Vec2 {
+    ((vec2(screen_width(), screen_height()) - menu.size()) / 2.0).max(Vec2::ZERO)
+}
+
+/// Handles the keys and clicks of `menu` drawn at `origin`, returning the
+/// picked choice.
+pub fn menu_input(menu: &mut Menu, origin: Vec2) -> Option<Choice> {
+    if config().keys.up.is_pressed() {
+        menu.up();
+    }
+    if config().keys.down.is_pressed() {
+        menu.down();
+    }
+    if config().keys.confirm.is_pressed() {
+        return Some(menu.selected());
+    }
+    if config().keys.pause.is_pressed() {
+        return menu.escape();
+    }
+    if config().keys.quit.is_pressed() || is_quit_requested() {
+        return Some(Choice::Quit);
+    }
+    for event in pointer_events() {
+        if let PointerEvent::Pressed(pos) = event
+            && let Some(i) = menu.item_at(origin, pos)
+        {
+            menu.cursor = i;
+            return Some(menu.selected());
+        }
+    }
+    None
+}
+
+/// How a game is left from the pause menu.
+pub fn leave(choice: Choice) -> GameEnd {
+    if choice == Choice::Quit {
+        GameEnd::Quit
+    } else {
+        GameEnd::Menu
+    }
+}
+
+/// Lists `lines` down the right panel, one per text row.
+pub fn draw_panel_lines<S: AsRef<str>>(layout: &Layout, lines: &[S]) {
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(
+            line.as_ref(),
+            layout.right_panel.x,
+            layout.right_panel.y + 30.0 * (i + 1) as f32,
+            28.0,
+            config().theme.muted,
+        );
+    }
+}
+
+/// Draws a small `rows` x `cols` board in the right panel, below `lines`
+/// rows of text, if the panel is shown.
+pub fn draw_panel_board(
+    layout: &Layout,
+    lines: usize,
+    board: &mut Board,
+    rows: usize,
+    cols: usize,
+) {
+    if layout.right_panel.w > 0.0 {
+        let width = layout.right_panel.w.min(200.0);
+        board.resize(vec2(width, width * rows as f32 / cols as f32));
+        let top = layout.right_panel.y + 30.0 * lines as f32 + 15.0;
+        board.draw(&mut MacroquadRenderer, vec2(layout.right_panel.x, top));
+    }
+}
+
+/// Tells a player who finished how to get to the results.
+pub fn draw_continue_hint(layout: &Layout) {
+    draw_text(
+        "enter: results",
+        layout.right_panel.x,
+        layout.right_panel.bottom() - 30.0,
+        28.0,
+        config().theme.muted,
+    );
+}
+
+pub fn continue_pressed() -> bool {
+    config().keys.confirm.is_pressed()
+        || is_key_pressed(KeyCode::Space)
+        || is_mouse_button_pressed(MouseButton::Left)
+}
+
+/// Move counter and elapsed time for the header.
+pub fn status_text(moves: usize, seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{} moves  {}:{:02}", moves, seconds / 60, seconds % 60)
+}
@@ -0,0 +1,142 @@
+//! Boards with walls, several blanks, wrapping edges or other cell shapes.
+
+use std::thread;
+use std::time::Instant;
+
+use ::rand::SeedableRng;
+use ::rand::rngs::StdRng;
+
+use macroquad::prelude::*;
+
+use slide_puzzle::input::TAP_DISTANCE;
+use slide_puzzle::variant::{Move, NODE_LIMIT, Topology, VariantError, VariantState};
+use slide_puzzle::{Cell, MacroquadRenderer, PointerEvent, pointer_events};
+
+use crate::classic::GameArgs;
+use crate::config;
+use crate::ui::{Pacer, new_board, quit_requested, status_text, update_layout};
+
+/// The shuffled board of a game with walls or several blanks.
+pub fn variant_state(args: &GameArgs) -> Result<VariantState, VariantError> {
+    let topology = if args.wrap {
+        Topology::Torus
+    } else {
+        Topology::Flat
+    };
+    let mut state = VariantState::goal(args.rows, args.cols, &args.walls, args.blanks)?
+        .with_topology(topology)
+        .with_geometry(args.grid);
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut ::rand::rng()),
+    };
+    // boards where nothing can move stay solved
+    for _ in 0..10 {
+        if !state.is_finished() {
+            break;
+        }
+        state.shuffle_with(&mut rng);
+    }
+    Ok(state)
+}
+
+/// The move for a tile grabbed in `cell` and dragged by `delta`: into the
+/// blank in the drag direction, or into any neighbouring blank for a tap.
+fn variant_move(state: &VariantState, cell: Cell, delta: Vec2) -> Option<Move> {
+    let moves = state.moves().into_iter().filter(|mv| mv.from == cell);
+    if delta.length() < TAP_DISTANCE {
+        return moves.into_iter().next();
+    }
+    let along = |mv: &Move| state.direction(*mv).dot(delta);
+    moves
+        .filter(|mv| along(mv) > 0.0)
+        .max_by(|a, b| along(a).total_cmp(&along(b)))
+}
+
+/// Plays a board with walls or several blanks.
+pub async fn run_variant(args: GameArgs, mut state: VariantState) {
+    let mut board = new_board(state.rows(), state.cols());
+    board.set_wrap(state.topology() == Topology::Torus);
+    board.set_geometry(state.geometry());
+    // the search can take seconds, so the board is drawn meanwhile
+    let mut search = args.ai.then(|| {
+        let state = state.clone();
+        thread::spawn(move || state.solve_ai())
+    });
+    let mut solution = vec![].into_iter();
+
+    simulate_mouse_with_touch(false);
+    let mut pressed: Option<(Cell, Vec2)> = None;
+    let mut moves = 0;
+    let mut pacer = Pacer::new(1.0);
+    let start = Instant::now();
+    let mut finished: Option<f64> = None;
+    loop {
+        clear_background(config().theme.background);
+        let layout = update_layout(&mut board, state.rows(), state.cols());
+        let board_relative_path = layout.board.point();
+
+        if quit_requested() {
+            break;
+        }
+
+        if state.is_finished() {
+            let seconds = *finished.get_or_insert_with(|| start.elapsed().as_secs_f64());
+            let txt = format!("GAME CLEAR  {}", status_text(moves, seconds));
+            draw_text(&txt, layout.header.x, layout.header.bottom(), 40.0, RED);
+        } else {
+            if let Some(handle) = search.take_if(|handle| handle.is_finished()) {
+                let found = handle.join().expect("the search doesn't panic");
+                solution = found
+                    .unwrap_or_else(|| {
+                        eprintln!("no solution found within {} positions", NODE_LIMIT);
+                        vec![]
+                    })
+                    .into_iter();
+            }
+            let mut txt = status_text(moves, start.elapsed().as_secs_f64());
+            if search.is_some() {
+                txt.push_str("  solving...");
+            }
+            draw_text(
+                &txt,
+                layout.header.x,
+                layout.header.bottom(),
+                40.0,
+                config().theme.accent,
+            );
+
+            if args.ai
+                && pacer.tick()
+                && let Some(mv) = solution.next()
+            {
+                state.slide(mv);
+                moves += 1;
+            }
+            for event in pointer_events() {
+                match event {
+                    PointerEvent::Pressed(pos) => {
+                        pressed = board
+                            .get_cell(pos - board_relative_path)
+                            .map(|cell| (cell, pos));
+                    }
+                    PointerEvent::Moved(_) => {}
+                    PointerEvent::Released(pos) => {
+                        if let Some((cell, origin)) = pressed.take()
+                            && !args.ai
+                            && let Some(mv) = variant_move(&state, cell, pos - origin)
+                        {
+                            state.slide(mv);
+                            moves += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        board.apply_variant(&state);
+        board.draw(&mut MacroquadRenderer, board_relative_path);
+
+        next_frame().await
+    }
+}
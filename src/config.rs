@@ -0,0 +1,380 @@
+//! Settings read from `config.toml` in the user's config directory.
+//!
+//! Every setting is optional; a missing file or a missing key keeps the
+//! built-in default.
+//!
+//! ```toml
+//! theme = "dark"
+//! solver = "astar"
+//!
+//! [board]
+//! rows = 3
+//! cols = 3
+//!
+//! [animation]
+//! moves_per_second = 10.0
+//!
+//! [keys]
+//! quit = "x"
+//!
+//! [window]
+//! width = 1280
+//! height = 720
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use macroquad::input::{self, KeyCode};
+use serde::{Deserialize, Serialize};
+
+use crate::{SolverKind, Theme, validate_board_size};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    /// A setting has a value the game can't use.
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{}", e),
+            ConfigError::Parse(e) => write!(f, "{}", e.to_string().trim_end()),
+            ConfigError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        ConfigError::Parse(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Theme,
+    /// Solver the AI plays with; `None` picks one by board size.
+    #[serde(with = "solver_name")]
+    pub solver: Option<SolverKind>,
+    pub board: BoardConfig,
+    pub animation: AnimationConfig,
+    pub keys: KeyBindings,
+    pub window: WindowConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoardConfig {
+    /// Size of a game started without one.
+    pub rows: usize,
+    pub cols: usize,
+    /// Width of the board's outline in pixels.
+    pub border: f32,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        BoardConfig {
+            rows: 4,
+            cols: 4,
+            border: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnimationConfig {
+    /// How fast the AI and other automatic moves are played.
+    pub moves_per_second: f64,
+}
+
+impl Default for AnimationConfig {
+    fn default() -> Self {
+        AnimationConfig {
+            moves_per_second: 6.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    pub quit: Key,
+    /// Opens the pause menu, or goes back from a menu.
+    pub pause: Key,
+    pub restart: Key,
+    /// Skips to the next level.
+    pub next: Key,
+    /// Picks the highlighted menu item.
+    pub confirm: Key,
+    /// Shows or hides the solver's next step over the board.
+    pub path: Key,
+    pub up: Key,
+    pub down: Key,
+    pub left: Key,
+    pub right: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            quit: Key(KeyCode::Q),
+            pause: Key(KeyCode::Escape),
+            restart: Key(KeyCode::R),
+            next: Key(KeyCode::N),
+            confirm: Key(KeyCode::Enter),
+            path: Key(KeyCode::P),
+            up: Key(KeyCode::Up),
+            down: Key(KeyCode::Down),
+            left: Key(KeyCode::Left),
+            right: Key(KeyCode::Right),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WindowConfig {
+    pub width: i32,
+    pub height: i32,
+    pub resizable: bool,
+    pub fullscreen: bool,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: 1920,
+            height: 1080,
+            resizable: true,
+            fullscreen: false,
+        }
+    }
+}
+
+/// Keys a binding can name.
+const KEYS: [KeyCode; 68] = [
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Key0,
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+    KeyCode::Key6,
+    KeyCode::Key7,
+    KeyCode::Key8,
+    KeyCode::Key9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Enter,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::Insert,
+    KeyCode::Delete,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Minus,
+    KeyCode::Equal,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+];
+
+/// A key of the keyboard, named like `q`, `5`, `f1`, `space` or `pageup`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Key(pub KeyCode);
+
+impl Key {
+    pub fn name(&self) -> String {
+        let name = format!("{:?}", self.0);
+        name.strip_prefix("Key").unwrap_or(&name).to_lowercase()
+    }
+
+    /// Looks a key up by name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        KEYS.into_iter()
+            .map(Key)
+            .find(|key| key.name().eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        input::is_key_pressed(self.0)
+    }
+
+    pub fn is_down(&self) -> bool {
+        input::is_key_down(self.0)
+    }
+}
+
+/// Letters and digits are shown like the keycap, `Q`; other keys by name.
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.name();
+        if name.len() == 1 {
+            write!(f, "{}", name.to_uppercase())
+        } else {
+            write!(f, "{}", name)
+        }
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, String> {
+        Key::from_name(&name).ok_or_else(|| format!("unknown key `{}`", name))
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.name()
+    }
+}
+
+/// The solver as its name, or `auto` for the default.
+mod solver_name {
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    use crate::SolverKind;
+
+    pub fn serialize<S: Serializer>(
+        solver: &Option<SolverKind>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(solver.map_or("auto", |kind| kind.name()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SolverKind>, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if name == "auto" {
+            return Ok(None);
+        }
+        SolverKind::from_name(&name).map(Some).ok_or_else(|| {
+            let names = SolverKind::ALL.map(|kind| kind.name()).join(", ");
+            de::Error::custom(format!(
+                "unknown solver `{}` (available: auto, {})",
+                name, names
+            ))
+        })
+    }
+}
+
+impl Config {
+    /// `config.toml` in the platform's config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("slide-puzzle").join("config.toml"))
+    }
+
+    /// Loads the config at `path`; a missing file means the defaults.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let config: Config = toml::from_str(text)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks the values that parse but can't be used.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: String| Err(ConfigError::Invalid(message));
+        if let Err(e) = validate_board_size(self.board.rows, self.board.cols) {
+            return invalid(format!("board: {}", e));
+        }
+        if !(self.board.border >= 0.0 && self.board.border.is_finite()) {
+            return invalid(format!(
+                "board.border must be a positive number, not {}",
+                self.board.border
+            ));
+        }
+        let speed = self.animation.moves_per_second;
+        if !(speed > 0.0 && speed.is_finite()) {
+            return invalid(format!(
+                "animation.moves_per_second must be a positive number, not {}",
+                speed
+            ));
+        }
+        if self.window.width <= 0 || self.window.height <= 0 {
+            return invalid(format!(
+                "window size {}x{} must be positive",
+                self.window.width, self.window.height
+            ));
+        }
+        Ok(())
+    }
+
+    /// The config written out as TOML.
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("the config is always valid TOML")
+    }
+}
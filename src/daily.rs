@@ -0,0 +1,204 @@
+//! Daily puzzle: one position per date and board size, the same for every
+//! player running the same build.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::generate::{self, Difficulty, GenerateError, Puzzle, Target};
+
+/// Difficulty of every daily puzzle.
+pub const DAILY_DIFFICULTY: Difficulty = Difficulty::Hard;
+
+/// A calendar date (UTC, proleptic Gregorian).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Today's date in UTC.
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Date::from_days((secs / 86400) as i64)
+    }
+
+    /// The date `days` days after 1970-01-01.
+    pub fn from_days(days: i64) -> Self {
+        // Howard Hinnant's civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Date { year, month, day }
+    }
+
+    /// Days since 1970-01-01.
+    pub fn to_days(&self) -> i64 {
+        let year = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = self.month as i64;
+        let doy =
+            (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    pub fn previous(&self) -> Self {
+        Date::from_days(self.to_days() - 1)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("`{}` is not a date of the form YYYY-MM-DD", s);
+        let mut parts = s.splitn(3, '-');
+        let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let (Ok(year), Ok(month), Ok(day)) = (year.parse(), month.parse(), day.parse()) else {
+            return Err(invalid());
+        };
+
+        let date = Date { year, month, day };
+        // reject dates such as 2025-02-30 that don't round-trip
+        if !(1..=12).contains(&month) || Date::from_days(date.to_days()) != date {
+            return Err(invalid());
+        }
+        Ok(date)
+    }
+}
+
+/// Seed of the daily puzzle for `date` on a `rows` x `cols` board.
+///
+/// FNV-1a over the date and size, so it doesn't depend on the standard
+/// library's randomized hasher.
+pub fn seed(date: Date, rows: usize, cols: usize) -> u64 {
+    let key = format!("{} {}x{}", date, rows, cols);
+    key.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The daily puzzle for `date`.
+pub fn puzzle(date: Date, rows: usize, cols: usize) -> Result<Puzzle, GenerateError> {
+    let mut rng = StdRng::seed_from_u64(seed(date, rows, cols));
+    generate::generate(rows, cols, Target::Tier(DAILY_DIFFICULTY), &mut rng)
+}
+
+/// A finished daily puzzle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyResult {
+    pub date: Date,
+    pub rows: usize,
+    pub cols: usize,
+    pub moves: usize,
+    pub seconds: f64,
+    pub optimal: Option<usize>,
+}
+
+/// Daily results, kept apart from any other play history.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyStats {
+    pub results: Vec<DailyResult>,
+}
+
+impl DailyStats {
+    /// `daily.json` in the platform's data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("slide-puzzle").join("daily.json"))
+    }
+
+    /// Loads stats from `path`; a missing file means no results yet.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(DailyStats::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    pub fn get(&self, date: Date, rows: usize, cols: usize) -> Option<&DailyResult> {
+        self.results
+            .iter()
+            .find(|r| r.date == date && r.rows == rows && r.cols == cols)
+    }
+
+    /// Records `result` unless that day's puzzle was already finished; only
+    /// the first solve counts. Returns true if it was recorded.
+    pub fn record(&mut self, result: DailyResult) -> bool {
+        if self.get(result.date, result.rows, result.cols).is_some() {
+            return false;
+        }
+        self.results.push(result);
+        true
+    }
+
+    /// Number of consecutive days up to `date` with a solved puzzle of this
+    /// size.
+    pub fn streak(&self, date: Date, rows: usize, cols: usize) -> usize {
+        let mut streak = 0;
+        let mut day = date;
+        while self.get(day, rows, cols).is_some() {
+            streak += 1;
+            day = day.previous();
+        }
+        streak
+    }
+}
+
+/// Text to paste into chat after finishing a daily puzzle.
+pub fn share_text(result: &DailyResult, streak: usize) -> String {
+    let seconds = result.seconds.round() as u64;
+    let mut text = format!(
+        "Slide Puzzle daily {} ({}x{})\n{} moves in {}:{:02}",
+        result.date,
+        result.rows,
+        result.cols,
+        result.moves,
+        seconds / 60,
+        seconds % 60
+    );
+    if let Some(optimal) = result.optimal {
+        text += &format!(" (optimal {})", optimal);
+    }
+    if streak > 1 {
+        text += &format!("\nstreak: {} days", streak);
+    }
+    text
+}
@@ -0,0 +1,334 @@
+//! Offscreen rendering of solutions to animated GIFs and PNG frames.
+//!
+//! [`Frame`] is a small CPU rasterizer implementing [`Renderer`], so
+//! [`Board::draw`] renders into it without a window or GPU.
+//!
+//! [`Board::draw`]: crate::Board::draw
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use macroquad::color::{self, Color};
+use macroquad::math::{Vec2, vec2};
+
+use crate::{Board, Cell, Renderer, State};
+
+/// Digits 0-9 as 3x5 bitmaps, one row per entry, most significant bit left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// An RGBA image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        let rgba: [u8; 4] = background.into();
+        Frame {
+            width,
+            height,
+            pixels: rgba.repeat(width * height),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Pixels in row-major order, four bytes each.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        self.pixels[i..i + 4].try_into().unwrap()
+    }
+
+    /// Fills the pixels whose centers lie inside the rectangle.
+    pub fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        let rgba: [u8; 4] = color.into();
+        let span = |lo: f32, len: f32, max: usize| {
+            let start = (lo - 0.5).ceil().clamp(0.0, max as f32) as usize;
+            let end = (lo + len - 0.5).ceil().clamp(0.0, max as f32) as usize;
+            start..end
+        };
+        let xs = span(x, w, self.width);
+        for py in span(y, h, self.height) {
+            for px in xs.clone() {
+                let i = (py * self.width + px) * 4;
+                self.pixels[i..i + 4].copy_from_slice(&rgba);
+            }
+        }
+    }
+
+    /// Blits `frame` stretched to `w` x `h`, nearest neighbour.
+    fn blit(&mut self, frame: &Frame, x: f32, y: f32, w: f32, h: f32) {
+        if frame.width == 0 || frame.height == 0 || w <= 0.0 || h <= 0.0 {
+            return;
+        }
+        let left = x.round() as isize;
+        let top = y.round() as isize;
+        for dy in 0..h.round() as isize {
+            for dx in 0..w.round() as isize {
+                let (px, py) = (left + dx, top + dy);
+                if px < 0 || py < 0 || px as usize >= self.width || py as usize >= self.height {
+                    continue;
+                }
+                let sx = ((dx as f32 + 0.5) * frame.width as f32 / w) as usize;
+                let sy = ((dy as f32 + 0.5) * frame.height as f32 / h) as usize;
+                let src = frame.pixel(sx.min(frame.width - 1), sy.min(frame.height - 1));
+                let i = (py as usize * self.width + px as usize) * 4;
+                self.pixels[i..i + 4].copy_from_slice(&src);
+            }
+        }
+    }
+}
+
+/// Size of one bitmap font pixel for `font_size`; digits are five of them
+/// tall.
+fn glyph_scale(font_size: u16) -> f32 {
+    (font_size as f32 * 0.6 / 5.0).floor().max(1.0)
+}
+
+/// Draws with the built-in bitmap font, which only has digits; other
+/// characters leave a gap.
+impl Renderer for Frame {
+    type Texture = Frame;
+
+    fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        self.fill_rect(x, y, w, h, color);
+    }
+
+    fn draw_rectangle_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        self.fill_rect(x, y, w, thickness, color);
+        self.fill_rect(x, y + h - thickness, w, thickness, color);
+        self.fill_rect(x, y, thickness, h, color);
+        self.fill_rect(x + w - thickness, y, thickness, h, color);
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        let half = thickness / 2.0;
+        let (left, right) = (x1.min(x2), x1.max(x2));
+        let (top, bottom) = (y1.min(y2), y1.max(y2));
+        if y1 == y2 {
+            self.fill_rect(left, top - half, right - left, thickness, color);
+        } else if x1 == x2 {
+            self.fill_rect(left - half, top, thickness, bottom - top, color);
+        } else {
+            // stamp a square brush along the line
+            let length = (x2 - x1).hypot(y2 - y1);
+            let steps = (length * 2.0).ceil() as usize;
+            for step in 0..=steps {
+                let t = step as f32 / steps as f32;
+                let x = x1 + (x2 - x1) * t;
+                let y = y1 + (y2 - y1) * t;
+                self.fill_rect(x - half, y - half, thickness, thickness, color);
+            }
+        }
+    }
+
+    fn draw_triangle(&mut self, v1: Vec2, v2: Vec2, v3: Vec2, color: Color) {
+        let rgba: [u8; 4] = color.into();
+        // a pixel is covered if its center is on the inner side of all three
+        // edges, whichever way round the corners are given
+        let edge = |a: Vec2, b: Vec2, p: Vec2| (b - a).perp_dot(p - a);
+        let area = edge(v1, v2, v3);
+        if area == 0.0 {
+            return;
+        }
+        let span = |lo: f32, hi: f32, max: usize| {
+            let start = (lo - 0.5).ceil().clamp(0.0, max as f32) as usize;
+            let end = (hi - 0.5).ceil().clamp(0.0, max as f32) as usize;
+            start..end
+        };
+        let xs = span(
+            v1.x.min(v2.x).min(v3.x),
+            v1.x.max(v2.x).max(v3.x),
+            self.width,
+        );
+        let ys = span(
+            v1.y.min(v2.y).min(v3.y),
+            v1.y.max(v2.y).max(v3.y),
+            self.height,
+        );
+        for py in ys {
+            for px in xs.clone() {
+                let p = vec2(px as f32 + 0.5, py as f32 + 0.5);
+                let inside = [edge(v1, v2, p), edge(v2, v3, p), edge(v3, v1, p)]
+                    .iter()
+                    .all(|&e| e * area >= 0.0);
+                if inside {
+                    let i = (py * self.width + px) * 4;
+                    self.pixels[i..i + 4].copy_from_slice(&rgba);
+                }
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+        let scale = glyph_scale(font_size);
+        let left = x.round();
+        let top = (y - 5.0 * scale).round();
+
+        for (n, c) in text.chars().enumerate() {
+            let Some(digit) = c.to_digit(10) else {
+                continue;
+            };
+            let glyph_x = left + (n * 4) as f32 * scale;
+            for (row, bits) in DIGITS[digit as usize].iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (0b100 >> col) != 0 {
+                        self.fill_rect(
+                            glyph_x + col as f32 * scale,
+                            top + row as f32 * scale,
+                            scale,
+                            scale,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn text_center(&self, text: &str, font_size: u16) -> Vec2 {
+        let scale = glyph_scale(font_size);
+        let width = (text.chars().count() * 4).saturating_sub(1) as f32 * scale;
+        vec2(width / 2.0, -2.5 * scale)
+    }
+
+    fn draw_texture(&mut self, texture: &Frame, x: f32, y: f32, w: f32, h: f32) {
+        self.blit(texture, x, y, w, h);
+    }
+}
+
+/// Draws `state` with [`Board::draw`], with square cells of `cell_size`
+/// pixels and a margin of `border_width` around the board.
+pub fn render(state: &State, cell_size: f32, border_width: f32) -> Frame {
+    let rows = state.rows();
+    let cols = state.cols();
+    let margin = border_width;
+    let size = vec2(cols as f32 * cell_size, rows as f32 * cell_size);
+
+    let mut board = Board::new(size, border_width, (rows, cols));
+    board.apply_state(state);
+
+    let mut frame = Frame::new(
+        (size.x + 2.0 * margin).ceil() as usize,
+        (size.y + 2.0 * margin).ceil() as usize,
+        color::WHITE,
+    );
+    board.draw(&mut frame, vec2(margin, margin));
+    frame
+}
+
+/// Renders `state` and the position after each of `moves`.
+pub fn solution_frames(state: &State, moves: &[Cell], cell_size: f32) -> Vec<Frame> {
+    let border_width = (cell_size / 16.0).max(2.0);
+    let mut state = state.clone();
+    let mut frames = vec![render(&state, cell_size, border_width)];
+    for &mv in moves {
+        state.swap(state.blank_cell(), mv);
+        frames.push(render(&state, cell_size, border_width));
+    }
+    frames
+}
+
+/// Writes `frames` as a looping GIF showing each frame for `delay_ms`; the
+/// first and last frames are held for a second.
+pub fn write_gif<W: Write>(frames: &[Frame], delay_ms: u32, w: W) -> io::Result<()> {
+    let Some(first) = frames.first() else {
+        return Err(io::Error::other("no frames to write"));
+    };
+    let too_large = || io::Error::other("frames are too large for a GIF");
+    let width = u16::try_from(first.width).map_err(|_| too_large())?;
+    let height = u16::try_from(first.height).map_err(|_| too_large())?;
+
+    // the board uses a handful of flat colors, so a shared palette is exact
+    let mut palette = vec![];
+    let mut indices = HashMap::new();
+    let mut indexed = vec![];
+    for frame in frames {
+        let mut pixels = Vec::with_capacity(frame.width * frame.height);
+        for rgba in frame.pixels.chunks_exact(4) {
+            let next = indices.len();
+            let index = *indices.entry([rgba[0], rgba[1], rgba[2]]).or_insert(next);
+            if index == next {
+                palette.extend_from_slice(&rgba[..3]);
+            }
+            pixels.push(u8::try_from(index).map_err(|_| io::Error::other("too many colors"))?);
+        }
+        indexed.push(pixels);
+    }
+
+    let mut encoder = gif::Encoder::new(w, width, height, &palette).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+    let last = indexed.len() - 1;
+    for (i, pixels) in indexed.into_iter().enumerate() {
+        let mut frame = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+        let delay = if i == 0 || i == last { 1000 } else { delay_ms };
+        frame.delay = (delay / 10).min(u16::MAX as u32) as u16;
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+pub fn write_png<W: Write>(frame: &Frame, w: W) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(w, frame.width as u32, frame.height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(&frame.pixels)
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+/// Writes `frames` to `dir` as `frame-0000.png`, `frame-0001.png`, ... and
+/// returns their paths.
+pub fn write_png_frames<P: AsRef<Path>>(frames: &[Frame], dir: P) -> io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let path = dir.join(format!("frame-{:04}.png", i));
+            let mut w = BufWriter::new(File::create(&path)?);
+            write_png(frame, &mut w)?;
+            w.flush()?;
+            Ok(path)
+        })
+        .collect()
+}
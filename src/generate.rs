@@ -0,0 +1,289 @@
+//! Puzzle generation with a target difficulty.
+//!
+//! Boards small enough for a [`DistanceTable`] are rated by their exact
+//! optimal solution length. Larger boards are rated by the Manhattan distance
+//! of the tiles relative to that of a fully random position, since optimal
+//! solving is out of reach there.
+
+use std::error::Error;
+use std::fmt;
+
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::analysis::DistanceTable;
+use crate::rank::RANKED_MAX_CELLS;
+use crate::{Cell, State, cell};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Difficulty::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    /// Share of the board's scale (God's number, or the Manhattan distance
+    /// of a random position) covered by this tier.
+    fn fraction(&self) -> (f32, f32) {
+        match self {
+            Difficulty::Easy => (0.2, 0.4),
+            Difficulty::Medium => (0.4, 0.6),
+            Difficulty::Hard => (0.6, 0.8),
+            Difficulty::Expert => (0.8, f32::INFINITY),
+        }
+    }
+
+    /// The tier `value` falls into on a board whose scale is `scale`, or
+    /// `None` if it is below even the easy tier.
+    fn tier(value: f32, scale: f32) -> Option<Difficulty> {
+        let share = value / scale;
+        Difficulty::ALL
+            .into_iter()
+            .rev()
+            .find(|d| share >= d.fraction().0)
+    }
+
+    /// Like `tier`, but positions easier than the easy tier count as easy.
+    fn of(value: f32, scale: f32) -> Difficulty {
+        Difficulty::tier(value, scale).unwrap_or(Difficulty::Easy)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// A difficulty tier.
+    Tier(Difficulty),
+    /// An exact optimal solution length, small boards only.
+    Length(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenerateError {
+    /// The length is 0 or longer than the hardest position needs.
+    LengthOutOfRange { length: usize, max: usize },
+    /// Exact lengths need a distance table, which large boards don't have.
+    LengthUnsupported { rows: usize, cols: usize },
+    /// No random walk reached a position in the tier.
+    TierUnreachable {
+        rows: usize,
+        cols: usize,
+        difficulty: Difficulty,
+    },
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::LengthOutOfRange { length, max } => write!(
+                f,
+                "the solution length must be between 1 and {} moves, not {}",
+                max, length
+            ),
+            GenerateError::LengthUnsupported { rows, cols } => write!(
+                f,
+                "exact solution lengths are only available for boards of at most {} cells, not {}x{}",
+                RANKED_MAX_CELLS, rows, cols
+            ),
+            GenerateError::TierUnreachable {
+                rows,
+                cols,
+                difficulty,
+            } => write!(
+                f,
+                "could not generate a {} position on a {}x{} board",
+                difficulty, rows, cols
+            ),
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+/// A generated position and how it was rated.
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    pub state: State,
+    pub difficulty: Difficulty,
+    /// Optimal solution length, known on small boards.
+    pub optimal: Option<usize>,
+}
+
+/// Random walks tried before a tier is given up on.
+const MAX_WALKS: usize = 100;
+
+/// Returns true if positions of a `rows` x `cols` board are rated by their
+/// exact optimal solution length.
+pub fn is_exact(rows: usize, cols: usize) -> bool {
+    rows * cols <= RANKED_MAX_CELLS
+}
+
+/// Generates a position for `target`. The same `rng` state always gives the
+/// same position.
+pub fn generate<R: Rng + ?Sized>(
+    rows: usize,
+    cols: usize,
+    target: Target,
+    rng: &mut R,
+) -> Result<Puzzle, GenerateError> {
+    if is_exact(rows, cols) {
+        let table = DistanceTable::shared(rows, cols).unwrap();
+        let max = table.max_distance();
+        let (lo, hi, difficulty) = match target {
+            Target::Length(length) if length == 0 || length > max => {
+                return Err(GenerateError::LengthOutOfRange { length, max });
+            }
+            Target::Length(length) => (length, length, Difficulty::of(length as f32, max as f32)),
+            Target::Tier(difficulty) => {
+                let (lo, hi) = distance_range(difficulty, max);
+                (lo, hi, difficulty)
+            }
+        };
+
+        let distance = rng.random_range(lo..=hi);
+        let state = table.positions_at(distance).choose(rng).unwrap().clone();
+        return Ok(Puzzle {
+            state,
+            difficulty,
+            optimal: Some(distance),
+        });
+    }
+
+    let Target::Tier(difficulty) = target else {
+        return Err(GenerateError::LengthUnsupported { rows, cols });
+    };
+
+    let scale = random_manhattan(rows, cols);
+    let (lo, hi) = manhattan_range(difficulty, scale);
+    // walk the blank around until the Manhattan sum, which changes by one a
+    // step, hits a target drawn from the tier; restart with a new target if
+    // the walk wanders too long
+    for _ in 0..MAX_WALKS {
+        let target = rng.random_range(lo..=hi);
+        let mut state = State::new(rows, cols);
+        let mut previous = None;
+        for _ in 0..rows * cols * 200 {
+            let blank = state.blank_cell();
+            let neighbors = state.neighbors(blank);
+            // don't undo the previous step unless it's the only way out
+            let choices = neighbors
+                .iter()
+                .copied()
+                .filter(|&c| Some(c) != previous)
+                .collect::<Vec<_>>();
+            let next = *choices.choose(rng).or(neighbors.first()).unwrap();
+            state.swap(blank, next);
+            previous = Some(blank);
+
+            if manhattan(&state) == target {
+                return Ok(Puzzle {
+                    state,
+                    difficulty,
+                    optimal: None,
+                });
+            }
+        }
+    }
+    Err(GenerateError::TierUnreachable {
+        rows,
+        cols,
+        difficulty,
+    })
+}
+
+/// Rates an existing position.
+pub fn rate(state: &State) -> Difficulty {
+    if is_exact(state.rows(), state.cols()) {
+        let table = DistanceTable::shared(state.rows(), state.cols()).unwrap();
+        let distance = table.distance(state).unwrap_or(0);
+        Difficulty::of(distance as f32, table.max_distance() as f32)
+    } else {
+        let scale = random_manhattan(state.rows(), state.cols());
+        Difficulty::of(manhattan(state) as f32, scale)
+    }
+}
+
+/// Optimal lengths covered by `difficulty` on a board whose hardest position
+/// needs `max` moves. Never empty; tiny boards may share lengths between
+/// tiers.
+fn distance_range(difficulty: Difficulty, max: usize) -> (usize, usize) {
+    let (lo, hi) = difficulty.fraction();
+    let lo = ((lo * max as f32).ceil() as usize).clamp(1, max);
+    let hi = if hi.is_infinite() {
+        max
+    } else {
+        ((hi * max as f32).ceil() as usize).saturating_sub(1)
+    };
+    (lo, hi.clamp(lo, max))
+}
+
+/// Manhattan sums covered by `difficulty` on a board where a random position
+/// averages `scale`. Expert stops at `scale`, since walks rarely go further.
+fn manhattan_range(difficulty: Difficulty, scale: f32) -> (usize, usize) {
+    let (lo, hi) = difficulty.fraction();
+    let lo = (lo * scale).ceil().max(1.0) as usize;
+    let hi = if hi.is_infinite() {
+        scale.floor() as usize
+    } else {
+        (hi * scale).ceil() as usize - 1
+    };
+    (lo, hi.max(lo))
+}
+
+/// Sum of the Manhattan distances of the tiles to their goal cells.
+fn manhattan(state: &State) -> usize {
+    state
+        .pieces()
+        .iter()
+        .enumerate()
+        .filter_map(|(i, piece)| {
+            let piece = (*piece)?;
+            let current = cell!(i, state.rows(), state.cols());
+            let goal = cell!(piece.num - 1, state.rows(), state.cols());
+            Some(current.manhattan_distance(&goal))
+        })
+        .sum()
+}
+
+/// Expected Manhattan sum of a uniformly random position.
+fn random_manhattan(rows: usize, cols: usize) -> f32 {
+    let n = rows * cols;
+    let cells = (0..n).map(|i| cell!(i, rows, cols)).collect::<Vec<Cell>>();
+    cells[..n - 1]
+        .iter()
+        .map(|goal| {
+            cells
+                .iter()
+                .map(|c| c.manhattan_distance(goal))
+                .sum::<usize>() as f32
+                / n as f32
+        })
+        .sum()
+}
@@ -0,0 +1,164 @@
+//! Shapes of the cells a board is tiled with.
+//!
+//! Cells keep their `(x, y)` coordinates on every grid:
+//!
+//! - [`Geometry::Hex`] uses pointy-top hexagons with every odd row shifted
+//!   half a cell to the right, so a cell has up to six neighbours.
+//! - [`Geometry::Triangle`] alternates triangles pointing up (`x + y` even)
+//!   and down along each row. A triangle touches its left and right
+//!   neighbours, and the row below (pointing up) or above (pointing down).
+//!
+//! Positions are in units where a square cell is 1 wide, a hexagon is 1 wide
+//! and a triangle's side is 1; [`Board`](crate::Board) scales them to fit.
+
+use std::f32::consts::FRAC_PI_3;
+
+use macroquad::math::{Vec2, vec2};
+
+use crate::{Cell, cell};
+
+const SQRT_3: f32 = 1.732_050_8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Geometry {
+    #[default]
+    Square,
+    Hex,
+    Triangle,
+}
+
+impl Geometry {
+    pub const ALL: [Geometry; 3] = [Geometry::Square, Geometry::Hex, Geometry::Triangle];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Geometry::Square => "square",
+            Geometry::Hex => "hex",
+            Geometry::Triangle => "triangle",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Geometry::ALL.into_iter().find(|g| g.name() == name)
+    }
+
+    /// Cells sharing an edge with `cell` on a `rows` x `cols` board.
+    pub fn neighbors(&self, cell: Cell, rows: usize, cols: usize) -> Vec<Cell> {
+        let offsets: &[(isize, isize)] = match self {
+            Geometry::Square => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Geometry::Hex if cell.y.is_multiple_of(2) => {
+                &[(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)]
+            }
+            Geometry::Hex => &[(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)],
+            Geometry::Triangle if points_up(cell) => &[(-1, 0), (1, 0), (0, 1)],
+            Geometry::Triangle => &[(0, -1), (-1, 0), (1, 0)],
+        };
+        offsets
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let x = cell.x.checked_add_signed(dx).filter(|&x| x < cols)?;
+                let y = cell.y.checked_add_signed(dy).filter(|&y| y < rows)?;
+                Some(cell!(x, y))
+            })
+            .collect()
+    }
+
+    /// Width and height of a `rows` x `cols` board.
+    pub fn extent(&self, rows: usize, cols: usize) -> Vec2 {
+        match self {
+            Geometry::Square => vec2(cols as f32, rows as f32),
+            Geometry::Hex => {
+                let shift = if rows > 1 { 0.5 } else { 0.0 };
+                let radius = 1.0 / SQRT_3;
+                vec2(cols as f32 + shift, radius * (1.5 * rows as f32 + 0.5))
+            }
+            Geometry::Triangle => vec2((cols + 1) as f32 / 2.0, rows as f32 * SQRT_3 / 2.0),
+        }
+    }
+
+    /// Center of `cell`, measured from the top-left corner of the board.
+    pub fn center(&self, cell: Cell) -> Vec2 {
+        let (x, y) = (cell.x as f32, cell.y as f32);
+        match self {
+            Geometry::Square => vec2(x + 0.5, y + 0.5),
+            Geometry::Hex => {
+                let shift = if cell.y.is_multiple_of(2) { 0.0 } else { 0.5 };
+                let radius = 1.0 / SQRT_3;
+                vec2(x + 0.5 + shift, radius * (1.0 + 1.5 * y))
+            }
+            Geometry::Triangle => {
+                let height = SQRT_3 / 2.0;
+                let below_top = if points_up(cell) { 2.0 } else { 1.0 };
+                vec2(x / 2.0 + 0.5, height * (y + below_top / 3.0))
+            }
+        }
+    }
+
+    /// Corners of `cell`, clockwise on screen.
+    pub fn corners(&self, cell: Cell) -> Vec<Vec2> {
+        let center = self.center(cell);
+        match self {
+            Geometry::Square => [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)]
+                .map(|(dx, dy)| center + vec2(dx, dy))
+                .to_vec(),
+            Geometry::Hex => {
+                let radius = 1.0 / SQRT_3;
+                // starting at the top corner
+                (0..6)
+                    .map(|i| {
+                        let angle = FRAC_PI_3 * i as f32 - FRAC_PI_3 * 1.5;
+                        center + vec2(angle.cos(), angle.sin()) * radius
+                    })
+                    .collect()
+            }
+            Geometry::Triangle => {
+                let height = SQRT_3 / 2.0;
+                let left = cell.x as f32 / 2.0;
+                let top = cell.y as f32 * height;
+                let bottom = top + height;
+                if points_up(cell) {
+                    vec![
+                        vec2(left + 0.5, top),
+                        vec2(left + 1.0, bottom),
+                        vec2(left, bottom),
+                    ]
+                } else {
+                    vec![
+                        vec2(left, top),
+                        vec2(left + 1.0, top),
+                        vec2(left + 0.5, bottom),
+                    ]
+                }
+            }
+        }
+    }
+
+    /// Distance from the center of a cell to its edges.
+    pub fn inradius(&self) -> f32 {
+        match self {
+            Geometry::Square | Geometry::Hex => 0.5,
+            Geometry::Triangle => SQRT_3 / 6.0,
+        }
+    }
+
+    /// The cell containing `pos`, if any.
+    pub fn cell_at(&self, pos: Vec2, rows: usize, cols: usize) -> Option<Cell> {
+        (0..rows * cols)
+            .map(|i| cell!(i, rows, cols))
+            .find(|&cell| contains(&self.corners(cell), pos))
+    }
+}
+
+fn points_up(cell: Cell) -> bool {
+    (cell.x + cell.y).is_multiple_of(2)
+}
+
+/// Whether `pos` is inside the convex polygon with clockwise `corners`.
+/// Points on an edge count as inside, with some slack for rounding.
+fn contains(corners: &[Vec2], pos: Vec2) -> bool {
+    (0..corners.len()).all(|i| {
+        let a = corners[i];
+        let b = corners[(i + 1) % corners.len()];
+        (b - a).perp_dot(pos - a) >= -1e-4
+    })
+}
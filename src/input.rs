@@ -0,0 +1,189 @@
+use macroquad::input::{self, MouseButton, TouchPhase};
+use macroquad::math::{Vec2, vec2};
+
+use crate::{Board, Cell};
+
+/// A way to slide a tile or block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// The step in `x` and `y` one cell this way.
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+}
+
+/// Pointer movement shared by mouse and touch input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerEvent {
+    Pressed(Vec2),
+    Moved(Vec2),
+    Released(Vec2),
+}
+
+/// Collects this frame's pointer events from the mouse and the first touch.
+///
+/// Call `input::simulate_mouse_with_touch(false)` once at startup, otherwise
+/// every touch is reported twice.
+pub fn pointer_events() -> Vec<PointerEvent> {
+    let mut events = vec![];
+
+    let (x, y) = input::mouse_position();
+    let mouse = vec2(x, y);
+    if input::is_mouse_button_pressed(MouseButton::Left) {
+        events.push(PointerEvent::Pressed(mouse));
+    } else if input::is_mouse_button_released(MouseButton::Left) {
+        events.push(PointerEvent::Released(mouse));
+    } else if input::is_mouse_button_down(MouseButton::Left) {
+        events.push(PointerEvent::Moved(mouse));
+    }
+
+    if let Some(touch) = input::touches().into_iter().min_by_key(|t| t.id) {
+        events.push(match touch.phase {
+            TouchPhase::Started => PointerEvent::Pressed(touch.position),
+            TouchPhase::Moved | TouchPhase::Stationary => PointerEvent::Moved(touch.position),
+            TouchPhase::Ended | TouchPhase::Cancelled => PointerEvent::Released(touch.position),
+        });
+    }
+    events
+}
+
+/// A tile (and every tile between it and the blank) being dragged towards
+/// the blank.
+///
+/// Only tiles in the blank's row or column can be grabbed. The tiles follow
+/// the pointer along that line, clamped between their current cell and the
+/// next one, and [`Drag::release`] decides whether they snap forward.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drag {
+    grabbed: Cell,
+    cells: Vec<Cell>,
+    direction: Vec2,
+    origin: Vec2,
+    distance: f32,
+    travelled: f32,
+}
+
+/// Pointer travel below this many pixels counts as a tap, not a drag.
+pub const TAP_DISTANCE: f32 = 8.0;
+
+impl Drag {
+    /// Starts dragging `grabbed` with the pointer at `origin`, or returns
+    /// `None` when the tile is not in line with `blank`.
+    pub fn new(grabbed: Cell, blank: Cell, origin: Vec2) -> Option<Self> {
+        if grabbed == blank || (grabbed.x != blank.x && grabbed.y != blank.y) {
+            return None;
+        }
+
+        let dx = (blank.x as isize - grabbed.x as isize).signum();
+        let dy = (blank.y as isize - grabbed.y as isize).signum();
+
+        let mut cells = vec![];
+        let mut current = grabbed;
+        while current != blank {
+            cells.push(current);
+            current = Cell::new(
+                (current.x as isize + dx) as usize,
+                (current.y as isize + dy) as usize,
+            );
+        }
+
+        Some(Drag {
+            grabbed,
+            cells,
+            direction: vec2(dx as f32, dy as f32),
+            origin,
+            distance: 0.0,
+            travelled: 0.0,
+        })
+    }
+
+    /// The cell the pointer grabbed.
+    pub fn grabbed(&self) -> Cell {
+        self.grabbed
+    }
+
+    /// The cells whose tiles move, from the grabbed one towards the blank.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// Follows the pointer to `pos`.
+    pub fn update(&mut self, pos: Vec2, cell_size: Vec2) {
+        let delta = pos - self.origin;
+        self.travelled = self.travelled.max(delta.length());
+        self.distance = delta
+            .dot(self.direction)
+            .clamp(0.0, self.cell_length(cell_size));
+    }
+
+    /// How far the dragged tiles are drawn from their cells.
+    pub fn offset(&self) -> Vec2 {
+        self.direction * self.distance
+    }
+
+    /// Returns true if the tiles should snap into the blank when the pointer
+    /// is released: either they were dragged past half a cell or the pointer
+    /// barely moved (a tap).
+    pub fn release(&self, cell_size: Vec2) -> bool {
+        self.travelled < TAP_DISTANCE || self.distance >= self.cell_length(cell_size) / 2.0
+    }
+
+    fn cell_length(&self, cell_size: Vec2) -> f32 {
+        (cell_size * self.direction).length()
+    }
+}
+
+/// Feeds `events` to `drag` for `board`, drawn at `origin`: a press grabs a
+/// tile in line with `blank` and a release lets it go. Returns the grabbed
+/// cell when the tiles snap into the blank; sliding them is up to the caller.
+pub fn drag_board(
+    drag: &mut Option<Drag>,
+    events: &[PointerEvent],
+    board: &mut Board,
+    origin: Vec2,
+    blank: Cell,
+) -> Option<Cell> {
+    let mut snapped = None;
+    for &event in events {
+        match event {
+            PointerEvent::Pressed(pos) => {
+                *drag = board
+                    .get_cell(pos - origin)
+                    .and_then(|cell| Drag::new(cell, blank, pos));
+            }
+            PointerEvent::Moved(pos) => {
+                if let Some(drag) = drag.as_mut() {
+                    drag.update(pos, board.cell_size());
+                }
+            }
+            PointerEvent::Released(pos) => {
+                if let Some(mut released) = drag.take() {
+                    released.update(pos, board.cell_size());
+                    if released.release(board.cell_size()) {
+                        snapped = Some(released.grabbed());
+                    }
+                }
+            }
+        }
+    }
+    snapped
+}
@@ -0,0 +1,482 @@
+//! Klotski ("Huarong Dao"): rectangular blocks spanning several cells slide
+//! around the board until one of them reaches the exit.
+//!
+//! A move shifts one block by one cell. Blocks of the same shape are
+//! interchangeable, which the solver uses to merge positions.
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use macroquad::color::{self, Color};
+use macroquad::math::Vec2;
+
+use crate::{Cell, Direction, MAX_BOARD_SIZE, Renderer, cell};
+
+/// The solver gives up after visiting this many positions.
+pub const NODE_LIMIT: usize = 2_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Block {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Block {
+    pub fn contains(&self, cell: Cell) -> bool {
+        (self.x..self.x + self.width).contains(&cell.x)
+            && (self.y..self.y + self.height).contains(&cell.y)
+    }
+
+    fn cells(&self) -> impl Iterator<Item = Cell> + '_ {
+        (self.y..self.y + self.height)
+            .flat_map(move |y| (self.x..self.x + self.width).map(move |x| cell!(x, y)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KlotskiMove {
+    /// Index into [`Klotski::blocks`].
+    pub block: usize,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KlotskiError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The cells of a block don't form a filled rectangle.
+    NotRectangle {
+        name: char,
+    },
+    Size {
+        rows: usize,
+        cols: usize,
+    },
+    /// The goal doesn't name a block, or puts it outside the board.
+    Goal,
+}
+
+impl fmt::Display for KlotskiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KlotskiError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            KlotskiError::NotRectangle { name } => {
+                write!(f, "the cells of block `{}` don't form a rectangle", name)
+            }
+            KlotskiError::Size { rows, cols } => write!(
+                f,
+                "{}x{} board is not supported (at most {} cells a side)",
+                rows, cols, MAX_BOARD_SIZE
+            ),
+            KlotskiError::Goal => write!(f, "the goal must name a block and fit on the board"),
+        }
+    }
+}
+
+impl Error for KlotskiError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Klotski {
+    rows: usize,
+    cols: usize,
+    blocks: Vec<Block>,
+    /// The block that has to reach the exit.
+    target: usize,
+    /// Where the target's top-left cell has to be.
+    exit: Cell,
+}
+
+impl Klotski {
+    /// Reads a board drawn with one letter per cell, the cells of a block
+    /// sharing its letter, and `.` for empty cells, e.g.
+    ///
+    /// ```text
+    /// B A A C
+    /// B A A C
+    /// . D D .
+    /// ```
+    ///
+    /// Blocks are numbered in reading order of their top-left cells.
+    pub fn parse(grid: &str, target: char, exit: Cell) -> Result<Self, KlotskiError> {
+        let mut names: Vec<Vec<char>> = vec![];
+        for (i, line) in grid.lines().enumerate() {
+            let row = line
+                .split_whitespace()
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => Ok(c),
+                        _ => Err(KlotskiError::Syntax {
+                            line: i + 1,
+                            message: format!("`{}` is not a single letter", word),
+                        }),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if row.is_empty() {
+                continue;
+            }
+            if names.first().is_some_and(|first| first.len() != row.len()) {
+                return Err(KlotskiError::Syntax {
+                    line: i + 1,
+                    message: "rows have different lengths".to_string(),
+                });
+            }
+            names.push(row);
+        }
+
+        let rows = names.len();
+        let cols = names.first().map_or(0, |row| row.len());
+        if rows == 0 || cols == 0 || rows > MAX_BOARD_SIZE || cols > MAX_BOARD_SIZE {
+            return Err(KlotskiError::Size { rows, cols });
+        }
+
+        let mut blocks = vec![];
+        let mut block_names = vec![];
+        for y in 0..rows {
+            for x in 0..cols {
+                let name = names[y][x];
+                if name == '.' || block_names.contains(&name) {
+                    continue;
+                }
+                let cells = (0..rows)
+                    .flat_map(|y| (0..cols).map(move |x| (x, y)))
+                    .filter(|&(x, y)| names[y][x] == name)
+                    .collect::<Vec<_>>();
+                let width = cells.iter().map(|c| c.0).max().unwrap() - x + 1;
+                let height = cells.iter().map(|c| c.1).max().unwrap() - y + 1;
+                let block = Block {
+                    x,
+                    y,
+                    width,
+                    height,
+                };
+                if cells.len() != width * height || !block.cells().all(|c| names[c.y][c.x] == name)
+                {
+                    return Err(KlotskiError::NotRectangle { name });
+                }
+                blocks.push(block);
+                block_names.push(name);
+            }
+        }
+
+        let target = block_names
+            .iter()
+            .position(|&name| name == target)
+            .ok_or(KlotskiError::Goal)?;
+        let size = &blocks[target];
+        if exit.x + size.width > cols || exit.y + size.height > rows {
+            return Err(KlotskiError::Goal);
+        }
+        Ok(Klotski {
+            rows,
+            cols,
+            blocks,
+            target,
+            exit,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// The cells the target has to cover.
+    pub fn exit(&self) -> Block {
+        Block {
+            x: self.exit.x,
+            y: self.exit.y,
+            ..self.blocks[self.target]
+        }
+    }
+
+    pub fn block_at(&self, cell: Cell) -> Option<usize> {
+        self.blocks.iter().position(|b| b.contains(cell))
+    }
+
+    /// The block covering each cell.
+    fn occupancy(&self) -> Vec<Option<usize>> {
+        let mut cells = vec![None; self.rows * self.cols];
+        for (i, block) in self.blocks.iter().enumerate() {
+            for cell in block.cells() {
+                cells[cell.as_index(self.rows, self.cols)] = Some(i);
+            }
+        }
+        cells
+    }
+
+    fn moved(&self, block: usize, direction: Direction) -> Option<Block> {
+        let b = self.blocks[block];
+        let (dx, dy) = direction.delta();
+        let x = b.x.checked_add_signed(dx)?;
+        let y = b.y.checked_add_signed(dy)?;
+        if x + b.width > self.cols || y + b.height > self.rows {
+            return None;
+        }
+        Some(Block { x, y, ..b })
+    }
+
+    fn is_free(&self, occupancy: &[Option<usize>], block: usize, to: &Block) -> bool {
+        to.cells().all(
+            |cell| match occupancy[cell.as_index(self.rows, self.cols)] {
+                Some(other) => other == block,
+                None => true,
+            },
+        )
+    }
+
+    pub fn can_move(&self, mv: KlotskiMove) -> bool {
+        self.moved(mv.block, mv.direction)
+            .is_some_and(|to| self.is_free(&self.occupancy(), mv.block, &to))
+    }
+
+    /// Every legal move.
+    pub fn moves(&self) -> Vec<KlotskiMove> {
+        let occupancy = self.occupancy();
+        let mut moves = vec![];
+        for block in 0..self.blocks.len() {
+            for direction in Direction::ALL {
+                if let Some(to) = self.moved(block, direction)
+                    && self.is_free(&occupancy, block, &to)
+                {
+                    moves.push(KlotskiMove { block, direction });
+                }
+            }
+        }
+        moves
+    }
+
+    /// Applies `mv` if it is legal.
+    pub fn apply(&mut self, mv: KlotskiMove) -> bool {
+        if !self.can_move(mv) {
+            return false;
+        }
+        self.blocks[mv.block] = self.moved(mv.block, mv.direction).unwrap();
+        true
+    }
+
+    pub fn is_solved(&self) -> bool {
+        let target = self.blocks[self.target];
+        cell!(target.x, target.y) == self.exit
+    }
+
+    /// Identifies the position up to swapping blocks of the same shape.
+    fn key(&self) -> Vec<u8> {
+        let mut key = vec![0u8; self.rows * self.cols];
+        for (i, block) in self.blocks.iter().enumerate() {
+            let kind = if i == self.target {
+                u8::MAX
+            } else {
+                (block.width * MAX_BOARD_SIZE + block.height) as u8
+            };
+            for cell in block.cells() {
+                key[cell.as_index(self.rows, self.cols)] = kind;
+            }
+        }
+        key
+    }
+
+    /// A shortest solution found by breadth-first search, or `None` if there
+    /// is none within [`NODE_LIMIT`] positions.
+    pub fn solve(&self) -> Option<Vec<KlotskiMove>> {
+        let mut nodes: Vec<(Klotski, usize, Option<KlotskiMove>)> = vec![(self.clone(), 0, None)];
+        let mut seen = HashMap::from([(self.key(), 0usize)]);
+        let mut queue = VecDeque::from([0usize]);
+
+        while let Some(idx) = queue.pop_front() {
+            if nodes[idx].0.is_solved() {
+                let mut moves = vec![];
+                let mut idx = idx;
+                while let (_, parent, Some(mv)) = &nodes[idx] {
+                    moves.push(*mv);
+                    idx = *parent;
+                }
+                moves.reverse();
+                return Some(moves);
+            }
+            if nodes.len() > NODE_LIMIT {
+                return None;
+            }
+            for mv in nodes[idx].0.moves() {
+                let mut next = nodes[idx].0.clone();
+                next.apply(mv);
+                let key = next.key();
+                if seen.contains_key(&key) {
+                    continue;
+                }
+                seen.insert(key, nodes.len());
+                queue.push_back(nodes.len());
+                nodes.push((next, idx, Some(mv)));
+            }
+        }
+        None
+    }
+
+    /// Draws the board into a `size` area with its top-left corner at `pos`.
+    /// The exit is outlined, the target block is red and `selected` is
+    /// highlighted.
+    pub fn draw<R: Renderer + ?Sized>(
+        &self,
+        renderer: &mut R,
+        pos: Vec2,
+        size: Vec2,
+        selected: Option<usize>,
+    ) {
+        let cell_w = size.x / self.cols as f32;
+        let cell_h = size.y / self.rows as f32;
+        let border = (cell_w.min(cell_h) / 16.0).max(2.0);
+
+        renderer.draw_rectangle(pos.x, pos.y, size.x, size.y, color::LIGHTGRAY);
+        let exit = self.exit();
+        renderer.draw_rectangle_lines(
+            pos.x + exit.x as f32 * cell_w,
+            pos.y + exit.y as f32 * cell_h,
+            exit.width as f32 * cell_w,
+            exit.height as f32 * cell_h,
+            border,
+            color::RED,
+        );
+
+        let gap = cell_w.min(cell_h) * 0.05;
+        for (i, block) in self.blocks.iter().enumerate() {
+            let color: Color = if i == self.target {
+                color::RED
+            } else {
+                color::BROWN
+            };
+            let x = pos.x + block.x as f32 * cell_w + gap;
+            let y = pos.y + block.y as f32 * cell_h + gap;
+            let w = block.width as f32 * cell_w - 2.0 * gap;
+            let h = block.height as f32 * cell_h - 2.0 * gap;
+            renderer.draw_rectangle(x, y, w, h, color);
+            if selected == Some(i) {
+                renderer.draw_rectangle_lines(x, y, w, h, border, color::YELLOW);
+            }
+        }
+
+        renderer.draw_rectangle_lines(pos.x, pos.y, size.x, size.y, border, color::BLACK);
+    }
+}
+
+/// A named puzzle from a level pack.
+#[derive(Debug, Clone)]
+pub struct Level {
+    pub name: String,
+    pub puzzle: Klotski,
+    /// Length of the shortest solution, if known.
+    pub optimal: Option<usize>,
+}
+
+/// Parses a level pack.
+///
+/// Each level starts with `level NAME`, followed by `goal LETTER X,Y` (the
+/// block that has to reach the exit, and where its top-left cell has to be),
+/// optionally `optimal MOVES`, and the board as read by [`Klotski::parse`].
+/// Lines starting with `#` are ignored.
+pub fn parse_levels(text: &str) -> Result<Vec<Level>, KlotskiError> {
+    struct Pending {
+        name: String,
+        line: usize,
+        goal: Option<(char, Cell)>,
+        optimal: Option<usize>,
+        grid: String,
+    }
+
+    fn finish(pending: Pending) -> Result<Level, KlotskiError> {
+        let (target, exit) = pending.goal.ok_or(KlotskiError::Syntax {
+            line: pending.line,
+            message: format!("level `{}` has no goal", pending.name),
+        })?;
+        let puzzle = Klotski::parse(&pending.grid, target, exit).map_err(|e| match e {
+            KlotskiError::Syntax { line, message } => KlotskiError::Syntax {
+                line: pending.line + line,
+                message,
+            },
+            e => e,
+        })?;
+        Ok(Level {
+            name: pending.name,
+            puzzle,
+            optimal: pending.optimal,
+        })
+    }
+
+    let mut levels = vec![];
+    let mut pending: Option<Pending> = None;
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let syntax = |message: &str| KlotskiError::Syntax {
+            line: line_no,
+            message: message.to_string(),
+        };
+        let trimmed = match line.trim() {
+            comment if comment.starts_with('#') => "",
+            trimmed => trimmed,
+        };
+
+        if let Some(name) = trimmed.strip_prefix("level ") {
+            if let Some(level) = pending.take() {
+                levels.push(finish(level)?);
+            }
+            pending = Some(Pending {
+                name: name.trim().to_string(),
+                line: line_no,
+                goal: None,
+                optimal: None,
+                grid: String::new(),
+            });
+            continue;
+        }
+
+        let Some(level) = pending.as_mut() else {
+            if trimmed.is_empty() {
+                continue;
+            }
+            return Err(syntax("expected `level NAME`"));
+        };
+        if let Some(goal) = trimmed.strip_prefix("goal ") {
+            let parsed = goal.split_once(' ').and_then(|(name, at)| {
+                let (x, y) = at.trim().split_once(',')?;
+                let name = name.chars().next()?;
+                Some((name, cell!(x.parse().ok()?, y.parse().ok()?)))
+            });
+            level.goal = Some(parsed.ok_or_else(|| syntax("expected `goal LETTER X,Y`"))?);
+        } else if let Some(optimal) = trimmed.strip_prefix("optimal ") {
+            let optimal = optimal
+                .trim()
+                .parse()
+                .map_err(|_| syntax("expected `optimal MOVES`"))?;
+            level.optimal = Some(optimal);
+        } else {
+            level.grid += trimmed;
+        }
+        // one grid line per file line, so errors point into the file
+        level.grid += "\n";
+    }
+    if let Some(level) = pending {
+        levels.push(finish(level)?);
+    }
+    Ok(levels)
+}
+
+/// Classic Huarong Dao layouts.
+pub fn builtin_levels() -> Vec<Level> {
+    parse_levels(include_str!("../assets/klotski/classic.txt")).unwrap()
+}
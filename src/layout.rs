@@ -0,0 +1,97 @@
+use macroquad::math::{Rect, Vec2, vec2};
+
+/// Spacing and panel sizes used to split the window into regions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutConfig {
+    /// Gap kept between the window edge and every region.
+    pub margin: f32,
+    /// Height of the text area above the board.
+    pub header_height: f32,
+    /// Preferred width of each side panel.
+    pub side_panel_width: f32,
+    /// Side panels are dropped when the board's longer side would get smaller
+    /// than this.
+    pub min_board_size: f32,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            margin: 20.0,
+            header_height: 60.0,
+            side_panel_width: 280.0,
+            min_board_size: 400.0,
+        }
+    }
+}
+
+/// Screen regions for one frame.
+///
+/// Recompute it every frame from `screen_width()` / `screen_height()` so the
+/// board follows window resizes. Panels have zero width when there is no room
+/// for them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub header: Rect,
+    pub board: Rect,
+    pub left_panel: Rect,
+    pub right_panel: Rect,
+}
+
+impl Layout {
+    /// Places a `rows` x `cols` board with square cells in a `screen` sized
+    /// window.
+    pub fn compute(screen: Vec2, rows: usize, cols: usize, config: &LayoutConfig) -> Self {
+        let margin = config.margin;
+        let body_top = margin + config.header_height;
+        let body_height = (screen.y - body_top - margin).max(0.0);
+
+        let full_width = (screen.x - margin * 2.0).max(0.0);
+        let with_panels = full_width - (config.side_panel_width + margin) * 2.0;
+        let panel_width = if fit(vec2(with_panels, body_height), rows, cols).max_element()
+            >= config.min_board_size
+        {
+            config.side_panel_width
+        } else {
+            0.0
+        };
+
+        let side = if panel_width > 0.0 {
+            panel_width + margin
+        } else {
+            0.0
+        };
+        let board_area = Rect::new(
+            margin + side,
+            body_top,
+            (full_width - side * 2.0).max(0.0),
+            body_height,
+        );
+        let board_size = fit(board_area.size(), rows, cols);
+        let board = Rect::new(
+            board_area.x + (board_area.w - board_size.x) / 2.0,
+            board_area.y + (board_area.h - board_size.y) / 2.0,
+            board_size.x,
+            board_size.y,
+        );
+
+        Layout {
+            header: Rect::new(margin, margin, full_width, config.header_height),
+            board,
+            left_panel: Rect::new(margin, body_top, panel_width, body_height),
+            right_panel: Rect::new(
+                screen.x - margin - panel_width,
+                body_top,
+                panel_width,
+                body_height,
+            ),
+        }
+    }
+}
+
+/// Largest size with square cells for a `rows` x `cols` board that fits in
+/// `area`.
+fn fit(area: Vec2, rows: usize, cols: usize) -> Vec2 {
+    let cell = (area.x / cols as f32).min(area.y / rows as f32).max(0.0);
+    vec2(cell * cols as f32, cell * rows as f32)
+}
@@ -0,0 +1,322 @@
+//! Level packs: named puzzles played in order, each one unlocked by finishing
+//! the one before it.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::State;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LevelError {
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The goal of the level can't be reached from its start.
+    Unreachable {
+        name: String,
+    },
+    /// The pack has no levels.
+    Empty,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            LevelError::Unreachable { name } => {
+                write!(
+                    f,
+                    "level `{}`: the goal can't be reached from the start",
+                    name
+                )
+            }
+            LevelError::Empty => write!(f, "the pack has no levels"),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub name: String,
+    pub start: State,
+    pub goal: State,
+    /// Moves a good solution takes.
+    pub par: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LevelPack {
+    /// Names the pack in the saved progress.
+    pub name: String,
+    pub levels: Vec<Level>,
+}
+
+impl LevelPack {
+    /// Parses a level pack.
+    ///
+    /// Each level starts with `level NAME`, followed by `size ROWSxCOLS`,
+    /// optionally `par MOVES`, and the starting rows of tile numbers with `.`
+    /// for the blank. The goal is the solved board unless a `goal` line
+    /// follows with rows of its own. Lines starting with `#` are ignored.
+    pub fn parse(name: &str, text: &str) -> Result<Self, LevelError> {
+        struct Pending {
+            name: String,
+            line: usize,
+            size: Option<(usize, usize)>,
+            par: Option<usize>,
+            start: Vec<(usize, Vec<usize>)>,
+            goal: Option<Vec<(usize, Vec<usize>)>>,
+        }
+
+        fn board(
+            pending: &Pending,
+            (rows, cols): (usize, usize),
+            grid: &[(usize, Vec<usize>)],
+        ) -> Result<State, LevelError> {
+            if grid.len() != rows {
+                return Err(LevelError::Syntax {
+                    line: grid.last().map_or(pending.line, |(line, _)| *line),
+                    message: format!(
+                        "level `{}` needs {} rows, found {}",
+                        pending.name,
+                        rows,
+                        grid.len()
+                    ),
+                });
+            }
+            if let Some((line, row)) = grid.iter().find(|(_, row)| row.len() != cols) {
+                return Err(LevelError::Syntax {
+                    line: *line,
+                    message: format!("expected {} tiles, found {}", cols, row.len()),
+                });
+            }
+            let numbers = grid.iter().flat_map(|(_, row)| row).copied();
+            State::from_numbers(rows, cols, &numbers.collect::<Vec<_>>()).map_err(|e| {
+                LevelError::Syntax {
+                    line: grid.first().map_or(pending.line, |(line, _)| *line),
+                    message: e.to_string(),
+                }
+            })
+        }
+
+        fn finish(pending: Pending) -> Result<Level, LevelError> {
+            let syntax = |message: String| LevelError::Syntax {
+                line: pending.line,
+                message,
+            };
+            let size = pending
+                .size
+                .ok_or_else(|| syntax(format!("level `{}` has no size", pending.name)))?;
+            let start = board(&pending, size, &pending.start)?;
+            let goal = match &pending.goal {
+                Some(grid) => board(&pending, size, grid)?,
+                None => State::new(size.0, size.1),
+            };
+            if start == goal {
+                return Err(syntax(format!("level `{}` starts solved", pending.name)));
+            }
+            if !can_reach(&start, &goal) {
+                return Err(LevelError::Unreachable { name: pending.name });
+            }
+            Ok(Level {
+                name: pending.name,
+                start,
+                goal,
+                par: pending.par,
+            })
+        }
+
+        let mut levels = vec![];
+        let mut pending: Option<Pending> = None;
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let syntax = |message: &str| LevelError::Syntax {
+                line: line_no,
+                message: message.to_string(),
+            };
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = trimmed.strip_prefix("level ") {
+                if let Some(level) = pending.take() {
+                    levels.push(finish(level)?);
+                }
+                pending = Some(Pending {
+                    name: name.trim().to_string(),
+                    line: line_no,
+                    size: None,
+                    par: None,
+                    start: vec![],
+                    goal: None,
+                });
+                continue;
+            }
+
+            let Some(level) = pending.as_mut() else {
+                return Err(syntax("expected `level NAME`"));
+            };
+            if let Some(size) = trimmed.strip_prefix("size ") {
+                let parsed = size
+                    .trim()
+                    .split_once('x')
+                    .and_then(|(rows, cols)| Some((rows.parse().ok()?, cols.parse().ok()?)));
+                level.size = Some(parsed.ok_or_else(|| syntax("expected `size ROWSxCOLS`"))?);
+            } else if let Some(par) = trimmed.strip_prefix("par ") {
+                let par = par
+                    .trim()
+                    .parse()
+                    .map_err(|_| syntax("expected `par MOVES`"))?;
+                level.par = Some(par);
+            } else if trimmed == "goal" {
+                if level.goal.is_some() {
+                    return Err(syntax("the goal is already given"));
+                }
+                level.goal = Some(vec![]);
+            } else {
+                let row = trimmed
+                    .split_whitespace()
+                    .map(|token| match token {
+                        "." => Some(0),
+                        n => n.parse().ok().filter(|&n| n > 0),
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| syntax("expected tile numbers or `.` for the blank"))?;
+                match level.goal.as_mut() {
+                    Some(goal) => goal.push((line_no, row)),
+                    None => level.start.push((line_no, row)),
+                }
+            }
+        }
+        if let Some(level) = pending {
+            levels.push(finish(level)?);
+        }
+        if levels.is_empty() {
+            return Err(LevelError::Empty);
+        }
+        Ok(LevelPack {
+            name: name.to_string(),
+            levels,
+        })
+    }
+
+    /// The pack that ships with the game.
+    pub fn builtin() -> Self {
+        LevelPack::parse("builtin", include_str!("../assets/levels/builtin.txt")).unwrap()
+    }
+}
+
+/// Whether sliding tiles can turn `start` into `goal`, two boards of the same
+/// size.
+///
+/// Every move swaps the blank with a neighbour, so the permutation between
+/// the boards must be even exactly when the blank has an even distance to
+/// travel. On a single line the tiles also have to be in the same order.
+pub fn can_reach(start: &State, goal: &State) -> bool {
+    let (from, to) = (start.to_numbers(), goal.to_numbers());
+    if start.is_line() {
+        return from
+            .iter()
+            .filter(|&&n| n != 0)
+            .eq(to.iter().filter(|&&n| n != 0));
+    }
+
+    let mut cell_in_goal = vec![0; to.len()];
+    for (i, &n) in to.iter().enumerate() {
+        cell_in_goal[n] = i;
+    }
+    let mut perm = from.iter().map(|&n| cell_in_goal[n]).collect::<Vec<_>>();
+    let mut swaps = 0usize;
+    for i in 0..perm.len() {
+        while perm[i] != i {
+            let j = perm[i];
+            perm.swap(i, j);
+            swaps += 1;
+        }
+    }
+    let distance = start.blank_cell().manhattan_distance(&goal.blank_cell());
+    swaps.is_multiple_of(2) == distance.is_multiple_of(2)
+}
+
+/// A finished level.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Completion {
+    pub pack: String,
+    pub level: String,
+    /// The fewest moves the level was finished in.
+    pub moves: usize,
+    pub seconds: f64,
+}
+
+/// Levels finished so far, in every pack.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Progress {
+    pub completed: Vec<Completion>,
+}
+
+impl Progress {
+    /// `levels.json` in the platform's data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("slide-puzzle").join("levels.json"))
+    }
+
+    /// Loads progress from `path`; a missing file means nothing is finished
+    /// yet.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Progress::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    pub fn get(&self, pack: &str, level: &str) -> Option<&Completion> {
+        self.completed
+            .iter()
+            .find(|c| c.pack == pack && c.level == level)
+    }
+
+    /// Records `completion`, keeping only the best result of each level.
+    /// Returns true if it was a first finish or took fewer moves.
+    pub fn record(&mut self, completion: Completion) -> bool {
+        match self
+            .completed
+            .iter_mut()
+            .find(|c| c.pack == completion.pack && c.level == completion.level)
+        {
+            Some(best) if best.moves <= completion.moves => false,
+            Some(best) => {
+                *best = completion;
+                true
+            }
+            None => {
+                self.completed.push(completion);
+                true
+            }
+        }
+    }
+
+    /// The first level of a pack is always open; every other one opens once
+    /// the level before it is finished.
+    pub fn is_unlocked(&self, pack: &LevelPack, index: usize) -> bool {
+        index == 0 || self.get(&pack.name, &pack.levels[index - 1].name).is_some()
+    }
+}
@@ -1,8 +1,98 @@
+use std::error::Error;
+use std::fmt;
+
 use macroquad::color::Color;
 use macroquad::math::{Vec2, vec2};
-use macroquad::{color, shapes, text};
+use serde::{Deserialize, Serialize};
+
+pub mod analysis;
+pub mod bench;
+pub mod config;
+pub mod daily;
+pub mod export;
+pub mod generate;
+pub mod geometry;
+pub mod input;
+pub mod klotski;
+pub mod layout;
+pub mod levels;
+pub mod loopover;
+pub mod net;
+pub mod packed;
+pub mod race;
+pub mod rank;
+pub mod render;
+pub mod replay;
+pub mod scene;
+pub mod solver;
+pub mod state;
+pub mod theme;
+pub mod tui;
+pub mod variant;
+
+pub use geometry::Geometry;
+pub use input::{Direction, Drag, PointerEvent, drag_board, pointer_events};
+pub use layout::{Layout, LayoutConfig};
+pub use loopover::Loopover;
+pub use render::{DrawCall, MacroquadRenderer, RecordingRenderer, Renderer};
+pub use solver::{AStarSolver, BFSSolver, MySolver, OracleSolver, SolvePlan, Solver, SolverKind};
+pub use state::{State, StateError};
+pub use theme::Theme;
+pub use variant::{Square, VariantState};
+
+/// Smallest number of rows or columns a board may have.
+pub const MIN_BOARD_SIZE: usize = 1;
+/// Largest number of rows or columns a board may have.
+pub const MAX_BOARD_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardSizeError {
+    OutOfRange { value: usize },
+    SingleCell,
+}
+
+impl fmt::Display for BoardSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardSizeError::OutOfRange { value } => write!(
+                f,
+                "board dimension {} is out of range (supported: {}..={})",
+                value, MIN_BOARD_SIZE, MAX_BOARD_SIZE
+            ),
+            BoardSizeError::SingleCell => {
+                write!(
+                    f,
+                    "a 1x1 board has no tile to slide (needs at least 2 cells)"
+                )
+            }
+        }
+    }
+}
+
+impl Error for BoardSizeError {}
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd)]
+/// Checks a single row or column count against the supported range.
+pub fn validate_board_dimension(value: usize) -> Result<usize, BoardSizeError> {
+    if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&value) {
+        return Err(BoardSizeError::OutOfRange { value });
+    }
+    Ok(value)
+}
+
+/// Checks that `rows` x `cols` describes a playable board.
+///
+/// Both dimensions must be within `MIN_BOARD_SIZE..=MAX_BOARD_SIZE` and the
+/// board needs at least two cells so that there is one tile and one blank.
+pub fn validate_board_size(rows: usize, cols: usize) -> Result<(), BoardSizeError> {
+    validate_board_dimension(rows)?;
+    validate_board_dimension(cols)?;
+    if rows * cols < 2 {
+        return Err(BoardSizeError::SingleCell);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct Cell {
     pub x: usize,
     pub y: usize,
@@ -60,12 +150,25 @@ pub struct Board {
     cell_size: Vec2,
 
     pieces: Vec<Option<Piece>>,
+    offsets: Vec<Vec2>,
+    walls: Vec<bool>,
+    /// Number of the tile that belongs in each cell; tiles there are green.
+    goal: Vec<usize>,
+    /// Rows and columns wrap around.
+    wrap: bool,
+    geometry: Geometry,
+    theme: Theme,
+    /// The solver's next step, drawn over the tiles.
+    plan: Option<SolvePlan>,
 }
 
 impl Board {
     pub fn new(size: Vec2, border_width: f32, cell: (usize, usize)) -> Self {
         let cell_size = size / vec2(cell.1 as f32, cell.0 as f32);
         let pieces = vec![None; cell.0 * cell.1];
+        let offsets = vec![Vec2::ZERO; cell.0 * cell.1];
+        let walls = vec![false; cell.0 * cell.1];
+        let goal = (1..=cell.0 * cell.1).collect();
         Board {
             size,
             border_width,
@@ -73,6 +176,47 @@ impl Board {
             cols: cell.1,
             cell_size,
             pieces,
+            offsets,
+            walls,
+            goal,
+            wrap: false,
+            geometry: Geometry::Square,
+            theme: Theme::LIGHT,
+            plan: None,
+        }
+    }
+
+    /// Changes the drawn size of the board and recomputes the cell size.
+    pub fn resize(&mut self, size: Vec2) {
+        self.size = size;
+        self.cell_size = size / vec2(self.cols as f32, self.rows as f32);
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
+    }
+
+    pub fn cell_size(&self) -> Vec2 {
+        self.cell_size
+    }
+
+    /// Draws the piece in `cell` shifted by `offset`, e.g. while it is dragged.
+    pub fn set_offset(&mut self, cell: Cell, offset: Vec2) {
+        let idx = cell.as_index(self.rows, self.cols);
+        self.offsets[idx] = offset;
+    }
+
+    pub fn clear_offsets(&mut self) {
+        self.offsets.fill(Vec2::ZERO);
+    }
+
+    /// Shifts the tiles of `drag`, if any, and puts every other tile back.
+    pub fn show_drag(&mut self, drag: Option<&Drag>) {
+        self.clear_offsets();
+        if let Some(drag) = drag {
+            for &cell in drag.cells() {
+                self.set_offset(cell, drag.offset());
+            }
         }
     }
 
@@ -83,7 +227,92 @@ impl Board {
         self.pieces[idx] = piece;
     }
 
+    pub fn put_wall(&mut self, cell: Cell, wall: bool) {
+        let idx = cell.as_index(self.rows, self.cols);
+        self.walls[idx] = wall;
+    }
+
+    /// Colors tiles by where they are in `goal` instead of the solved board.
+    pub fn set_goal(&mut self, goal: &State) {
+        self.goal = goal.to_numbers();
+    }
+
+    /// Shows the tiles across each edge of a board whose rows and columns wrap
+    /// around.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Changes the shape of the cells. Square cells stretch to fill the board;
+    /// other shapes keep their proportions and are centered.
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Draws `plan` over the tiles: its locked cells shaded and arrows along
+    /// the blank's and the target tile's paths. Only square cells show it.
+    pub fn set_plan(&mut self, plan: Option<SolvePlan>) {
+        self.plan = plan;
+    }
+
+    /// Scale and top-left corner of a grid of non-square cells.
+    fn grid_transform(&self) -> (f32, Vec2) {
+        let extent = self.geometry.extent(self.rows, self.cols);
+        let scale = (self.size.x / extent.x).min(self.size.y / extent.y);
+        (scale, (self.size - extent * scale) / 2.0)
+    }
+
+    /// Center of `cell`, relative to the board's top-left corner.
+    pub fn cell_center(&self, cell: Cell) -> Vec2 {
+        match self.geometry {
+            Geometry::Square => {
+                (vec2(cell.x as f32, cell.y as f32) + vec2(0.5, 0.5)) * self.cell_size
+            }
+            _ => {
+                let (scale, origin) = self.grid_transform();
+                origin + self.geometry.center(cell) * scale
+            }
+        }
+    }
+
+    /// Copies every piece of `state` onto the board.
+    pub fn apply_state(&mut self, state: &State) {
+        for (i, piece) in state.pieces().iter().enumerate() {
+            self.put_piece(cell!(i, state.rows(), state.cols()), *piece);
+        }
+    }
+
+    /// Copies the tiles and walls of `state` onto the board.
+    pub fn apply_variant(&mut self, state: &VariantState) {
+        for (i, square) in state.squares().iter().enumerate() {
+            let cell = cell!(i, state.rows(), state.cols());
+            let piece = match square {
+                Square::Tile(num) => Some(Piece::new(*num)),
+                _ => None,
+            };
+            self.put_piece(cell, piece);
+            self.put_wall(cell, *square == Square::Wall);
+        }
+    }
+
+    /// Copies the tiles of a Loopover board onto the board.
+    pub fn apply_loopover(&mut self, board: &Loopover) {
+        for (i, &num) in board.tiles().iter().enumerate() {
+            self.put_piece(cell!(i, board.rows(), board.cols()), Some(Piece::new(num)));
+        }
+    }
+
     pub fn get_cell(&mut self, pos: Vec2) -> Option<Cell> {
+        if self.geometry != Geometry::Square {
+            let (scale, origin) = self.grid_transform();
+            return self
+                .geometry
+                .cell_at((pos - origin) / scale, self.rows, self.cols);
+        }
         if pos.x < 0.0 || pos.y < 0.0 || pos.x >= self.size.x || pos.y >= self.size.y {
             return None;
         }
@@ -93,54 +322,72 @@ impl Board {
         Some(cell!(cell_x, cell_y))
     }
 
-    pub fn draw(&self, pos: Vec2) {
+    /// Draws the board with its top-left corner at `pos`.
+    pub fn draw<R: Renderer + ?Sized>(&self, renderer: &mut R, pos: Vec2) {
+        if self.geometry != Geometry::Square {
+            self.draw_cells(renderer, pos);
+            return;
+        }
         let cell_width = self.cell_size.x;
         let cell_height = self.cell_size.y;
 
         for i in 1..=self.cols {
             let x = i as f32 * cell_width;
-            draw_relative_line(
-                pos,
-                x,
-                0.0,
-                x,
-                self.size.y,
+            renderer.draw_line(
+                pos.x + x,
+                pos.y,
+                pos.x + x,
+                pos.y + self.size.y,
                 self.border_width / 2.0,
-                color::GRAY,
+                self.theme.grid,
             );
         }
         for j in 1..=self.rows {
             let y = j as f32 * cell_height;
-            draw_relative_line(
-                pos,
-                0.0,
-                y,
-                self.size.x,
-                y,
+            renderer.draw_line(
+                pos.x,
+                pos.y + y,
+                pos.x + self.size.x,
+                pos.y + y,
                 self.border_width / 2.0,
-                color::GRAY,
+                self.theme.grid,
+            );
+        }
+
+        for (i, _) in self.walls.iter().enumerate().filter(|(_, wall)| **wall) {
+            renderer.draw_rectangle(
+                pos.x + (i % self.cols) as f32 * cell_width,
+                pos.y + (i / self.cols) as f32 * cell_height,
+                cell_width,
+                cell_height,
+                self.theme.wall,
             );
         }
 
         let box_width = cell_width * 0.90;
         let box_height = cell_height * 0.90;
 
+        if self.wrap {
+            self.draw_wrap(renderer, pos, box_width, box_height);
+        }
+
         let font_size = (cell_width.min(cell_height) * 0.75) as u16;
         for (i, piece) in self.pieces.iter().enumerate() {
             if let Some(piece) = piece {
                 let cell_x = (i % self.cols) as f32;
                 let cell_y = (i / self.cols) as f32;
 
-                let cell_center_x = cell_x * cell_width + cell_width / 2.0;
-                let cell_center_y = cell_y * cell_height + cell_height / 2.0;
+                let offset = self.offsets[i];
+                let cell_center_x = cell_x * cell_width + cell_width / 2.0 + offset.x;
+                let cell_center_y = cell_y * cell_height + cell_height / 2.0 + offset.y;
 
-                let rect_color = if piece.num == i + 1 {
-                    color::GREEN
+                let rect_color = if piece.num == self.goal[i] {
+                    self.theme.tile_in_place
                 } else {
-                    color::BROWN
+                    self.theme.tile
                 };
 
-                shapes::draw_rectangle(
+                renderer.draw_rectangle(
                     pos.x + cell_center_x - box_width / 2.0,
                     pos.y + cell_center_y - box_height / 2.0,
                     box_width,
@@ -149,39 +396,173 @@ impl Board {
                 );
 
                 let text = &format!("{}", piece.num);
-                let center = text::get_text_center(text, None, font_size, 1.0, 0.0);
-                text::draw_text_ex(
+                let center = renderer.text_center(text, font_size);
+                renderer.draw_text(
                     text,
                     pos.x + cell_center_x - center.x,
                     pos.y + cell_center_y - center.y,
-                    text::TextParams {
-                        font_size,
-                        color: color::BLACK,
-                        ..Default::default()
-                    },
+                    font_size,
+                    self.theme.tile_text,
                 );
             }
         }
 
-        shapes::draw_rectangle_lines(
+        if let Some(plan) = &self.plan {
+            self.draw_plan(renderer, pos, plan);
+        }
+
+        renderer.draw_rectangle_lines(
             pos.x,
             pos.y,
             self.size.x,
             self.size.y,
             self.border_width,
-            color::BLACK,
+            self.theme.border,
         );
     }
+
+    fn draw_plan<R: Renderer + ?Sized>(&self, renderer: &mut R, pos: Vec2, plan: &SolvePlan) {
+        for (i, _) in plan
+            .locked
+            .iter()
+            .enumerate()
+            .filter(|(_, locked)| **locked)
+        {
+            renderer.draw_rectangle(
+                pos.x + (i % self.cols) as f32 * self.cell_size.x,
+                pos.y + (i / self.cols) as f32 * self.cell_size.y,
+                self.cell_size.x,
+                self.cell_size.y,
+                self.theme.locked,
+            );
+        }
+        let thickness = self.cell_size.min_element() * 0.08;
+        for (path, color) in [
+            (&plan.blank_path, self.theme.blank_path),
+            (&plan.tile_path, self.theme.tile_path),
+        ] {
+            let points = path.iter().map(|&cell| pos + self.cell_center(cell));
+            draw_arrow(renderer, &points.collect::<Vec<_>>(), thickness, color);
+        }
+    }
+
+    /// Draws hexagonal or triangular cells: outlines, walls, then the tiles
+    /// shrunk towards their centers.
+    fn draw_cells<R: Renderer + ?Sized>(&self, renderer: &mut R, pos: Vec2) {
+        let (scale, origin) = self.grid_transform();
+        let at = |p: Vec2| pos + origin + p * scale;
+        let cells = (0..self.rows * self.cols).map(|i| cell!(i, self.rows, self.cols));
+
+        for cell in cells.clone() {
+            let corners = self.geometry.corners(cell);
+            for (i, &a) in corners.iter().enumerate() {
+                let b = at(corners[(i + 1) % corners.len()]);
+                let a = at(a);
+                renderer.draw_line(a.x, a.y, b.x, b.y, self.border_width / 2.0, self.theme.grid);
+            }
+        }
+        for (i, cell) in cells.clone().enumerate() {
+            if self.walls[i] {
+                let corners = self.geometry.corners(cell).into_iter().map(at);
+                fill_polygon(renderer, &corners.collect::<Vec<_>>(), self.theme.wall);
+            }
+        }
+
+        let font_size = (self.geometry.inradius() * scale * 1.5) as u16;
+        for (i, cell) in cells.enumerate() {
+            let Some(piece) = self.pieces[i] else {
+                continue;
+            };
+            let center = self.geometry.center(cell);
+            let shift = self.offsets[i];
+            let corners = self
+                .geometry
+                .corners(cell)
+                .into_iter()
+                .map(|c| at(center + (c - center) * 0.9) + shift)
+                .collect::<Vec<_>>();
+            let rect_color = if piece.num == self.goal[i] {
+                self.theme.tile_in_place
+            } else {
+                self.theme.tile
+            };
+            fill_polygon(renderer, &corners, rect_color);
+
+            let text = &format!("{}", piece.num);
+            let text_center = renderer.text_center(text, font_size);
+            let center = at(center) + shift;
+            renderer.draw_text(
+                text,
+                center.x - text_center.x,
+                center.y - text_center.y,
+                font_size,
+                self.theme.tile_text,
+            );
+        }
+    }
+
+    /// Faded slivers outside each edge showing what lies across the wrap:
+    /// above the top row the bottom row, left of the first column the last,
+    /// and so on.
+    fn draw_wrap<R: Renderer + ?Sized>(&self, renderer: &mut R, pos: Vec2, box_w: f32, box_h: f32) {
+        let depth = self.border_width * 1.5;
+        let color_at = |x: usize, y: usize| {
+            let i = y * self.cols + x;
+            let color = match self.pieces[i] {
+                Some(piece) if piece.num == self.goal[i] => self.theme.tile_in_place,
+                Some(_) => self.theme.tile,
+                None if self.walls[i] => self.theme.wall,
+                None => return None,
+            };
+            Some(Color { a: 0.5, ..color })
+        };
+
+        for x in 0..self.cols {
+            let left = pos.x + (x as f32 + 0.5) * self.cell_size.x - box_w / 2.0;
+            if let Some(color) = color_at(x, self.rows - 1) {
+                renderer.draw_rectangle(left, pos.y - depth, box_w, depth, color);
+            }
+            if let Some(color) = color_at(x, 0) {
+                renderer.draw_rectangle(left, pos.y + self.size.y, box_w, depth, color);
+            }
+        }
+        for y in 0..self.rows {
+            let top = pos.y + (y as f32 + 0.5) * self.cell_size.y - box_h / 2.0;
+            if let Some(color) = color_at(self.cols - 1, y) {
+                renderer.draw_rectangle(pos.x - depth, top, depth, box_h, color);
+            }
+            if let Some(color) = color_at(0, y) {
+                renderer.draw_rectangle(pos.x + self.size.x, top, depth, box_h, color);
+            }
+        }
+    }
+}
+
+/// Lines through `points` with a head on the last one.
+fn draw_arrow<R: Renderer + ?Sized>(
+    renderer: &mut R,
+    points: &[Vec2],
+    thickness: f32,
+    color: Color,
+) {
+    let [.., from, tip] = points else {
+        return;
+    };
+    let direction = (*tip - *from).normalize_or_zero();
+    let head = thickness * 3.0;
+    let base = *tip - direction * head;
+    // the last line stops where the head starts
+    let ends = points[1..points.len() - 1].iter().copied().chain([base]);
+    for (start, end) in points.iter().zip(ends) {
+        renderer.draw_line(start.x, start.y, end.x, end.y, thickness, color);
+    }
+    let side = vec2(-direction.y, direction.x) * head / 2.0;
+    renderer.draw_triangle(*tip, base + side, base - side, color);
 }
 
-#[inline]
-fn draw_relative_line(pos: Vec2, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
-    shapes::draw_line(
-        pos.x + x1,
-        pos.y + y1,
-        pos.x + x2,
-        pos.y + y2,
-        thickness,
-        color,
-    )
+/// Fills a convex polygon as a fan of triangles.
+fn fill_polygon<R: Renderer + ?Sized>(renderer: &mut R, corners: &[Vec2], color: Color) {
+    for i in 1..corners.len().saturating_sub(1) {
+        renderer.draw_triangle(corners[0], corners[i], corners[i + 1], color);
+    }
 }
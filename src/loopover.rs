@@ -0,0 +1,336 @@
+//! Loopover: every cell holds a tile and a move rotates a whole row or column
+//! by one cell, the tile pushed off one end coming back in at the other.
+//!
+//! Tile `n` belongs in cell `n - 1` (row-major). A rotation of a line of even
+//! length is an odd permutation, so if either side is even every arrangement
+//! can be solved; if both are odd only even permutations can. Boards one cell
+//! wide only have a single line and can only be rotated back.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use rand::Rng;
+
+use crate::{BoardSizeError, Cell, cell, validate_board_size};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Line {
+    Row(usize),
+    Col(usize),
+}
+
+/// Rotates `line` by one cell: forward is right for a row and down for a
+/// column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    pub line: Line,
+    pub forward: bool,
+}
+
+impl Rotation {
+    pub fn inverse(self) -> Self {
+        Rotation {
+            forward: !self.forward,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoopoverError {
+    Size(BoardSizeError),
+    /// The numbers aren't `1..=rows * cols`, each once.
+    Tiles,
+}
+
+impl fmt::Display for LoopoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoopoverError::Size(e) => e.fmt(f),
+            LoopoverError::Tiles => write!(f, "tiles must be the numbers 1 to rows * cols"),
+        }
+    }
+}
+
+impl Error for LoopoverError {}
+
+impl From<BoardSizeError> for LoopoverError {
+    fn from(e: BoardSizeError) -> Self {
+        LoopoverError::Size(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Loopover {
+    rows: usize,
+    cols: usize,
+    tiles: Vec<usize>,
+}
+
+impl Loopover {
+    /// The solved board.
+    pub fn new(rows: usize, cols: usize) -> Result<Self, LoopoverError> {
+        validate_board_size(rows, cols)?;
+        Ok(Loopover {
+            rows,
+            cols,
+            tiles: (1..=rows * cols).collect(),
+        })
+    }
+
+    /// A board with the tiles in row-major order.
+    pub fn from_numbers(rows: usize, cols: usize, tiles: &[usize]) -> Result<Self, LoopoverError> {
+        validate_board_size(rows, cols)?;
+        let mut sorted = tiles.to_vec();
+        sorted.sort();
+        if !sorted.iter().copied().eq(1..=rows * cols) {
+            return Err(LoopoverError::Tiles);
+        }
+        Ok(Loopover {
+            rows,
+            cols,
+            tiles: tiles.to_vec(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn tiles(&self) -> &[usize] {
+        &self.tiles
+    }
+
+    /// Every rotation, forward and back, of every line longer than one cell.
+    pub fn rotations(&self) -> Vec<Rotation> {
+        let rows = (0..self.rows).filter(|_| self.cols > 1).map(Line::Row);
+        let cols = (0..self.cols).filter(|_| self.rows > 1).map(Line::Col);
+        rows.chain(cols)
+            .flat_map(|line| [true, false].map(|forward| Rotation { line, forward }))
+            .collect()
+    }
+
+    /// Where the tile in cell `idx` ends up after `rotation`.
+    fn moved(&self, idx: usize, rotation: Rotation) -> usize {
+        let cell = cell!(idx, self.rows, self.cols);
+        let step = |i: usize, len: usize| {
+            if rotation.forward {
+                (i + 1) % len
+            } else {
+                (i + len - 1) % len
+            }
+        };
+        let cell = match rotation.line {
+            Line::Row(y) if y == cell.y => cell!(step(cell.x, self.cols), cell.y),
+            Line::Col(x) if x == cell.x => cell!(cell.x, step(cell.y, self.rows)),
+            _ => cell,
+        };
+        cell.as_index(self.rows, self.cols)
+    }
+
+    pub fn rotate(&mut self, rotation: Rotation) {
+        let mut tiles = self.tiles.clone();
+        for (i, &tile) in self.tiles.iter().enumerate() {
+            tiles[self.moved(i, rotation)] = tile;
+        }
+        self.tiles = tiles;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tiles.iter().enumerate().all(|(i, &n)| n == i + 1)
+    }
+
+    pub fn is_solvable(&self) -> bool {
+        if self.rows == 1 || self.cols == 1 {
+            let len = self.tiles.len();
+            return (0..len).all(|i| self.tiles[(i + 1) % len] == self.tiles[i] % len + 1);
+        }
+        self.rows.is_multiple_of(2) || self.cols.is_multiple_of(2) || self.is_even()
+    }
+
+    fn is_even(&self) -> bool {
+        let mut perm = self.tiles.iter().map(|n| n - 1).collect::<Vec<_>>();
+        let mut swaps = 0usize;
+        for i in 0..perm.len() {
+            while perm[i] != i {
+                let j = perm[i];
+                perm.swap(i, j);
+                swaps += 1;
+            }
+        }
+        swaps.is_multiple_of(2)
+    }
+
+    /// Shuffles the board by random rotations.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let rotations = self.rotations();
+        let count = rng.random_range(500..=1000);
+        for _ in 0..count {
+            self.rotate(rotations[rng.random_range(0..rotations.len())]);
+        }
+    }
+
+    /// A solution, or `None` if the board is unsolvable.
+    ///
+    /// Tiles are placed in reading order with three-cycles: the commutator
+    /// "row right, column down, row left, column up" cycles three cells and
+    /// leaves every other tile in place, and conjugating it with a few setup
+    /// rotations moves those three cells anywhere. The solutions are far from
+    /// shortest.
+    pub fn solve(&self) -> Option<Vec<Rotation>> {
+        if !self.is_solvable() {
+            return None;
+        }
+        let mut state = self.clone();
+        let mut moves = vec![];
+        let mut apply = |state: &mut Loopover, rotations: &[Rotation]| {
+            for &rotation in rotations {
+                state.rotate(rotation);
+                moves.push(rotation);
+            }
+        };
+
+        if self.rows == 1 || self.cols == 1 {
+            let line = if self.rows == 1 {
+                Line::Row(0)
+            } else {
+                Line::Col(0)
+            };
+            let len = self.tiles.len();
+            // tile 1 is in cell `shift`, and has to go back `shift` cells
+            let shift = self.tiles.iter().position(|&n| n == 1).unwrap();
+            let (forward, count) = if shift * 2 <= len {
+                (false, shift)
+            } else {
+                (true, len - shift)
+            };
+            apply(&mut state, &vec![Rotation { line, forward }; count]);
+            return Some(moves);
+        }
+
+        if !state.is_even() {
+            let line = if self.cols.is_multiple_of(2) {
+                Line::Row(0)
+            } else {
+                Line::Col(0)
+            };
+            apply(
+                &mut state,
+                &[Rotation {
+                    line,
+                    forward: true,
+                }],
+            );
+        }
+
+        let cycles = self.three_cycles();
+        let len = self.tiles.len();
+        for target in 0..len.saturating_sub(2) {
+            let from = state.tiles.iter().position(|&n| n == target + 1).unwrap();
+            if from == target {
+                continue;
+            }
+            let sequence = self.place(from, target, &cycles);
+            apply(&mut state, &sequence);
+        }
+        debug_assert!(state.is_finished());
+        Some(cancel(moves))
+    }
+
+    /// Every commutator on the board, keyed by each step `(a, b)` of the
+    /// three-cycle it performs (the tile in `a` moves to `b`), with the third
+    /// cell of the cycle.
+    fn three_cycles(&self) -> HashMap<(usize, usize), (Vec<Rotation>, usize)> {
+        let mut cycles = HashMap::new();
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                let row = Rotation {
+                    line: Line::Row(y),
+                    forward: true,
+                };
+                let col = Rotation {
+                    line: Line::Col(x),
+                    forward: true,
+                };
+                let commutator = vec![row, col, row.inverse(), col.inverse()];
+                let inverse = commutator.iter().rev().map(|r| r.inverse()).collect();
+                for sequence in [commutator, inverse] {
+                    let mut probe = Loopover::new(self.rows, self.cols).unwrap();
+                    for &rotation in &sequence {
+                        probe.rotate(rotation);
+                    }
+                    // probe.tiles[b] = a + 1: the tile from a is now in b
+                    let step = |a: usize| probe.tiles.iter().position(|&n| n == a + 1).unwrap();
+                    let moved = (0..probe.tiles.len())
+                        .filter(|&a| step(a) != a)
+                        .collect::<Vec<_>>();
+                    debug_assert_eq!(moved.len(), 3);
+                    for &a in &moved {
+                        let b = step(a);
+                        cycles.insert((a, b), (sequence.clone(), step(b)));
+                    }
+                }
+            }
+        }
+        cycles
+    }
+
+    /// Rotations moving the tile in `from` to `target` without disturbing the
+    /// cells before `target`.
+    fn place(
+        &self,
+        from: usize,
+        target: usize,
+        cycles: &HashMap<(usize, usize), (Vec<Rotation>, usize)>,
+    ) -> Vec<Rotation> {
+        // Find setup rotations taking `from` and `target` to two cells of a
+        // three-cycle whose third cell, taken back, is not yet solved. Then
+        // setup, cycle, and undo the setup.
+        let mut seen = HashSet::from([(from, target)]);
+        let mut queue = VecDeque::from([((from, target), vec![])]);
+        while let Some(((a, b), setup)) = queue.pop_front() {
+            if let Some((cycle, c)) = cycles.get(&(a, b)) {
+                let third = setup
+                    .iter()
+                    .rev()
+                    .fold(*c, |cell, &r: &Rotation| self.moved(cell, r.inverse()));
+                if third > target && third != from {
+                    let undo = setup.iter().rev().map(|r: &Rotation| r.inverse());
+                    return setup
+                        .iter()
+                        .copied()
+                        .chain(cycle.iter().copied())
+                        .chain(undo)
+                        .collect();
+                }
+            }
+            for rotation in self.rotations() {
+                let next = (self.moved(a, rotation), self.moved(b, rotation));
+                if seen.insert(next) {
+                    let mut setup = setup.clone();
+                    setup.push(rotation);
+                    queue.push_back((next, setup));
+                }
+            }
+        }
+        unreachable!("no three-cycle reaches cell {}", target)
+    }
+}
+
+/// Drops rotations that are immediately undone.
+fn cancel(moves: Vec<Rotation>) -> Vec<Rotation> {
+    let mut kept: Vec<Rotation> = vec![];
+    for rotation in moves {
+        if kept.last() == Some(&rotation.inverse()) {
+            kept.pop();
+        } else {
+            kept.push(rotation);
+        }
+    }
+    kept
+}
@@ -0,0 +1,323 @@
+//! Races over the local network.
+//!
+//! One player hosts: they pick the position and wait for the other to join
+//! over TCP. The host then sends the position, and from there on both sides
+//! send every slide they make, so each can follow the other's board.
+//! Messages are JSON objects, one per line.
+//!
+//! There is no referee: each side stamps its slides with its own clock, and
+//! the winner is decided by those self-reported times.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Cell, State, StateError};
+
+/// Port used when none is given.
+pub const DEFAULT_PORT: u16 = 7878;
+
+const VERSION: u32 = 1;
+
+/// Longest line a message may take; the position of the largest board fits
+/// easily.
+const MAX_MESSAGE_LEN: usize = 4096;
+
+/// How long joining waits for the host to answer.
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a connection being dropped waits to send what is still queued.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message {
+    /// The position to race on, sent by the host once a player has joined.
+    Start {
+        version: u32,
+        rows: usize,
+        cols: usize,
+        seed: u64,
+        /// Row-major order, `0` being the blank.
+        tiles: Vec<usize>,
+    },
+    /// The sender slid the tiles between `cell` and the blank, `ms` into the
+    /// race by the sender's clock.
+    Slide { cell: Cell, ms: u64 },
+    /// The sender left the race.
+    Leave,
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    /// The other side speaks another version of the protocol.
+    Version(u32),
+    State(StateError),
+    /// A message arrived where another one was expected.
+    Unexpected(Message),
+    /// The other side hung up before the race started.
+    Closed,
+    /// The other side sent more than a message can hold without ending it.
+    TooLong,
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(e) => e.fmt(f),
+            NetError::Format(e) => write!(f, "invalid message: {}", e),
+            NetError::Version(v) => write!(
+                f,
+                "the host speaks protocol version {}, this game version {}",
+                v, VERSION
+            ),
+            NetError::State(e) => write!(f, "invalid position: {}", e),
+            NetError::Unexpected(message) => write!(f, "unexpected message {:?}", message),
+            NetError::Closed => write!(f, "the connection was closed"),
+            NetError::TooLong => write!(f, "message longer than {} bytes", MAX_MESSAGE_LEN),
+        }
+    }
+}
+
+impl Error for NetError {}
+
+impl From<io::Error> for NetError {
+    fn from(e: io::Error) -> Self {
+        NetError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for NetError {
+    fn from(e: serde_json::Error) -> Self {
+        NetError::Format(e)
+    }
+}
+
+/// The position a race starts from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaceStart {
+    pub state: State,
+    pub seed: u64,
+}
+
+/// A game waiting for a player to join.
+pub struct Host {
+    listener: TcpListener,
+}
+
+impl Host {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Host {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Waits for a player to join and sends them `start`.
+    pub fn accept(&self, start: &RaceStart) -> Result<Connection, NetError> {
+        let (stream, _) = self.listener.accept()?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&Message::Start {
+            version: VERSION,
+            rows: start.state.rows(),
+            cols: start.state.cols(),
+            seed: start.seed,
+            tiles: start.state.to_numbers(),
+        })?;
+        connection.stream.set_nonblocking(true)?;
+        Ok(connection)
+    }
+}
+
+/// Joins the game hosted at `addr` and returns the position to race on.
+pub fn join<A: ToSocketAddrs>(addr: A) -> Result<(Connection, RaceStart), NetError> {
+    let mut last_error = None;
+    let mut stream = None;
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, JOIN_TIMEOUT) {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    let stream = match (stream, last_error) {
+        (Some(stream), _) => stream,
+        (None, Some(e)) => return Err(e.into()),
+        (None, None) => {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no address to connect to").into());
+        }
+    };
+
+    stream.set_read_timeout(Some(JOIN_TIMEOUT))?;
+    let mut connection = Connection::new(stream)?;
+    let message = loop {
+        if let Some(message) = connection.next_message()? {
+            break message;
+        }
+        match connection.fill() {
+            Ok(true) => {}
+            Ok(false) => return Err(NetError::Closed),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                let message = "the host didn't send the position in time";
+                return Err(io::Error::new(io::ErrorKind::TimedOut, message).into());
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+    let Message::Start {
+        version,
+        rows,
+        cols,
+        seed,
+        tiles,
+    } = message
+    else {
+        return Err(NetError::Unexpected(message));
+    };
+    if version != VERSION {
+        return Err(NetError::Version(version));
+    }
+    let state = State::from_numbers(rows, cols, &tiles).map_err(NetError::State)?;
+    connection.stream.set_nonblocking(true)?;
+    Ok((connection, RaceStart { state, seed }))
+}
+
+/// One side of a race over the network.
+pub struct Connection {
+    stream: TcpStream,
+    /// Bytes received that don't make a whole line yet.
+    buffer: Vec<u8>,
+    /// Bytes sent that the stream couldn't take yet.
+    outgoing: Vec<u8>,
+    closed: bool,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> io::Result<Self> {
+        // slides are tiny and should leave right away
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            stream,
+            buffer: vec![],
+            outgoing: vec![],
+            closed: false,
+        })
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Queues `message` and sends as much of it as the stream takes without
+    /// waiting; `poll` sends the rest.
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        serde_json::to_writer(&mut self.outgoing, message)?;
+        self.outgoing.push(b'\n');
+        self.flush()?;
+        Ok(())
+    }
+
+    /// The messages that arrived since the last call, without waiting. A
+    /// closed connection ends with a `Leave`, once.
+    pub fn poll(&mut self) -> Result<Vec<Message>, NetError> {
+        let mut messages = vec![];
+        if self.closed {
+            return Ok(messages);
+        }
+        self.flush()?;
+        let open = loop {
+            // taking the messages off as they come keeps the buffer to one
+            // partial line
+            while let Some(message) = self.next_message()? {
+                messages.push(message);
+            }
+            match self.fill() {
+                Ok(true) => {}
+                Ok(false) => break false,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break true,
+                Err(e) if e.kind() == io::ErrorKind::ConnectionReset => break false,
+                Err(e) => return Err(e.into()),
+            }
+        };
+        if !open {
+            self.closed = true;
+            if messages.last() != Some(&Message::Leave) {
+                messages.push(Message::Leave);
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Writes what the stream takes of the queued bytes.
+    fn flush(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads what the stream has into the buffer. Returns false once the
+    /// other side has closed it.
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Ok(false),
+                Ok(n) => {
+                    self.buffer.extend_from_slice(&chunk[..n]);
+                    return Ok(true);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Takes the first whole line off the buffer.
+    fn next_message(&mut self) -> Result<Option<Message>, NetError> {
+        let Some(end) = self.buffer.iter().position(|&b| b == b'\n') else {
+            if self.buffer.len() > MAX_MESSAGE_LEN {
+                return Err(NetError::TooLong);
+            }
+            return Ok(None);
+        };
+        let line = self.buffer.drain(..=end).collect::<Vec<_>>();
+        Ok(Some(serde_json::from_slice(&line)?))
+    }
+}
+
+impl Drop for Connection {
+    /// Sends what is still queued, such as a last `Leave`, waiting briefly.
+    fn drop(&mut self) {
+        if self.outgoing.is_empty() || self.closed {
+            return;
+        }
+        let _ = self.stream.set_nonblocking(false);
+        let _ = self.stream.set_write_timeout(Some(CLOSE_TIMEOUT));
+        let _ = self.stream.write_all(&self.outgoing);
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::{Cell, MAX_BOARD_SIZE, State, cell};
+
+/// Integer type a board is packed into, a fixed number of bits per cell.
+pub trait Code: Copy + Eq + Ord + Hash + Debug {
+    const BITS_PER_CELL: usize;
+    /// Largest number of cells that fit.
+    const CAPACITY: usize;
+    const ZERO: Self;
+
+    fn get(self, idx: usize) -> usize;
+
+    /// Sets the cell at `idx`, which must currently hold 0.
+    fn put(self, idx: usize, value: usize) -> Self;
+
+    /// Clears the cell at `idx`, which must currently hold `value`.
+    fn clear(self, idx: usize, value: usize) -> Self;
+}
+
+macro_rules! impl_code {
+    ($t:ty, $bits:expr) => {
+        impl Code for $t {
+            const BITS_PER_CELL: usize = $bits;
+            const CAPACITY: usize = <$t>::BITS as usize / $bits;
+            const ZERO: Self = 0;
+
+            #[inline]
+            fn get(self, idx: usize) -> usize {
+                ((self >> (idx * $bits)) & ((1 << $bits) - 1)) as usize
+            }
+
+            #[inline]
+            fn put(self, idx: usize, value: usize) -> Self {
+                self | ((value as $t) << (idx * $bits))
+            }
+
+            #[inline]
+            fn clear(self, idx: usize, value: usize) -> Self {
+                self ^ ((value as $t) << (idx * $bits))
+            }
+        }
+    };
+}
+
+// 4 bits per cell covers boards up to 4x4, 5 bits up to 5x5
+impl_code!(u64, 4);
+impl_code!(u128, 5);
+
+/// Cells of the largest board, one byte each.
+pub type Wide = [u8; MAX_BOARD_SIZE * MAX_BOARD_SIZE];
+
+// larger boards fall back to a byte per cell
+impl Code for Wide {
+    const BITS_PER_CELL: usize = 8;
+    const CAPACITY: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
+    const ZERO: Self = [0; MAX_BOARD_SIZE * MAX_BOARD_SIZE];
+
+    #[inline]
+    fn get(self, idx: usize) -> usize {
+        self[idx] as usize
+    }
+
+    #[inline]
+    fn put(mut self, idx: usize, value: usize) -> Self {
+        self[idx] = value as u8;
+        self
+    }
+
+    #[inline]
+    fn clear(mut self, idx: usize, _value: usize) -> Self {
+        self[idx] = 0;
+        self
+    }
+}
+
+/// A board position packed into a single integer, tile number per cell and
+/// 0 for the blank, plus the blank's index.
+///
+/// Cheap to copy, hash and compare, which makes it the key type for the
+/// search solvers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackedState<C> {
+    code: C,
+    blank: u8,
+}
+
+impl<C: Code> PackedState<C> {
+    /// Returns true if a `rows` x `cols` board fits into `C`.
+    pub fn fits(rows: usize, cols: usize) -> bool {
+        rows * cols <= C::CAPACITY
+    }
+
+    pub fn pack(state: &State) -> Self {
+        assert!(
+            Self::fits(state.rows, state.cols),
+            "{}x{} board does not fit",
+            state.rows,
+            state.cols
+        );
+
+        let code = state
+            .pieces
+            .iter()
+            .enumerate()
+            .fold(C::ZERO, |code, (i, piece)| {
+                code.put(i, piece.map_or(0, |p| p.num))
+            });
+        PackedState {
+            code,
+            blank: state.get_index(state.blank_cell) as u8,
+        }
+    }
+
+    pub fn unpack(&self, rows: usize, cols: usize) -> State {
+        let numbers = (0..rows * cols)
+            .map(|i| self.code.get(i))
+            .collect::<Vec<_>>();
+        State::from_numbers(rows, cols, &numbers).unwrap()
+    }
+
+    #[inline]
+    pub fn code(&self) -> C {
+        self.code
+    }
+
+    #[inline]
+    pub fn blank(&self) -> usize {
+        self.blank as usize
+    }
+
+    /// Tile number at `idx`, 0 for the blank.
+    #[inline]
+    pub fn tile(&self, idx: usize) -> usize {
+        self.code.get(idx)
+    }
+
+    /// Moves the tile at `idx` into the blank. `idx` must be adjacent to the
+    /// blank.
+    #[inline]
+    pub fn slide(&self, idx: usize) -> Self {
+        let tile = self.code.get(idx);
+        PackedState {
+            code: self.code.clear(idx, tile).put(self.blank(), tile),
+            blank: idx as u8,
+        }
+    }
+}
+
+/// Precomputed neighbour indices for every cell of a board.
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub rows: usize,
+    pub cols: usize,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    pub fn new(state: &State) -> Self {
+        let neighbors = (0..state.rows * state.cols)
+            .map(|i| {
+                state
+                    .neighbors(cell!(i, state.rows, state.cols))
+                    .into_iter()
+                    .map(|c| state.get_index(c))
+                    .collect()
+            })
+            .collect();
+        Grid {
+            rows: state.rows,
+            cols: state.cols,
+            neighbors,
+        }
+    }
+
+    #[inline]
+    pub fn neighbors(&self, idx: usize) -> &[usize] {
+        &self.neighbors[idx]
+    }
+
+    #[inline]
+    pub fn cell(&self, idx: usize) -> Cell {
+        cell!(idx, self.rows, self.cols)
+    }
+
+    /// Manhattan distance between two cell indices.
+    #[inline]
+    pub fn distance(&self, a: usize, b: usize) -> usize {
+        self.cell(a).manhattan_distance(&self.cell(b))
+    }
+}
@@ -0,0 +1,121 @@
+//! Two players racing to solve the same position.
+//!
+//! Both boards start from the same shuffle. The first player to solve their
+//! board wins; once someone has, the other board stops taking moves.
+
+use crate::{Cell, Direction, State, cell};
+
+/// One player's board in a race.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Racer {
+    pub state: State,
+    pub moves: usize,
+    /// Time into the race the board was solved at, in milliseconds.
+    pub finished_ms: Option<u64>,
+}
+
+impl Racer {
+    fn new(state: State) -> Self {
+        Racer {
+            state,
+            moves: 0,
+            finished_ms: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Winner(usize),
+    /// Both solved at the same moment in the same number of moves.
+    Draw,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Race {
+    start: State,
+    racers: [Racer; 2],
+}
+
+impl Race {
+    pub fn new(start: State) -> Self {
+        Race {
+            racers: [Racer::new(start.clone()), Racer::new(start.clone())],
+            start,
+        }
+    }
+
+    pub fn start(&self) -> &State {
+        &self.start
+    }
+
+    pub fn racer(&self, player: usize) -> &Racer {
+        &self.racers[player]
+    }
+
+    /// Both boards back to the start.
+    pub fn restart(&mut self) {
+        *self = Race::new(self.start.clone());
+    }
+
+    /// Slides the tiles between `cell` and the blank of `player`'s board
+    /// towards the blank, `ms` milliseconds into the race. Returns how many
+    /// tiles moved: none if `cell` is off the board or isn't in line with the
+    /// blank, or the race was already won before `ms`.
+    pub fn slide(&mut self, player: usize, cell: Cell, ms: u64) -> usize {
+        let over = self
+            .racers
+            .iter()
+            .any(|racer| racer.finished_ms.is_some_and(|finished| finished < ms));
+        let racer = &mut self.racers[player];
+        let blank = racer.state.blank_cell();
+        if over
+            || racer.finished_ms.is_some()
+            || cell.x >= racer.state.cols()
+            || cell.y >= racer.state.rows()
+            || cell == blank
+            || (cell.x != blank.x && cell.y != blank.y)
+        {
+            return 0;
+        }
+
+        let moved = racer.state.slide_line(cell).len();
+        racer.moves += moved;
+        if racer.state.is_finished() {
+            racer.finished_ms = Some(ms);
+        }
+        moved
+    }
+
+    /// Slides the tile next to the blank of `player`'s board in `direction`:
+    /// left moves the tile right of the blank to the left, and so on.
+    pub fn push(&mut self, player: usize, direction: Direction, ms: u64) -> bool {
+        tile_toward(&self.racers[player].state, direction)
+            .is_some_and(|tile| self.slide(player, tile, ms) > 0)
+    }
+
+    /// Who won, once someone has: the first to solve, then the one with fewer
+    /// moves.
+    pub fn outcome(&self) -> Option<Outcome> {
+        let [a, b] = &self.racers;
+        let key = |racer: &Racer| racer.finished_ms.map(|ms| (ms, racer.moves));
+        match (key(a), key(b)) {
+            (None, None) => None,
+            (Some(_), None) => Some(Outcome::Winner(0)),
+            (None, Some(_)) => Some(Outcome::Winner(1)),
+            (Some(a), Some(b)) if a < b => Some(Outcome::Winner(0)),
+            (Some(a), Some(b)) if b < a => Some(Outcome::Winner(1)),
+            _ => Some(Outcome::Draw),
+        }
+    }
+}
+
+/// The tile a push in `direction` moves into the blank: the one on the
+/// opposite side of the blank, if the board has one there.
+pub fn tile_toward(state: &State, direction: Direction) -> Option<Cell> {
+    let (dx, dy) = direction.delta();
+    let blank = state.blank_cell();
+    let x = blank.x.checked_add_signed(-dx)?;
+    let y = blank.y.checked_add_signed(-dy)?;
+    (x < state.cols() && y < state.rows()).then(|| cell!(x, y))
+}
@@ -0,0 +1,45 @@
+//! Perfect hashing of board positions by permutation rank (Lehmer code).
+//!
+//! A position on an n-cell board is a permutation of the tile numbers
+//! `0..n`, so it maps to a unique index in `0..n!`. Small boards can then keep
+//! per-position data in flat vectors instead of hash maps.
+
+use crate::packed::{Code, PackedState};
+
+/// Boards with at most this many cells are small enough to index by rank
+/// (10! is about 3.6 million).
+pub const RANKED_MAX_CELLS: usize = 10;
+
+pub fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+/// Lehmer rank of a permutation of `0..perm.len()`.
+pub fn rank(perm: &[usize]) -> usize {
+    let n = perm.len();
+    let mut r = 0;
+    for i in 0..n {
+        let smaller = perm[i + 1..].iter().filter(|&&p| p < perm[i]).count();
+        r = r * (n - i) + smaller;
+    }
+    r
+}
+
+/// Inverse of [`rank`] for permutations of `0..n`.
+pub fn unrank(mut r: usize, n: usize) -> Vec<usize> {
+    let mut digits = vec![0; n];
+    for i in (0..n).rev() {
+        let base = n - i;
+        digits[i] = r % base;
+        r /= base;
+    }
+
+    let mut remaining = (0..n).collect::<Vec<_>>();
+    digits.into_iter().map(|d| remaining.remove(d)).collect()
+}
+
+/// Rank of a packed `n`-cell position.
+pub fn rank_packed<C: Code>(state: &PackedState<C>, n: usize) -> usize {
+    let perm = (0..n).map(|i| state.tile(i)).collect::<Vec<_>>();
+    rank(&perm)
+}
@@ -0,0 +1,235 @@
+//! Drawing backends.
+//!
+//! [`Board`](crate::Board) draws through the [`Renderer`] trait so the same
+//! drawing code runs in the window, offscreen (see
+//! [`export::Frame`](crate::export::Frame)) and in tests.
+
+use macroquad::color::{self, Color};
+use macroquad::math::{Vec2, vec2};
+use macroquad::texture::{self, Texture2D};
+use macroquad::{shapes, text};
+
+pub trait Renderer {
+    /// Image type accepted by [`draw_texture`](Renderer::draw_texture).
+    type Texture;
+
+    fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color);
+
+    /// Outline of a rectangle, the lines lying inside it.
+    fn draw_rectangle_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    );
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color);
+
+    /// Filled triangle, e.g. part of a hexagonal cell.
+    fn draw_triangle(&mut self, v1: Vec2, v2: Vec2, v3: Vec2, color: Color);
+
+    /// Draws `text` with its baseline starting at (`x`, `y`).
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, font_size: u16, color: Color);
+
+    /// Offset from the start of the baseline to the center of `text`.
+    fn text_center(&self, text: &str, font_size: u16) -> Vec2;
+
+    /// Draws `texture` stretched to `w` x `h`.
+    fn draw_texture(&mut self, texture: &Self::Texture, x: f32, y: f32, w: f32, h: f32);
+}
+
+/// Draws to the macroquad window.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MacroquadRenderer;
+
+impl Renderer for MacroquadRenderer {
+    type Texture = Texture2D;
+
+    fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        shapes::draw_rectangle(x, y, w, h, color);
+    }
+
+    fn draw_rectangle_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        shapes::draw_rectangle_lines(x, y, w, h, thickness, color);
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        shapes::draw_line(x1, y1, x2, y2, thickness, color);
+    }
+
+    fn draw_triangle(&mut self, v1: Vec2, v2: Vec2, v3: Vec2, color: Color) {
+        shapes::draw_triangle(v1, v2, v3, color);
+    }
+
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+        text::draw_text_ex(
+            text,
+            x,
+            y,
+            text::TextParams {
+                font_size,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn text_center(&self, text: &str, font_size: u16) -> Vec2 {
+        text::get_text_center(text, None, font_size, 1.0, 0.0)
+    }
+
+    fn draw_texture(&mut self, texture: &Texture2D, x: f32, y: f32, w: f32, h: f32) {
+        texture::draw_texture_ex(
+            texture,
+            x,
+            y,
+            color::WHITE,
+            texture::DrawTextureParams {
+                dest_size: Some(vec2(w, h)),
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// One call made to a [`RecordingRenderer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCall {
+    Rectangle {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        color: Color,
+    },
+    RectangleLines {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    },
+    Line {
+        x1: f32,
+        y1: f32,
+        x2: f32,
+        y2: f32,
+        thickness: f32,
+        color: Color,
+    },
+    Triangle {
+        v1: Vec2,
+        v2: Vec2,
+        v3: Vec2,
+        color: Color,
+    },
+    Text {
+        text: String,
+        x: f32,
+        y: f32,
+        font_size: u16,
+        color: Color,
+    },
+    Texture {
+        texture: String,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    },
+}
+
+/// Records draw calls instead of drawing, e.g. to check what a board draws.
+///
+/// Text is measured with fixed metrics (every character half the font size
+/// wide, capitals 0.7 of it tall) so results don't depend on a font.
+/// Textures are identified by name.
+#[derive(Debug, Clone, Default)]
+pub struct RecordingRenderer {
+    pub calls: Vec<DrawCall>,
+}
+
+impl RecordingRenderer {
+    pub fn new() -> Self {
+        RecordingRenderer::default()
+    }
+}
+
+impl Renderer for RecordingRenderer {
+    type Texture = String;
+
+    fn draw_rectangle(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        self.calls.push(DrawCall::Rectangle { x, y, w, h, color });
+    }
+
+    fn draw_rectangle_lines(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        thickness: f32,
+        color: Color,
+    ) {
+        self.calls.push(DrawCall::RectangleLines {
+            x,
+            y,
+            w,
+            h,
+            thickness,
+            color,
+        });
+    }
+
+    fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, thickness: f32, color: Color) {
+        self.calls.push(DrawCall::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            thickness,
+            color,
+        });
+    }
+
+    fn draw_triangle(&mut self, v1: Vec2, v2: Vec2, v3: Vec2, color: Color) {
+        self.calls.push(DrawCall::Triangle { v1, v2, v3, color });
+    }
+
+    fn draw_text(&mut self, text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+        self.calls.push(DrawCall::Text {
+            text: text.to_string(),
+            x,
+            y,
+            font_size,
+            color,
+        });
+    }
+
+    fn text_center(&self, text: &str, font_size: u16) -> Vec2 {
+        let size = font_size as f32;
+        vec2(text.chars().count() as f32 * size * 0.25, -size * 0.35)
+    }
+
+    fn draw_texture(&mut self, texture: &String, x: f32, y: f32, w: f32, h: f32) {
+        self.calls.push(DrawCall::Texture {
+            texture: texture.clone(),
+            x,
+            y,
+            w,
+            h,
+        });
+    }
+}
@@ -0,0 +1,178 @@
+//! Recorded games.
+//!
+//! A replay stores the starting position, the seed it came from and every
+//! move with the time it was made, so a session can be watched again after
+//! the window is closed.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::daily::Date;
+use crate::{Cell, State, StateError};
+
+const VERSION: u32 = 1;
+
+/// One move: the cell the blank moved to, `time_ms` after the game started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayMove {
+    pub time_ms: u64,
+    pub cell: Cell,
+}
+
+/// How the session ended.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub solved: bool,
+    pub moves: usize,
+    pub seconds: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub rows: usize,
+    pub cols: usize,
+    /// Seed the starting position was generated from, if any.
+    pub seed: Option<u64>,
+    /// Starting position in row-major order, `0` being the blank.
+    pub initial: Vec<usize>,
+    pub moves: Vec<ReplayMove>,
+    /// `None` while the game is still being played.
+    pub result: Option<ReplayResult>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    Version(u32),
+    State(StateError),
+    IllegalMove { index: usize, cell: Cell },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => e.fmt(f),
+            ReplayError::Format(e) => write!(f, "invalid replay: {}", e),
+            ReplayError::Version(v) => write!(f, "unsupported replay version {}", v),
+            ReplayError::State(e) => write!(f, "invalid initial position: {}", e),
+            ReplayError::IllegalMove { index, cell } => {
+                write!(
+                    f,
+                    "move {} to ({}, {}) is illegal",
+                    index + 1,
+                    cell.x,
+                    cell.y
+                )
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+impl Replay {
+    /// Starts recording a game from `initial`.
+    pub fn new(initial: &State, seed: Option<u64>) -> Self {
+        Replay {
+            version: VERSION,
+            rows: initial.rows(),
+            cols: initial.cols(),
+            seed,
+            initial: initial.to_numbers(),
+            moves: vec![],
+            result: None,
+        }
+    }
+
+    pub fn push(&mut self, time_ms: u64, cell: Cell) {
+        self.moves.push(ReplayMove { time_ms, cell });
+    }
+
+    pub fn finish(&mut self, solved: bool, seconds: f64) {
+        self.result = Some(ReplayResult {
+            solved,
+            moves: self.moves.len(),
+            seconds,
+        });
+    }
+
+    pub fn initial_state(&self) -> Result<State, ReplayError> {
+        State::from_numbers(self.rows, self.cols, &self.initial).map_err(ReplayError::State)
+    }
+
+    /// The position after the first `count` moves.
+    pub fn state_at(&self, count: usize) -> Result<State, ReplayError> {
+        let mut state = self.initial_state()?;
+        for (index, mv) in self.moves.iter().take(count).enumerate() {
+            if !state.try_move(mv.cell) {
+                return Err(ReplayError::IllegalMove {
+                    index,
+                    cell: mv.cell,
+                });
+            }
+        }
+        Ok(state)
+    }
+
+    /// Checks the initial position and that every move is legal.
+    pub fn validate(&self) -> Result<(), ReplayError> {
+        if self.version != VERSION {
+            return Err(ReplayError::Version(self.version));
+        }
+        self.state_at(self.moves.len()).map(|_| ())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+
+    /// Loads and validates a replay.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        let text = fs::read_to_string(path)?;
+        let replay: Replay = serde_json::from_str(&text).map_err(ReplayError::Format)?;
+        replay.validate()?;
+        Ok(replay)
+    }
+
+    /// `replays` in the platform's data directory.
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("slide-puzzle").join("replays"))
+    }
+
+    /// A file name for a replay recorded now, e.g. `2026-01-31T120501-4x4.json`.
+    pub fn file_name(&self) -> String {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let date = Date::from_days((secs / 86400) as i64);
+        let time = secs % 86400;
+        format!(
+            "{}T{:02}{:02}{:02}-{}x{}.json",
+            date,
+            time / 3600,
+            time / 60 % 60,
+            time % 60,
+            self.rows,
+            self.cols
+        )
+    }
+}
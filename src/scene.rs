@@ -0,0 +1,346 @@
+//! Screens of the game and the menus that lead from one to the next.
+//!
+//! A run of the game moves through [`Scene`]s: the main menu, the mode
+//! select, the board size picker, a game, and the results of that game. Each
+//! menu screen offers [`Choice`]s and [`Scene::choose`] says where a choice
+//! leads, so the flow can be followed (and tested) without a window.
+
+use macroquad::math::{Vec2, vec2};
+
+use crate::{MAX_BOARD_SIZE, MIN_BOARD_SIZE, Renderer, Theme, validate_board_size};
+
+/// Board size the size picker starts at.
+pub const DEFAULT_SIZE: (usize, usize) = (4, 4);
+
+const MENU_WIDTH: f32 = 480.0;
+const TITLE_HEIGHT: f32 = 70.0;
+const TEXT_HEIGHT: f32 = 34.0;
+const ITEM_HEIGHT: f32 = 52.0;
+
+/// What kind of game to start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Play,
+    /// The solver plays while you watch.
+    Watch,
+    Daily,
+    Levels,
+    /// Two players on one screen.
+    Race,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 5] = [
+        Mode::Play,
+        Mode::Watch,
+        Mode::Daily,
+        Mode::Levels,
+        Mode::Race,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mode::Play => "play",
+            Mode::Watch => "watch the AI",
+            Mode::Daily => "daily puzzle",
+            Mode::Levels => "levels",
+            Mode::Race => "two player race",
+        }
+    }
+}
+
+/// A game picked in the menus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSetup {
+    pub mode: Mode,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+/// How a finished game went.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    pub moves: usize,
+    pub seconds: f64,
+    /// Length of the shortest solution, if known.
+    pub optimal: Option<usize>,
+    /// Text to share, for a daily puzzle.
+    pub share: Option<String>,
+}
+
+/// How a game was left.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEnd {
+    Finished(GameResult),
+    /// Back to the main menu from the pause menu.
+    Menu,
+    Quit,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scene {
+    MainMenu,
+    ModeSelect,
+    SizePicker {
+        mode: Mode,
+        rows: usize,
+        cols: usize,
+    },
+    Game(GameSetup),
+    /// The level select and the levels picked from it.
+    Levels,
+    Results {
+        setup: GameSetup,
+        result: GameResult,
+    },
+    Quit,
+}
+
+/// An item of a menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    NewGame,
+    Mode(Mode),
+    /// Steps the size picker's rows; left and right change it too.
+    Rows,
+    Cols,
+    Start,
+    PlayAgain,
+    Resume,
+    Restart,
+    MainMenu,
+    Back,
+    Quit,
+}
+
+/// A list of choices with one of them highlighted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Menu {
+    pub title: String,
+    /// Lines shown between the title and the items.
+    pub text: Vec<String>,
+    pub items: Vec<(String, Choice)>,
+    pub cursor: usize,
+}
+
+impl Menu {
+    pub fn new(title: &str, items: Vec<(String, Choice)>) -> Self {
+        Menu {
+            title: title.to_string(),
+            text: vec![],
+            items,
+            cursor: 0,
+        }
+    }
+
+    /// The menu shown while a game is paused.
+    pub fn pause() -> Self {
+        Menu::new(
+            "PAUSED",
+            items(&[
+                ("resume", Choice::Resume),
+                ("restart", Choice::Restart),
+                ("main menu", Choice::MainMenu),
+                ("quit", Choice::Quit),
+            ]),
+        )
+    }
+
+    pub fn up(&mut self) {
+        self.cursor = (self.cursor + self.items.len() - 1) % self.items.len();
+    }
+
+    pub fn down(&mut self) {
+        self.cursor = (self.cursor + 1) % self.items.len();
+    }
+
+    pub fn selected(&self) -> Choice {
+        self.items[self.cursor].1
+    }
+
+    /// The choice Escape picks: going back or resuming, if the menu has it.
+    pub fn escape(&self) -> Option<Choice> {
+        self.items
+            .iter()
+            .map(|(_, choice)| *choice)
+            .find(|choice| matches!(choice, Choice::Back | Choice::Resume))
+    }
+
+    /// Width and height of the drawn menu.
+    pub fn size(&self) -> Vec2 {
+        let height = TITLE_HEIGHT
+            + TEXT_HEIGHT * self.text.len() as f32
+            + ITEM_HEIGHT * self.items.len() as f32;
+        vec2(MENU_WIDTH, height)
+    }
+
+    /// Index of the item under `pos`, for a menu drawn at `origin`.
+    pub fn item_at(&self, origin: Vec2, pos: Vec2) -> Option<usize> {
+        let rel = pos - origin - vec2(0.0, self.items_top());
+        if rel.x < 0.0 || rel.x >= MENU_WIDTH || rel.y < 0.0 {
+            return None;
+        }
+        Some((rel.y / ITEM_HEIGHT) as usize).filter(|&i| i < self.items.len())
+    }
+
+    fn items_top(&self) -> f32 {
+        TITLE_HEIGHT + TEXT_HEIGHT * self.text.len() as f32
+    }
+
+    /// Draws the menu with its top-left corner at `origin`.
+    pub fn draw<R: Renderer + ?Sized>(&self, renderer: &mut R, origin: Vec2, theme: &Theme) {
+        renderer.draw_text(
+            &self.title,
+            origin.x,
+            origin.y + TITLE_HEIGHT * 0.7,
+            48,
+            theme.accent,
+        );
+        for (i, line) in self.text.iter().enumerate() {
+            let y = origin.y + TITLE_HEIGHT + TEXT_HEIGHT * (i as f32 + 0.75);
+            renderer.draw_text(line, origin.x, y, 28, theme.muted);
+        }
+        for (i, (label, _)) in self.items.iter().enumerate() {
+            let top = origin.y + self.items_top() + ITEM_HEIGHT * i as f32;
+            if i == self.cursor {
+                renderer.draw_rectangle(origin.x, top, MENU_WIDTH, ITEM_HEIGHT, theme.highlight);
+            }
+            renderer.draw_text(
+                label,
+                origin.x + 16.0,
+                top + ITEM_HEIGHT * 0.7,
+                36,
+                theme.text,
+            );
+        }
+    }
+}
+
+fn items(items: &[(&str, Choice)]) -> Vec<(String, Choice)> {
+    items
+        .iter()
+        .map(|(label, choice)| (label.to_string(), *choice))
+        .collect()
+}
+
+impl Scene {
+    /// The menu of this screen; games and the level select have none.
+    pub fn menu(&self) -> Option<Menu> {
+        let menu = match self {
+            Scene::MainMenu => Menu::new(
+                "SLIDE PUZZLE",
+                items(&[("new game", Choice::NewGame), ("quit", Choice::Quit)]),
+            ),
+            Scene::ModeSelect => {
+                let mut modes = Mode::ALL
+                    .iter()
+                    .map(|&mode| (mode.name().to_string(), Choice::Mode(mode)))
+                    .collect::<Vec<_>>();
+                modes.push(("back".to_string(), Choice::Back));
+                Menu::new("MODE", modes)
+            }
+            Scene::SizePicker { rows, cols, .. } => Menu {
+                text: vec!["left / right: change".to_string()],
+                ..Menu::new(
+                    "BOARD SIZE",
+                    vec![
+                        (format!("rows: {}", rows), Choice::Rows),
+                        (format!("columns: {}", cols), Choice::Cols),
+                        ("start".to_string(), Choice::Start),
+                        ("back".to_string(), Choice::Back),
+                    ],
+                )
+            },
+            Scene::Results { setup, result } => {
+                let seconds = result.seconds as u64;
+                let mut text = vec![format!(
+                    "{}x{}  {} moves in {}:{:02}",
+                    setup.rows,
+                    setup.cols,
+                    result.moves,
+                    seconds / 60,
+                    seconds % 60
+                )];
+                if let Some(optimal) = result.optimal {
+                    text.push(format!("optimal: {} moves", optimal));
+                }
+                if let Some(share) = &result.share {
+                    text.extend(share.lines().map(String::from));
+                }
+                Menu {
+                    text,
+                    ..Menu::new(
+                        "CLEAR",
+                        items(&[
+                            ("play again", Choice::PlayAgain),
+                            ("main menu", Choice::MainMenu),
+                            ("quit", Choice::Quit),
+                        ]),
+                    )
+                }
+            }
+            Scene::Game(_) | Scene::Levels | Scene::Quit => return None,
+        };
+        Some(menu)
+    }
+
+    /// The screen `choice` leads to; choices that don't belong to this screen
+    /// stay on it.
+    pub fn choose(self, choice: Choice) -> Scene {
+        match (self, choice) {
+            (_, Choice::Quit) => Scene::Quit,
+            (_, Choice::MainMenu) => Scene::MainMenu,
+            (Scene::MainMenu, Choice::NewGame) => Scene::ModeSelect,
+            (Scene::ModeSelect, Choice::Mode(Mode::Levels)) => Scene::Levels,
+            (Scene::ModeSelect, Choice::Mode(mode)) => Scene::SizePicker {
+                mode,
+                rows: DEFAULT_SIZE.0,
+                cols: DEFAULT_SIZE.1,
+            },
+            (Scene::ModeSelect, Choice::Back) => Scene::MainMenu,
+            (scene @ Scene::SizePicker { .. }, Choice::Rows | Choice::Cols) => {
+                scene.adjust(choice, 1)
+            }
+            (Scene::SizePicker { mode, rows, cols }, Choice::Start) => {
+                Scene::Game(GameSetup { mode, rows, cols })
+            }
+            (Scene::SizePicker { .. }, Choice::Back) => Scene::ModeSelect,
+            (Scene::Results { setup, .. }, Choice::PlayAgain) => Scene::Game(setup),
+            (scene, _) => scene,
+        }
+    }
+
+    /// Steps the size picker's rows or columns by `delta`, wrapping around
+    /// and skipping the unplayable 1x1 board.
+    pub fn adjust(self, choice: Choice, delta: isize) -> Scene {
+        let Scene::SizePicker { mode, rows, cols } = self else {
+            return self;
+        };
+        let step = |value: usize| {
+            let span = (MAX_BOARD_SIZE - MIN_BOARD_SIZE + 1) as isize;
+            let offset = (value - MIN_BOARD_SIZE) as isize + delta;
+            MIN_BOARD_SIZE + offset.rem_euclid(span) as usize
+        };
+        let (mut rows, mut cols) = (rows, cols);
+        loop {
+            match choice {
+                Choice::Rows => rows = step(rows),
+                Choice::Cols => cols = step(cols),
+                _ => break,
+            }
+            if validate_board_size(rows, cols).is_ok() {
+                break;
+            }
+        }
+        Scene::SizePicker { mode, rows, cols }
+    }
+
+    /// The screen after a game of `setup` ends.
+    pub fn after_game(setup: GameSetup, end: GameEnd) -> Scene {
+        match end {
+            GameEnd::Finished(result) => Scene::Results { setup, result },
+            GameEnd::Menu => Scene::MainMenu,
+            GameEnd::Quit => Scene::Quit,
+        }
+    }
+}
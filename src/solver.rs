@@ -0,0 +1,610 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use crate::analysis::DistanceTable;
+use crate::packed::{Code, Grid, PackedState, Wide};
+use crate::rank::{RANKED_MAX_CELLS, factorial, rank_packed, unrank};
+use crate::{Cell, State, cell};
+
+pub trait Solver {
+    /// Short identifier used in reports.
+    fn name(&self) -> &'static str;
+
+    /// Returns the cells the blank moves through, in order.
+    fn solve(&mut self) -> Vec<Cell>;
+
+    /// Number of search nodes expanded by the last `solve`.
+    fn nodes_expanded(&self) -> usize;
+}
+
+/// Every solver implementation, so callers can pick one by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverKind {
+    My,
+    AStar,
+    Bfs,
+    Oracle,
+}
+
+impl SolverKind {
+    pub const ALL: [SolverKind; 4] = [
+        SolverKind::My,
+        SolverKind::AStar,
+        SolverKind::Bfs,
+        SolverKind::Oracle,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolverKind::My => "my",
+            SolverKind::AStar => "astar",
+            SolverKind::Bfs => "bfs",
+            SolverKind::Oracle => "oracle",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SolverKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    /// Returns false for boards the solver can't finish in reasonable time
+    /// and memory.
+    pub fn supports(&self, rows: usize, cols: usize) -> bool {
+        let num = rows * cols;
+        match self {
+            SolverKind::My => true,
+            SolverKind::AStar => num <= 16,
+            SolverKind::Bfs => num <= RANKED_MAX_CELLS || rows == 1 || cols == 1,
+            SolverKind::Oracle => num <= RANKED_MAX_CELLS,
+        }
+    }
+
+    pub fn build(&self, state: State) -> Box<dyn Solver> {
+        match self {
+            SolverKind::My => Box::new(MySolver::new(state)),
+            SolverKind::AStar => Box::new(AStarSolver::new(state)),
+            SolverKind::Bfs => Box::new(BFSSolver::new(state)),
+            SolverKind::Oracle => Box::new(OracleSolver::new(state)),
+        }
+    }
+
+    /// The solver the game uses for a board of this size.
+    pub fn default_for(state: &State) -> Self {
+        let num = state.rows * state.cols;
+        if state.is_line() {
+            // a line has at most `num` reachable positions, so BFS is instant
+            SolverKind::Bfs
+        } else if num > 16 {
+            SolverKind::My
+        } else if num > 9 {
+            SolverKind::AStar
+        } else {
+            SolverKind::Bfs
+        }
+    }
+}
+
+/// The next step of [`MySolver`]'s reduction from a position, for drawing
+/// over the board.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SolvePlan {
+    /// Cells holding tiles the reduction has placed and won't move again,
+    /// row-major.
+    pub locked: Vec<bool>,
+    /// Cells the blank moves through before the target tile next moves,
+    /// from the blank to the cell the tile moves into.
+    pub blank_path: Vec<Cell>,
+    /// Cells the target tile moves through, from where it is to its goal.
+    pub tile_path: Vec<Cell>,
+}
+
+pub struct MySolver {
+    states: State,
+    nodes_expanded: usize,
+}
+
+impl MySolver {
+    pub fn new(states: State) -> Self {
+        MySolver {
+            states,
+            nodes_expanded: 0,
+        }
+    }
+
+    fn solve(&mut self) -> Vec<Cell> {
+        self.nodes_expanded = 0;
+        let mut ans = vec![];
+
+        //  1  2  3  4
+        //  5  6  7  8
+        //  9 10 11 12
+        // 13 14 15
+
+        let mut moved = vec![false; self.states.cols * self.states.rows];
+
+        for target in self.targets() {
+            if self.is_correct_place(target) {
+                moved[self.states.get_index(target)] = true;
+                continue;
+            }
+
+            let target_num = self.states.get_index(target) + 1;
+            let current_target_cell = self.find_target_num(target_num);
+
+            if !self.move_to(current_target_cell, target, &mut moved, &mut ans) {
+                break;
+            }
+            moved[self.states.get_index(target)] = true;
+        }
+
+        ans
+    }
+
+    /// What the reduction does next: the tiles already placed, the tile it
+    /// places next and the route of the blank to it. Nothing is moved.
+    pub fn plan(&mut self) -> SolvePlan {
+        let mut locked = vec![false; self.states.cols * self.states.rows];
+        for target in self.targets() {
+            let target_idx = self.states.get_index(target);
+            if self.is_correct_place(target) {
+                locked[target_idx] = true;
+                continue;
+            }
+
+            let tile = self.find_target_num(target_idx + 1);
+            let mut tile_path = vec![tile];
+            tile_path.extend(self.find_routes(tile, target, &locked));
+            let mut blank_path = vec![];
+            if let Some(&next) = tile_path.get(1) {
+                let blank = self.states.blank_cell;
+                let tile_idx = self.states.get_index(tile);
+                locked[tile_idx] = true;
+                let routes = self.find_routes(blank, next, &locked);
+                locked[tile_idx] = false;
+                if blank == next || !routes.is_empty() {
+                    blank_path.push(blank);
+                    blank_path.extend(routes);
+                }
+            }
+            return SolvePlan {
+                locked,
+                blank_path,
+                tile_path,
+            };
+        }
+        SolvePlan {
+            locked,
+            ..SolvePlan::default()
+        }
+    }
+
+    /// The cells the reduction places tiles in, in order: along the top row
+    /// and down the left column, then the same one ring in. Only the first
+    /// four are placed.
+    fn targets(&self) -> Vec<Cell> {
+        let mut targets = vec![];
+        let depth = self.states.rows.min(self.states.cols) - 1;
+        for i in 0..depth {
+            for x in i..self.states.cols {
+                targets.push(cell!(x, i));
+            }
+            for y in i + 1..self.states.rows {
+                targets.push(cell!(i, y));
+            }
+        }
+        targets.truncate(4);
+        targets
+    }
+
+    /// Moves the tile at `start` to `end`, appending the blank's moves to
+    /// `ans`. Returns false if the blank can't get around the placed tiles.
+    fn move_to(
+        &mut self,
+        start: Cell,
+        end: Cell,
+        constraints: &mut [bool],
+        ans: &mut Vec<Cell>,
+    ) -> bool {
+        let mut current = start;
+        let routes = self.find_routes(current, end, constraints);
+        for &next in routes.iter() {
+            constraints[self.states.get_index(current)] = true;
+            let blank_routes = self.find_routes(self.states.blank_cell, next, constraints);
+            for &b in blank_routes.iter() {
+                self.states.swap(self.states.blank_cell, b);
+                ans.push(b);
+            }
+            constraints[self.states.get_index(current)] = false;
+            if self.states.blank_cell != next {
+                return false;
+            }
+            self.states.swap(self.states.blank_cell, current);
+            ans.push(current);
+
+            current = next;
+        }
+        true
+    }
+
+    fn find_routes(&mut self, start: Cell, end: Cell, constraints: &[bool]) -> Vec<Cell> {
+        let mut queues = VecDeque::new();
+        queues.push_back((start, vec![]));
+
+        let mut memo = vec![false; constraints.len()];
+        while !queues.is_empty() {
+            let (current, routes) = queues.pop_front().unwrap();
+            self.nodes_expanded += 1;
+
+            if current == end {
+                return routes;
+            }
+
+            let dx = [1, 0, -1, 0];
+            let dy = [0, 1, 0, -1];
+            for k in 0..4 {
+                let nx = dx[k] + current.x as isize;
+                let ny = dy[k] + current.y as isize;
+
+                if nx < 0
+                    || ny < 0
+                    || nx >= self.states.cols as isize
+                    || ny >= self.states.rows as isize
+                {
+                    continue;
+                }
+                let nx = nx as usize;
+                let ny = ny as usize;
+
+                let next = cell!(nx, ny);
+                let next_idx = self.states.get_index(next);
+                if memo[next_idx] {
+                    continue;
+                }
+                memo[next_idx] = true;
+
+                if constraints[next_idx] {
+                    continue;
+                }
+
+                let mut tmp = routes.clone();
+                tmp.push(next);
+                queues.push_back((next, tmp));
+            }
+        }
+        vec![]
+    }
+
+    fn find_target_num(&self, num: usize) -> Cell {
+        assert!(num >= 1 && num < self.states.rows * self.states.cols);
+
+        for (i, piece) in self.states.pieces.iter().enumerate() {
+            if let Some(piece) = piece
+                && piece.num == num
+            {
+                let cell = cell!(i, self.states.rows, self.states.cols);
+                return cell;
+            }
+        }
+        unreachable!("should has piece");
+    }
+
+    fn is_correct_place(&self, cell: Cell) -> bool {
+        if let Some(piece) = self.states.get_piece(cell) {
+            return piece.num - 1 == cell.as_index(self.states.rows, self.states.cols);
+        }
+        cell == cell!(self.states.cols - 1, self.states.rows - 1)
+    }
+}
+
+impl Solver for MySolver {
+    fn name(&self) -> &'static str {
+        SolverKind::My.name()
+    }
+
+    fn solve(&mut self) -> Vec<Cell> {
+        self.solve()
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+}
+
+pub struct AStarSolver {
+    states: State,
+    nodes_expanded: usize,
+}
+
+impl AStarSolver {
+    pub fn new(states: State) -> Self {
+        AStarSolver {
+            states,
+            nodes_expanded: 0,
+        }
+    }
+
+    fn solve_with_expected(&mut self, expected: &State) -> Vec<Cell> {
+        if PackedState::<u64>::fits(self.states.rows, self.states.cols) {
+            self.search::<u64>(expected)
+        } else if PackedState::<u128>::fits(self.states.rows, self.states.cols) {
+            self.search::<u128>(expected)
+        } else {
+            self.search::<Wide>(expected)
+        }
+    }
+
+    fn search<C: Code>(&mut self, expected: &State) -> Vec<Cell> {
+        self.nodes_expanded = 0;
+        let grid = Grid::new(&self.states);
+        let goal = goal_indices(expected);
+
+        let state = PackedState::<C>::pack(&self.states);
+        let cost = heuristic(&grid, &goal, &state);
+
+        let mut pq = BinaryHeap::new();
+        pq.push((Reverse(cost), state, vec![]));
+
+        let mut memo = HashMap::new();
+        while let Some((Reverse(cost), state, routes)) = pq.pop() {
+            self.nodes_expanded += 1;
+            if cost == 0 {
+                return to_cells(&grid, &routes);
+            }
+
+            if let Some(count) = memo.get(&state)
+                && *count < routes.len()
+            {
+                continue;
+            }
+            memo.insert(state, routes.len());
+
+            let blank = state.blank();
+            for &next in grid.neighbors(blank) {
+                let tile = state.tile(next);
+                // only the moved tile and the blank change places
+                let next_cost = cost + grid.distance(goal[tile], blank)
+                    - grid.distance(goal[tile], next)
+                    + grid.distance(goal[0], next)
+                    - grid.distance(goal[0], blank);
+
+                let mut next_routes = routes.clone();
+                next_routes.push(next as u8);
+
+                pq.push((Reverse(next_cost), state.slide(next), next_routes));
+            }
+        }
+
+        vec![]
+    }
+}
+
+impl Solver for AStarSolver {
+    fn name(&self) -> &'static str {
+        SolverKind::AStar.name()
+    }
+
+    fn solve(&mut self) -> Vec<Cell> {
+        let expected = State::new(self.states.rows, self.states.cols);
+        self.solve_with_expected(&expected)
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+}
+
+pub struct BFSSolver {
+    states: State,
+    nodes_expanded: usize,
+}
+
+impl BFSSolver {
+    pub fn new(states: State) -> Self {
+        BFSSolver {
+            states,
+            nodes_expanded: 0,
+        }
+    }
+
+    fn solve_with_expected(&mut self, expected: &State) -> Vec<Cell> {
+        if self.states.rows * self.states.cols <= RANKED_MAX_CELLS {
+            self.bidirectional(expected)
+        } else if PackedState::<u64>::fits(self.states.rows, self.states.cols) {
+            self.search::<u64>(expected)
+        } else if PackedState::<u128>::fits(self.states.rows, self.states.cols) {
+            self.search::<u128>(expected)
+        } else {
+            self.search::<Wide>(expected)
+        }
+    }
+
+    /// Bidirectional BFS for small boards, keyed by permutation rank.
+    ///
+    /// Each side stores the parent rank of every position it reached, so
+    /// memory is two flat `n!` vectors instead of a route per queue entry.
+    /// The frontier with fewer positions is expanded one whole level at a
+    /// time; the first level that touches the other side yields a shortest
+    /// path.
+    fn bidirectional(&mut self, expected: &State) -> Vec<Cell> {
+        const UNVISITED: u32 = u32::MAX;
+
+        self.nodes_expanded = 0;
+        let grid = Grid::new(&self.states);
+        let n = self.states.rows * self.states.cols;
+
+        let start = PackedState::<u64>::pack(&self.states);
+        let goal = PackedState::<u64>::pack(expected);
+        let start_rank = rank_packed(&start, n);
+        let goal_rank = rank_packed(&goal, n);
+        if start_rank == goal_rank {
+            return vec![];
+        }
+
+        // a root is its own parent
+        let mut parents = [vec![UNVISITED; factorial(n)], vec![UNVISITED; factorial(n)]];
+        parents[0][start_rank] = start_rank as u32;
+        parents[1][goal_rank] = goal_rank as u32;
+        let mut frontiers = [vec![start], vec![goal]];
+
+        let meet = loop {
+            let side = if frontiers[0].len() <= frontiers[1].len() {
+                0
+            } else {
+                1
+            };
+            if frontiers[side].is_empty() {
+                // the goal is not reachable from this position
+                return vec![];
+            }
+
+            let mut meet = None;
+            let mut next_frontier = vec![];
+            for state in std::mem::take(&mut frontiers[side]) {
+                self.nodes_expanded += 1;
+                let r = rank_packed(&state, n);
+                for &next in grid.neighbors(state.blank()) {
+                    let next_state = state.slide(next);
+                    let next_rank = rank_packed(&next_state, n);
+                    if parents[side][next_rank] != UNVISITED {
+                        continue;
+                    }
+                    parents[side][next_rank] = r as u32;
+                    if parents[1 - side][next_rank] != UNVISITED {
+                        meet = Some(next_rank);
+                    }
+                    next_frontier.push(next_state);
+                }
+            }
+            if let Some(meet) = meet {
+                break meet;
+            }
+            frontiers[side] = next_frontier;
+        };
+
+        let chain = |parents: &[u32], mut r: usize| {
+            let mut ranks = vec![r];
+            while parents[r] as usize != r {
+                r = parents[r] as usize;
+                ranks.push(r);
+            }
+            ranks
+        };
+        let mut ranks = chain(&parents[0], meet);
+        ranks.reverse();
+        ranks.extend(chain(&parents[1], meet).into_iter().skip(1));
+
+        ranks
+            .into_iter()
+            .skip(1)
+            .map(|r| {
+                let blank = unrank(r, n).iter().position(|&t| t == 0).unwrap();
+                grid.cell(blank)
+            })
+            .collect()
+    }
+
+    fn search<C: Code>(&mut self, expected: &State) -> Vec<Cell> {
+        self.nodes_expanded = 0;
+        let grid = Grid::new(&self.states);
+        let expected = PackedState::<C>::pack(expected);
+
+        let mut q = VecDeque::new();
+        q.push_back((PackedState::<C>::pack(&self.states), vec![]));
+
+        let mut memo = HashMap::new();
+        while let Some((state, routes)) = q.pop_front() {
+            self.nodes_expanded += 1;
+            if state == expected {
+                return to_cells(&grid, &routes);
+            }
+
+            if let Some(count) = memo.get(&state)
+                && *count < routes.len()
+            {
+                continue;
+            }
+            memo.insert(state, routes.len());
+
+            for &next in grid.neighbors(state.blank()) {
+                let mut next_routes = routes.clone();
+                next_routes.push(next as u8);
+
+                q.push_back((state.slide(next), next_routes));
+            }
+        }
+
+        vec![]
+    }
+}
+
+impl Solver for BFSSolver {
+    fn name(&self) -> &'static str {
+        SolverKind::Bfs.name()
+    }
+
+    fn solve(&mut self) -> Vec<Cell> {
+        let expected = State::new(self.states.rows, self.states.cols);
+        self.solve_with_expected(&expected)
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+}
+
+/// Reads the optimal move off the board's [`DistanceTable`].
+///
+/// The table is built on first use for each board size (or comes from
+/// [`DistanceTable::install`]), after which solving only costs one lookup
+/// per neighbour along the path.
+pub struct OracleSolver {
+    states: State,
+    nodes_expanded: usize,
+}
+
+impl OracleSolver {
+    pub fn new(states: State) -> Self {
+        OracleSolver {
+            states,
+            nodes_expanded: 0,
+        }
+    }
+}
+
+impl Solver for OracleSolver {
+    fn name(&self) -> &'static str {
+        SolverKind::Oracle.name()
+    }
+
+    fn solve(&mut self) -> Vec<Cell> {
+        let table = DistanceTable::shared(self.states.rows, self.states.cols)
+            .expect("oracle only supports small boards");
+        let moves = table.solve(&self.states).unwrap_or_default();
+        self.nodes_expanded = moves.len();
+        moves
+    }
+
+    fn nodes_expanded(&self) -> usize {
+        self.nodes_expanded
+    }
+}
+
+/// Goal cell index of every tile number (0 is the blank) in `expected`.
+fn goal_indices(expected: &State) -> Vec<usize> {
+    let mut goal = vec![0; expected.pieces.len()];
+    for (i, piece) in expected.pieces.iter().enumerate() {
+        goal[piece.map_or(0, |p| p.num)] = i;
+    }
+    goal
+}
+
+/// Sum of Manhattan distances of every tile and the blank to their goal.
+fn heuristic<C: Code>(grid: &Grid, goal: &[usize], state: &PackedState<C>) -> usize {
+    (0..goal.len())
+        .map(|i| grid.distance(i, goal[state.tile(i)]))
+        .sum()
+}
+
+fn to_cells(grid: &Grid, routes: &[u8]) -> Vec<Cell> {
+    routes.iter().map(|&i| grid.cell(i as usize)).collect()
+}
@@ -0,0 +1,289 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::{BoardSizeError, Cell, Piece, cell, validate_board_size};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateError {
+    Size(BoardSizeError),
+    WrongCount { expected: usize, found: usize },
+    InvalidTile(usize),
+    DuplicateTile(usize),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::Size(e) => e.fmt(f),
+            StateError::WrongCount { expected, found } => {
+                write!(f, "expected {} tiles, found {}", expected, found)
+            }
+            StateError::InvalidTile(num) => write!(f, "tile {} is out of range", num),
+            StateError::DuplicateTile(num) => write!(f, "tile {} appears more than once", num),
+        }
+    }
+}
+
+impl Error for StateError {}
+
+impl From<BoardSizeError> for StateError {
+    fn from(e: BoardSizeError) -> Self {
+        StateError::Size(e)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct State {
+    pub(crate) pieces: Vec<Option<Piece>>,
+
+    pub(crate) rows: usize,
+    pub(crate) cols: usize,
+
+    pub(crate) blank_cell: Cell,
+}
+
+impl Ord for State {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        Ordering::Greater
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl State {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        assert!(
+            validate_board_size(rows, cols).is_ok(),
+            "invalid board size {}x{}",
+            rows,
+            cols
+        );
+        let blank_index = rows * cols - 1;
+        let mut pieces = (0..=blank_index)
+            .map(|n| Some(Piece::new(n + 1)))
+            .collect::<Vec<_>>();
+        pieces[blank_index] = None;
+
+        State {
+            rows,
+            cols,
+            pieces,
+            blank_cell: cell!(blank_index, rows, cols),
+        }
+    }
+
+    /// Builds a position from tile numbers in row-major order, `0` being the
+    /// blank.
+    pub fn from_numbers(rows: usize, cols: usize, numbers: &[usize]) -> Result<Self, StateError> {
+        validate_board_size(rows, cols)?;
+        let num = rows * cols;
+        if numbers.len() != num {
+            return Err(StateError::WrongCount {
+                expected: num,
+                found: numbers.len(),
+            });
+        }
+
+        let mut seen = vec![false; num];
+        for &n in numbers {
+            if n >= num {
+                return Err(StateError::InvalidTile(n));
+            }
+            if seen[n] {
+                return Err(StateError::DuplicateTile(n));
+            }
+            seen[n] = true;
+        }
+
+        let blank_index = numbers.iter().position(|&n| n == 0).unwrap();
+        Ok(State {
+            rows,
+            cols,
+            pieces: numbers
+                .iter()
+                .map(|&n| if n == 0 { None } else { Some(Piece::new(n)) })
+                .collect(),
+            blank_cell: cell!(blank_index, rows, cols),
+        })
+    }
+
+    /// Tile numbers in row-major order, `0` being the blank.
+    pub fn to_numbers(&self) -> Vec<usize> {
+        self.pieces.iter().map(|p| p.map_or(0, |p| p.num)).collect()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn blank_cell(&self) -> Cell {
+        self.blank_cell
+    }
+
+    pub fn pieces(&self) -> &[Option<Piece>] {
+        &self.pieces
+    }
+
+    /// Returns true when the board is a single row or column, where tiles
+    /// can only slide along the line and their order never changes.
+    pub fn is_line(&self) -> bool {
+        self.rows == 1 || self.cols == 1
+    }
+
+    pub(crate) fn neighbors(&self, cell: Cell) -> Vec<Cell> {
+        self.neighbors_at(cell.x, cell.y)
+    }
+
+    fn neighbors_at(&self, x: usize, y: usize) -> Vec<Cell> {
+        let dx = [0, -1, 1, 0];
+        let dy = [-1, 0, 0, 1];
+        (0..4)
+            .filter_map(|k| {
+                let nx = x as isize + dx[k];
+                let ny = y as isize + dy[k];
+
+                if nx < 0 || ny < 0 || nx >= self.cols as isize || ny >= self.rows as isize {
+                    return None;
+                }
+                Some(cell!(nx as usize, ny as usize))
+            })
+            .collect::<Vec<_>>()
+    }
+
+    #[inline]
+    pub fn get_index(&self, cell: Cell) -> usize {
+        cell.as_index(self.rows, self.cols)
+    }
+
+    pub fn get_piece(&self, cell: Cell) -> Option<Piece> {
+        self.pieces[self.get_index(cell)]
+    }
+
+    #[inline]
+    pub fn swap(&mut self, cell1: Cell, cell2: Cell) {
+        assert!(
+            self.blank_cell == cell1 || self.blank_cell == cell2,
+            "swap target should equal blank_index"
+        );
+
+        let idx1 = self.get_index(cell1);
+        let idx2 = self.get_index(cell2);
+
+        self.pieces.swap(idx1, idx2);
+        match self.blank_cell {
+            c if c == cell1 => self.blank_cell = cell2,
+            c if c == cell2 => self.blank_cell = cell1,
+            _ => unreachable!("swap target should equal blank_index"),
+        }
+    }
+
+    /// Moves the blank to `cell` if they are adjacent. Returns false and
+    /// leaves the state untouched otherwise.
+    pub fn try_move(&mut self, cell: Cell) -> bool {
+        if self.blank_cell.manhattan_distance(&cell) != 1
+            || cell.x >= self.cols
+            || cell.y >= self.rows
+        {
+            return false;
+        }
+        self.swap(self.blank_cell, cell);
+        true
+    }
+
+    /// Returns true if the goal can be reached from this position.
+    ///
+    /// Uses the usual inversion parity rule; on a single row or column the
+    /// tiles must already be in order.
+    pub fn is_solvable(&self) -> bool {
+        let tiles = self
+            .pieces
+            .iter()
+            .flatten()
+            .map(|p| p.num)
+            .collect::<Vec<_>>();
+        if self.is_line() {
+            return tiles.windows(2).all(|w| w[0] < w[1]);
+        }
+
+        let mut inversions = 0usize;
+        for i in 0..tiles.len() {
+            for j in i + 1..tiles.len() {
+                if tiles[i] > tiles[j] {
+                    inversions += 1;
+                }
+            }
+        }
+        if !self.cols.is_multiple_of(2) {
+            inversions.is_multiple_of(2)
+        } else {
+            let blank_row_from_bottom = self.rows - 1 - self.blank_cell.y;
+            (inversions + blank_row_from_bottom).is_multiple_of(2)
+        }
+    }
+
+    /// Slides every tile between `cell` and the blank one step towards the
+    /// blank, which ends up at `cell`. `cell` must share a row or column with
+    /// the blank. Returns the cells the blank moved through.
+    pub fn slide_line(&mut self, cell: Cell) -> Vec<Cell> {
+        assert!(
+            cell.x == self.blank_cell.x || cell.y == self.blank_cell.y,
+            "slide target should be in line with blank_cell"
+        );
+
+        let mut moves = vec![];
+        while self.blank_cell != cell {
+            let blank = self.blank_cell;
+            let next = cell!(
+                (blank.x as isize + (cell.x as isize - blank.x as isize).signum()) as usize,
+                (blank.y as isize + (cell.y as isize - blank.y as isize).signum()) as usize
+            );
+            self.swap(blank, next);
+            moves.push(next);
+        }
+        moves
+    }
+
+    pub fn shuffle(&mut self) {
+        self.shuffle_with(&mut rand::rng());
+    }
+
+    /// Shuffles with a random walk of the blank driven by `rng`, so a seeded
+    /// generator always produces the same position.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let n = rng.random_range(500..=1000);
+
+        for _ in 0..n {
+            if let Some(next) = self.neighbors(self.blank_cell).choose(rng) {
+                self.swap(self.blank_cell, *next);
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.blank_cell == cell!(self.cols - 1, self.rows - 1)
+            && self.pieces.iter().enumerate().all(|(i, piece)| {
+                if let Some(piece) = piece {
+                    i == piece.num - 1
+                } else {
+                    i == self.rows * self.cols - 1
+                }
+            })
+    }
+
+    pub fn movable_position(&self, cell: Cell) -> Option<Cell> {
+        let neighbors = self.neighbors(cell);
+        neighbors.into_iter().find(|&n| n == self.blank_cell)
+    }
+}
@@ -0,0 +1,98 @@
+//! Color themes for the board and the screens around it.
+
+use macroquad::color::{self, Color};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+/// Saved by name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: Color,
+    /// Menu items and other plain text.
+    pub text: Color,
+    /// Hints and side panels.
+    pub muted: Color,
+    /// Headers and titles.
+    pub accent: Color,
+    /// The selected menu item.
+    pub highlight: Color,
+    pub tile: Color,
+    /// A tile in its goal cell.
+    pub tile_in_place: Color,
+    pub tile_text: Color,
+    pub grid: Color,
+    pub wall: Color,
+    pub border: Color,
+    /// Shade over tiles the solver has locked in place.
+    pub locked: Color,
+    /// Arrow along the blank's upcoming moves.
+    pub blank_path: Color,
+    /// Arrow from the solver's next tile to its goal.
+    pub tile_path: Color,
+}
+
+impl Theme {
+    pub const LIGHT: Theme = Theme {
+        name: "light",
+        background: color::WHITE,
+        text: color::BLACK,
+        muted: color::DARKGRAY,
+        accent: color::BLUE,
+        highlight: color::LIGHTGRAY,
+        tile: color::BROWN,
+        tile_in_place: color::GREEN,
+        tile_text: color::BLACK,
+        grid: color::GRAY,
+        wall: color::DARKGRAY,
+        border: color::BLACK,
+        locked: Color::new(0.0, 0.0, 0.0, 0.3),
+        blank_path: color::ORANGE,
+        tile_path: color::BLUE,
+    };
+
+    pub const DARK: Theme = Theme {
+        name: "dark",
+        background: Color::new(0.11, 0.11, 0.13, 1.0),
+        text: color::WHITE,
+        muted: color::LIGHTGRAY,
+        accent: color::SKYBLUE,
+        highlight: color::DARKGRAY,
+        tile: Color::new(0.50, 0.33, 0.20, 1.0),
+        tile_in_place: color::DARKGREEN,
+        tile_text: color::WHITE,
+        grid: color::DARKGRAY,
+        wall: Color::new(0.35, 0.35, 0.38, 1.0),
+        border: color::LIGHTGRAY,
+        locked: Color::new(0.0, 0.0, 0.0, 0.45),
+        blank_path: color::GOLD,
+        tile_path: color::SKYBLUE,
+    };
+
+    pub const ALL: [Theme; 2] = [Theme::LIGHT, Theme::DARK];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Theme::ALL.into_iter().find(|theme| theme.name == name)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::LIGHT
+    }
+}
+
+impl Serialize for Theme {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for Theme {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Theme::from_name(&name).ok_or_else(|| {
+            let names = Theme::ALL.map(|theme| theme.name).join(", ");
+            de::Error::custom(format!("unknown theme `{}` (available: {})", name, names))
+        })
+    }
+}
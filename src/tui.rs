@@ -0,0 +1,134 @@
+//! Terminal front end: a box-drawing board and keyboard input, for playing
+//! where no display is available, e.g. over SSH.
+
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Stylize;
+use crossterm::{cursor, execute, queue, terminal};
+
+use crate::{Cell, State, cell};
+
+/// What a key press asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Slide the tile in this cell into the blank.
+    Slide(Cell),
+    Undo,
+    Quit,
+}
+
+/// Draws `state` with box-drawing characters, one line per text row.
+/// Tiles in their goal cell are green if `color` is set.
+pub fn render(state: &State, color: bool) -> String {
+    let rows = state.rows();
+    let cols = state.cols();
+    let width = (rows * cols - 1).to_string().len() + 2;
+
+    let border = |left: &str, middle: &str, right: &str| {
+        let segment = "─".repeat(width);
+        let inner = vec![segment; cols].join(middle);
+        format!("{}{}{}\n", left, inner, right)
+    };
+
+    let mut text = border("┌", "┬", "┐");
+    for y in 0..rows {
+        text += "│";
+        for x in 0..cols {
+            let i = cell!(x, y).as_index(rows, cols);
+            let label = match state.pieces()[i] {
+                Some(piece) => format!("{:^width$}", piece.num, width = width),
+                None => " ".repeat(width),
+            };
+            match state.pieces()[i] {
+                Some(piece) if color && piece.num == i + 1 => {
+                    text += &label.green().to_string();
+                }
+                _ => text += &label,
+            }
+            text += "│";
+        }
+        text += "\n";
+        text += &if y + 1 < rows {
+            border("├", "┼", "┤")
+        } else {
+            border("└", "┴", "┘")
+        };
+    }
+    text
+}
+
+/// Maps a key to an action. Arrow keys, WASD and hjkl slide the tile next to
+/// the blank in that direction, e.g. left slides the tile right of the blank.
+pub fn key_action(state: &State, key: KeyEvent) -> Option<Action> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        return Some(Action::Quit);
+    }
+
+    let (dx, dy) = match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => return Some(Action::Quit),
+        KeyCode::Char('u') | KeyCode::Char('z') | KeyCode::Backspace => {
+            return Some(Action::Undo);
+        }
+        KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('h') => (1, 0),
+        KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('l') => (-1, 0),
+        KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('k') => (0, 1),
+        KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('j') => (0, -1),
+        _ => return None,
+    };
+
+    let blank = state.blank_cell();
+    let x = blank.x.checked_add_signed(dx)?;
+    let y = blank.y.checked_add_signed(dy)?;
+    if x >= state.cols() || y >= state.rows() {
+        return None;
+    }
+    Some(Action::Slide(cell!(x, y)))
+}
+
+/// The terminal in raw mode on the alternate screen; restored when dropped.
+pub struct Terminal {
+    stdout: Stdout,
+}
+
+impl Terminal {
+    pub fn enter() -> io::Result<Self> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Terminal { stdout })
+    }
+
+    /// Replaces the screen contents with `text`.
+    pub fn draw(&mut self, text: &str) -> io::Result<()> {
+        queue!(
+            self.stdout,
+            cursor::MoveTo(0, 0),
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        for line in text.lines() {
+            write!(self.stdout, "{}", line)?;
+            queue!(self.stdout, cursor::MoveToNextLine(1))?;
+        }
+        self.stdout.flush()
+    }
+
+    /// Waits up to `timeout` for a key press.
+    pub fn next_key(&mut self, timeout: Duration) -> io::Result<Option<KeyEvent>> {
+        if !event::poll(timeout)? {
+            return Ok(None);
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => Ok(Some(key)),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(self.stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
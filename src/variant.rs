@@ -0,0 +1,659 @@
+//! Boards with several blanks and immovable walls.
+//!
+//! [`State`](crate::State) is the classic puzzle with exactly one blank, which
+//! the packed and ranked solvers rely on. [`VariantState`] is the general
+//! model: any cell may be a wall, and any tile next to any blank can slide
+//! into it.
+//!
+//! As in the classic puzzle, tile `n` belongs in cell `n - 1` (row-major), so
+//! numbers skip the walls; the blanks belong in the last free cells. On a
+//! [`Topology::Torus`] the rows and columns wrap around, so a tile on one edge
+//! can slide into a blank on the opposite edge. Boards can also be tiled with
+//! hexagons or triangles (see [`Geometry`]); those don't wrap.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use macroquad::math::{Vec2, vec2};
+
+use crate::{BoardSizeError, Cell, Geometry, cell, validate_board_size};
+
+/// Searches give up after expanding this many positions.
+pub const NODE_LIMIT: usize = 2_000_000;
+
+/// Boards with up to this many open cells get a shortest solution from the
+/// AI; larger ones a quick one.
+pub const OPTIMAL_MAX_CELLS: usize = 12;
+
+/// Boards with more open cells than this are too large for the AI.
+pub const AI_MAX_CELLS: usize = 20;
+
+/// How much more the tiles' distances count than the moves made in the quick
+/// search.
+const QUICK_WEIGHT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Square {
+    Tile(usize),
+    Blank,
+    Wall,
+}
+
+/// How the edges of the board connect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Topology {
+    /// The board ends at its edges.
+    #[default]
+    Flat,
+    /// Rows and columns wrap around.
+    Torus,
+}
+
+/// The tile in `from` slides into the blank `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub from: Cell,
+    pub to: Cell,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VariantError {
+    Size(BoardSizeError),
+    WallOutside(Cell),
+    /// At least one blank is needed, and at most one per free cell.
+    Blanks {
+        blanks: usize,
+        free: usize,
+    },
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The tiles don't match the walls and blanks of the board.
+    Tiles,
+}
+
+impl fmt::Display for VariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariantError::Size(e) => e.fmt(f),
+            VariantError::WallOutside(cell) => {
+                write!(f, "wall ({}, {}) is outside the board", cell.x, cell.y)
+            }
+            VariantError::Blanks { blanks, free } => write!(
+                f,
+                "{} blanks don't fit a board with {} free cells (need 1..={})",
+                blanks, free, free
+            ),
+            VariantError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            VariantError::Tiles => write!(
+                f,
+                "tile numbers must be the cell numbers of the free cells before the blanks"
+            ),
+        }
+    }
+}
+
+impl Error for VariantError {}
+
+impl From<BoardSizeError> for VariantError {
+    fn from(e: BoardSizeError) -> Self {
+        VariantError::Size(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VariantState {
+    rows: usize,
+    cols: usize,
+    squares: Vec<Square>,
+    topology: Topology,
+    geometry: Geometry,
+}
+
+impl VariantState {
+    /// The solved board with `walls` and `blanks` blanks.
+    pub fn goal(
+        rows: usize,
+        cols: usize,
+        walls: &[Cell],
+        blanks: usize,
+    ) -> Result<Self, VariantError> {
+        validate_board_size(rows, cols)?;
+        let mut squares = vec![Square::Blank; rows * cols];
+        for &wall in walls {
+            if wall.x >= cols || wall.y >= rows {
+                return Err(VariantError::WallOutside(wall));
+            }
+            squares[wall.as_index(rows, cols)] = Square::Wall;
+        }
+
+        let free = squares.iter().filter(|&&s| s == Square::Blank).count();
+        if blanks == 0 || blanks > free {
+            return Err(VariantError::Blanks { blanks, free });
+        }
+        let mut tiles = free - blanks;
+        for (i, square) in squares.iter_mut().enumerate() {
+            if *square == Square::Blank && tiles > 0 {
+                *square = Square::Tile(i + 1);
+                tiles -= 1;
+            }
+        }
+        Ok(VariantState {
+            rows,
+            cols,
+            squares,
+            topology: Topology::Flat,
+            geometry: Geometry::Square,
+        })
+    }
+
+    /// The same board with `topology`.
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// The same board tiled with `geometry`. Only square boards wrap.
+    pub fn with_geometry(mut self, geometry: Geometry) -> Self {
+        self.geometry = geometry;
+        self
+    }
+
+    /// Parses a board written one row per line: tile numbers, `.` for a
+    /// blank and `#` for a wall, separated by spaces.
+    pub fn parse(text: &str) -> Result<Self, VariantError> {
+        let mut squares = vec![];
+        let mut rows = 0;
+        let mut cols = None;
+        for (i, line) in text.lines().enumerate() {
+            let syntax = |message: String| VariantError::Syntax {
+                line: i + 1,
+                message,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let row = line
+                .split_whitespace()
+                .map(|word| match word {
+                    "." => Ok(Square::Blank),
+                    "#" => Ok(Square::Wall),
+                    _ => word
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .map(Square::Tile)
+                        .ok_or_else(|| syntax(format!("`{}` is not a tile, `.` or `#`", word))),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if *cols.get_or_insert(row.len()) != row.len() {
+                return Err(syntax("rows have different lengths".to_string()));
+            }
+            squares.extend(row);
+            rows += 1;
+        }
+
+        let cols = cols.unwrap_or(0);
+        let state = VariantState {
+            rows,
+            cols,
+            squares,
+            topology: Topology::Flat,
+            geometry: Geometry::Square,
+        };
+        let goal = VariantState::goal(rows, cols, &state.walls(), state.blanks().len())?;
+        let mut tiles = state.tiles();
+        tiles.sort();
+        if tiles != goal.tiles() {
+            return Err(VariantError::Tiles);
+        }
+        Ok(state)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    pub fn squares(&self) -> &[Square] {
+        &self.squares
+    }
+
+    pub fn square(&self, cell: Cell) -> Square {
+        self.squares[cell.as_index(self.rows, self.cols)]
+    }
+
+    pub fn blanks(&self) -> Vec<Cell> {
+        self.cells_where(|s| s == Square::Blank)
+    }
+
+    pub fn walls(&self) -> Vec<Cell> {
+        self.cells_where(|s| s == Square::Wall)
+    }
+
+    fn cells_where(&self, f: impl Fn(Square) -> bool) -> Vec<Cell> {
+        (0..self.squares.len())
+            .filter(|&i| f(self.squares[i]))
+            .map(|i| cell!(i, self.rows, self.cols))
+            .collect()
+    }
+
+    fn tiles(&self) -> Vec<usize> {
+        self.squares
+            .iter()
+            .filter_map(|s| match s {
+                Square::Tile(n) => Some(*n),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The solved board with the same walls, number of blanks and topology.
+    pub fn solved(&self) -> Self {
+        VariantState::goal(self.rows, self.cols, &self.walls(), self.blanks().len())
+            .unwrap()
+            .with_topology(self.topology)
+            .with_geometry(self.geometry)
+    }
+
+    /// Cells next to `cell` that aren't walls.
+    pub fn open_neighbors(&self, cell: Cell) -> Vec<Cell> {
+        if self.geometry != Geometry::Square {
+            let mut cells = self.geometry.neighbors(cell, self.rows, self.cols);
+            cells.retain(|&c| self.square(c) != Square::Wall);
+            return cells;
+        }
+        let mut cells = vec![];
+        for (dx, dy) in [(0, -1), (-1, 0), (1, 0), (0, 1)] {
+            if let Some(next) = self.step_from(cell, dx, dy)
+                && next != cell
+                && !cells.contains(&next)
+            {
+                cells.push(next);
+            }
+        }
+        cells.retain(|&c| self.square(c) != Square::Wall);
+        cells
+    }
+
+    /// The cell one step from `cell`, wrapping around on a torus.
+    fn step_from(&self, cell: Cell, dx: isize, dy: isize) -> Option<Cell> {
+        match self.topology {
+            Topology::Flat => {
+                let x = cell.x.checked_add_signed(dx).filter(|&x| x < self.cols)?;
+                let y = cell.y.checked_add_signed(dy).filter(|&y| y < self.rows)?;
+                Some(cell!(x, y))
+            }
+            Topology::Torus => Some(cell!(
+                (cell.x + self.cols).wrapping_add_signed(dx) % self.cols,
+                (cell.y + self.rows).wrapping_add_signed(dy) % self.rows
+            )),
+        }
+    }
+
+    /// The direction `mv` moves its tile in on screen, as a unit vector.
+    pub fn direction(&self, mv: Move) -> Vec2 {
+        if self.geometry != Geometry::Square {
+            let step = self.geometry.center(mv.to) - self.geometry.center(mv.from);
+            return step.normalize_or_zero();
+        }
+        [(0, -1), (-1, 0), (1, 0), (0, 1)]
+            .into_iter()
+            .find(|&(dx, dy)| self.step_from(mv.from, dx, dy) == Some(mv.to))
+            .map_or(Vec2::ZERO, |(dx, dy)| vec2(dx as f32, dy as f32))
+    }
+
+    /// Every legal move.
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        for to in self.blanks() {
+            for from in self.open_neighbors(to) {
+                if matches!(self.square(from), Square::Tile(_)) {
+                    moves.push(Move { from, to });
+                }
+            }
+        }
+        moves
+    }
+
+    /// Applies `mv` if it is legal.
+    pub fn slide(&mut self, mv: Move) -> bool {
+        let legal = mv.to.x < self.cols
+            && mv.to.y < self.rows
+            && self.open_neighbors(mv.to).contains(&mv.from)
+            && matches!(self.square(mv.from), Square::Tile(_))
+            && self.square(mv.to) == Square::Blank;
+        if legal {
+            let from = mv.from.as_index(self.rows, self.cols);
+            let to = mv.to.as_index(self.rows, self.cols);
+            self.squares.swap(from, to);
+        }
+        legal
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.squares.iter().enumerate().all(|(i, s)| match s {
+            Square::Tile(n) => *n == i + 1,
+            _ => true,
+        })
+    }
+
+    /// Number of steps between every pair of cells, walking around the walls
+    /// (and across the edges of a torus); 0 where there's no way through.
+    fn walking_distances(&self) -> Vec<Vec<usize>> {
+        let mut distances = vec![vec![0; self.squares.len()]; self.squares.len()];
+        for start in self.cells_where(|s| s != Square::Wall) {
+            let row = &mut distances[start.as_index(self.rows, self.cols)];
+            let mut seen = vec![false; self.squares.len()];
+            seen[start.as_index(self.rows, self.cols)] = true;
+            let mut queue = VecDeque::from([(start, 0)]);
+            while let Some((cell, steps)) = queue.pop_front() {
+                row[cell.as_index(self.rows, self.cols)] = steps;
+                for next in self.open_neighbors(cell) {
+                    let idx = next.as_index(self.rows, self.cols);
+                    if !seen[idx] {
+                        seen[idx] = true;
+                        queue.push_back((next, steps + 1));
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    /// Sum of the distances of the tiles to their goal cells.
+    fn tile_distance(&self, distances: &[Vec<usize>]) -> usize {
+        self.squares
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| match s {
+                Square::Tile(n) => Some(distances[i][n - 1]),
+                _ => None,
+            })
+            .sum()
+    }
+
+    /// Returns false if the position can't reach the goal.
+    ///
+    /// Each region enclosed by walls is checked on its own: its tiles and
+    /// number of blanks must match the goal; with one blank the classic
+    /// parity rule applies, and tiles in a corridor or loop can't pass each
+    /// other. The parity rule only holds if the region can be colored like a
+    /// checkerboard, which a torus with an odd side can't. The check is exact
+    /// for corridors, loops and regions without a cell whose removal would
+    /// split them, which covers the usual layouts; for other shapes a
+    /// position may pass and still be unsolvable.
+    pub fn is_solvable(&self) -> bool {
+        let goal = self.solved();
+        self.regions().iter().all(|region| {
+            let contents =
+                |state: &VariantState| region.iter().map(|&c| state.square(c)).collect::<Vec<_>>();
+            let current = contents(self);
+            let target = contents(&goal);
+
+            let mut a = current.clone();
+            let mut b = target.clone();
+            a.sort_by_key(square_key);
+            b.sort_by_key(square_key);
+            if a != b {
+                return false;
+            }
+            let blanks = current.iter().filter(|&&s| s == Square::Blank).count();
+            if blanks == 0 {
+                return current == target;
+            }
+
+            let degrees = region
+                .iter()
+                .map(|&c| self.open_neighbors(c).len())
+                .collect::<Vec<_>>();
+            let edges = degrees.iter().sum::<usize>() / 2;
+            if degrees.iter().all(|&d| d <= 2) && region.len() > 2 {
+                let is_loop = edges == region.len();
+                return same_order(&self.walk(region), self, &goal, is_loop);
+            }
+
+            if blanks > 1 {
+                return true;
+            }
+            match self.checkerboard(region) {
+                Some(colors) => one_blank_parity(&colors, &current, &target),
+                None => true,
+            }
+        })
+    }
+
+    /// Colors the cells of `region` with 0 and 1 so that neighbours differ,
+    /// or returns `None` if that's impossible.
+    fn checkerboard(&self, region: &[Cell]) -> Option<Vec<usize>> {
+        let index = |cell: Cell| cell.as_index(self.rows, self.cols);
+        let mut colors = vec![None; self.squares.len()];
+        colors[index(region[0])] = Some(0);
+        let mut queue = VecDeque::from([region[0]]);
+        while let Some(cell) = queue.pop_front() {
+            let color = colors[index(cell)].unwrap();
+            for next in self.open_neighbors(cell) {
+                match colors[index(next)] {
+                    Some(c) if c == color => return None,
+                    Some(_) => {}
+                    None => {
+                        colors[index(next)] = Some(1 - color);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        region.iter().map(|&c| colors[index(c)]).collect()
+    }
+
+    /// Free cells grouped into regions connected around the walls.
+    fn regions(&self) -> Vec<Vec<Cell>> {
+        let mut seen = vec![false; self.squares.len()];
+        let mut regions = vec![];
+        for start in self.cells_where(|s| s != Square::Wall) {
+            if seen[start.as_index(self.rows, self.cols)] {
+                continue;
+            }
+            seen[start.as_index(self.rows, self.cols)] = true;
+            let mut region = vec![start];
+            let mut queue = VecDeque::from([start]);
+            while let Some(cell) = queue.pop_front() {
+                for next in self.open_neighbors(cell) {
+                    let idx = next.as_index(self.rows, self.cols);
+                    if !seen[idx] {
+                        seen[idx] = true;
+                        region.push(next);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            regions.push(region);
+        }
+        regions
+    }
+
+    /// Cells of a corridor or loop region in walking order.
+    fn walk(&self, region: &[Cell]) -> Vec<Cell> {
+        let start = region
+            .iter()
+            .copied()
+            .find(|&c| self.open_neighbors(c).len() == 1)
+            .unwrap_or(region[0]);
+        let mut order = vec![start];
+        let mut previous = None;
+        let mut current = start;
+        while let Some(next) = self
+            .open_neighbors(current)
+            .into_iter()
+            .find(|&c| Some(c) != previous && c != start)
+        {
+            order.push(next);
+            previous = Some(current);
+            current = next;
+        }
+        order
+    }
+
+    /// Shuffles the board by a random walk of blank moves, so the result is
+    /// always solvable.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let count = rng.random_range(500..=1000);
+        for _ in 0..count {
+            if let Some(&mv) = self.moves().choose(rng) {
+                self.slide(mv);
+            }
+        }
+    }
+
+    /// A shortest solution found by A* on the tiles' distances to their
+    /// goals, or `None` if the goal isn't reached within [`NODE_LIMIT`]
+    /// positions.
+    pub fn solve(&self) -> Option<Vec<Move>> {
+        let distances = self.walking_distances();
+        self.search(|state| state.tile_distance(&distances))
+    }
+
+    /// A solution found quickly by weighting the tiles' distances over the
+    /// moves made so far; it may be several times longer than the shortest.
+    pub fn solve_quick(&self) -> Option<Vec<Move>> {
+        let distances = self.walking_distances();
+        self.search(|state| QUICK_WEIGHT * state.tile_distance(&distances))
+    }
+
+    /// Cells that aren't walls.
+    pub fn open_cells(&self) -> usize {
+        self.squares.iter().filter(|&&s| s != Square::Wall).count()
+    }
+
+    /// The solution the AI plays: the shortest on boards of up to
+    /// [`OPTIMAL_MAX_CELLS`] open cells, a quick one up to [`AI_MAX_CELLS`].
+    /// `None` on larger boards or if the search gives up.
+    pub fn solve_ai(&self) -> Option<Vec<Move>> {
+        match self.open_cells() {
+            n if n <= OPTIMAL_MAX_CELLS => self.solve(),
+            n if n <= AI_MAX_CELLS => self.solve_quick(),
+            _ => None,
+        }
+    }
+
+    /// A shortest solution found by breadth-first search.
+    pub fn solve_bfs(&self) -> Option<Vec<Move>> {
+        self.search(|_| 0)
+    }
+
+    fn search(&self, heuristic: impl Fn(&VariantState) -> usize) -> Option<Vec<Move>> {
+        // every node remembers its parent and the move that led to it
+        let mut nodes: Vec<(VariantState, usize, Option<Move>)> = vec![(self.clone(), 0, None)];
+        let mut best = HashMap::from([(self.clone(), 0usize)]);
+        let mut open = BinaryHeap::from([Reverse((heuristic(self), 0usize, 0usize))]);
+
+        while let Some(Reverse((_, g, idx))) = open.pop() {
+            if best[&nodes[idx].0] < g {
+                continue;
+            }
+            if nodes[idx].0.is_finished() {
+                let mut moves = vec![];
+                let mut idx = idx;
+                while let (_, parent, Some(mv)) = &nodes[idx] {
+                    moves.push(*mv);
+                    idx = *parent;
+                }
+                moves.reverse();
+                return Some(moves);
+            }
+            if nodes.len() > NODE_LIMIT {
+                return None;
+            }
+
+            for mv in nodes[idx].0.moves() {
+                let mut next = nodes[idx].0.clone();
+                next.slide(mv);
+                if best.get(&next).is_some_and(|&known| known <= g + 1) {
+                    continue;
+                }
+                best.insert(next.clone(), g + 1);
+                let f = g + 1 + heuristic(&next);
+                nodes.push((next, idx, Some(mv)));
+                open.push(Reverse((f, g + 1, nodes.len() - 1)));
+            }
+        }
+        None
+    }
+}
+
+fn square_key(square: &Square) -> usize {
+    match square {
+        Square::Tile(n) => *n,
+        Square::Blank => 0,
+        Square::Wall => usize::MAX,
+    }
+}
+
+/// Tiles along a corridor keep their order; around a loop they keep their
+/// cyclic order.
+fn same_order(path: &[Cell], state: &VariantState, goal: &VariantState, is_loop: bool) -> bool {
+    let tiles = |s: &VariantState| {
+        path.iter()
+            .filter_map(|&c| match s.square(c) {
+                Square::Tile(n) => Some(n),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    let current = tiles(state);
+    let target = tiles(goal);
+    if !is_loop || current.is_empty() {
+        return current == target;
+    }
+    (0..current.len()).any(|shift| {
+        current
+            .iter()
+            .cycle()
+            .skip(shift)
+            .take(current.len())
+            .eq(target.iter())
+    })
+}
+
+/// The parity rule for one blank on a grid: every move swaps the blank with
+/// a tile and changes the color of the blank's cell on a checkerboard, so the
+/// permutation and the blank's color change must have the same parity.
+/// `colors` are the checkerboard colors of the region's cells.
+fn one_blank_parity(colors: &[usize], current: &[Square], target: &[Square]) -> bool {
+    let position =
+        |squares: &[Square], square: Square| squares.iter().position(|&s| s == square).unwrap();
+    let mut perm = current
+        .iter()
+        .map(|&s| position(target, s))
+        .collect::<Vec<_>>();
+
+    let mut swaps = 0usize;
+    for i in 0..perm.len() {
+        while perm[i] != i {
+            let j = perm[i];
+            perm.swap(i, j);
+            swaps += 1;
+        }
+    }
+
+    let blank = colors[position(current, Square::Blank)];
+    let goal_blank = colors[position(target, Square::Blank)];
+    swaps.is_multiple_of(2) == (blank == goal_blank)
+}
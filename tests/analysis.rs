@@ -0,0 +1,49 @@
+//! Distance tables and how they are read back from disk.
+
+use slide_puzzle::analysis::{AnalysisError, DistanceTable};
+
+fn written(table: &DistanceTable) -> Vec<u8> {
+    let mut bytes = vec![];
+    table.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn a_written_table_reads_back() {
+    let table = DistanceTable::build(2, 3).unwrap();
+    // the 2x3 board's God's number
+    assert_eq!(table.max_distance(), 21);
+    let read = DistanceTable::read_from(&written(&table)[..]).unwrap();
+    assert_eq!(read, table);
+}
+
+#[test]
+fn inconsistent_tables_are_rejected() {
+    let table = DistanceTable::build(2, 3).unwrap();
+    let header = 7;
+
+    // the goal is not at 0
+    let mut bytes = written(&table);
+    let goal = bytes[header..].iter().position(|&d| d == 0).unwrap();
+    bytes[header + goal] = 1;
+    assert!(matches!(
+        DistanceTable::read_from(&bytes[..]),
+        Err(AnalysisError::Format(_))
+    ));
+
+    // a position one move from the goal claims to be five away
+    let mut bytes = written(&table);
+    let one = bytes[header..].iter().position(|&d| d == 1).unwrap();
+    bytes[header + one] = 5;
+    assert!(matches!(
+        DistanceTable::read_from(&bytes[..]),
+        Err(AnalysisError::Format(_))
+    ));
+
+    // a truncated file
+    let bytes = written(&table);
+    assert!(matches!(
+        DistanceTable::read_from(&bytes[..bytes.len() - 1]),
+        Err(AnalysisError::Format(_))
+    ));
+}
@@ -0,0 +1,80 @@
+//! Benchmark corpora.
+
+use slide_puzzle::bench::{self, BenchRecord, CorpusError, Instance};
+use slide_puzzle::{SolverKind, State};
+
+#[test]
+fn built_in_corpora_parse() {
+    let hardest = bench::hardest_8_puzzle();
+    assert_eq!(hardest.len(), 2);
+
+    let korf = bench::korf_100();
+    assert_eq!(korf.len(), 100);
+    assert!(
+        korf.iter()
+            .all(|i| i.state.rows() == 4 && i.state.cols() == 4)
+    );
+    let total = korf.iter().map(|i| i.optimal.unwrap()).sum::<usize>();
+    // the published average optimal length is 53.05
+    assert_eq!(total, 5305);
+}
+
+#[test]
+fn a_line_without_a_name_is_a_syntax_error() {
+    assert!(matches!(
+        bench::parse_corpus("size 2 2\n; 5\n"),
+        Err(CorpusError::Syntax { line: 2, .. })
+    ));
+    assert!(matches!(
+        bench::parse_corpus("a 1 2 3 0\n"),
+        Err(CorpusError::Syntax { line: 1, .. })
+    ));
+}
+
+fn record<'a>(records: &'a [BenchRecord], instance: &str, solver: SolverKind) -> &'a BenchRecord {
+    records
+        .iter()
+        .find(|r| r.instance == instance && r.solver == solver.name())
+        .unwrap()
+}
+
+#[test]
+fn run_rates_every_solver_against_the_optimum() {
+    let mut instances = bench::hardest_8_puzzle();
+    instances.truncate(1);
+    // no known optimum, so BFS provides it
+    instances.push(Instance {
+        name: "near".to_string(),
+        state: State::from_numbers(3, 3, &[1, 2, 3, 4, 5, 6, 0, 7, 8]).unwrap(),
+        optimal: None,
+    });
+    let kinds = [SolverKind::My, SolverKind::AStar, SolverKind::Bfs];
+    let records = bench::run(&instances, &kinds);
+    assert_eq!(records.len(), 6);
+
+    let bfs = record(&records, "hardest-8-a", SolverKind::Bfs);
+    assert!(bfs.solved);
+    assert_eq!(bfs.solution_length, 31);
+    assert_eq!(bfs.optimality_gap, Some(0));
+    let astar = record(&records, "hardest-8-a", SolverKind::AStar);
+    assert!(astar.solved);
+    assert_eq!(astar.optimality_gap, Some(astar.solution_length - 31));
+    // the reduction solver only places the first row and column
+    let my = record(&records, "hardest-8-a", SolverKind::My);
+    assert!(!my.solved);
+    assert_eq!(my.optimality_gap, None);
+    assert_eq!(my.optimal_length, Some(31));
+
+    for kind in kinds {
+        assert_eq!(record(&records, "near", kind).optimal_length, Some(2));
+    }
+    assert_eq!(
+        record(&records, "near", SolverKind::Bfs).optimality_gap,
+        Some(0)
+    );
+
+    // without BFS there is nothing to compare with
+    let records = bench::run(&instances[1..], &[SolverKind::AStar]);
+    assert_eq!(records[0].optimal_length, None);
+    assert_eq!(records[0].optimality_gap, None);
+}
@@ -0,0 +1,117 @@
+//! Reading and checking the config file.
+
+use macroquad::input::KeyCode;
+use slide_puzzle::config::{Config, ConfigError, Key};
+use slide_puzzle::{SolverKind, Theme};
+
+#[test]
+fn an_empty_file_keeps_the_defaults() {
+    let config = Config::parse("").unwrap();
+    assert_eq!(config, Config::default());
+    assert_eq!(config.theme, Theme::LIGHT);
+    assert_eq!(config.solver, None);
+    assert_eq!((config.board.rows, config.board.cols), (4, 4));
+    assert_eq!(config.keys.quit, Key(KeyCode::Q));
+    assert_eq!((config.window.width, config.window.height), (1920, 1080));
+}
+
+#[test]
+fn settings_replace_only_what_they_name() {
+    let config = Config::parse(
+        r#"
+theme = "dark"
+solver = "astar"
+
+[board]
+cols = 3
+
+[animation]
+moves_per_second = 12.5
+
+[keys]
+quit = "X"
+pause = "p"
+up = "W"
+
+[window]
+fullscreen = true
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.theme, Theme::DARK);
+    assert_eq!(config.solver, Some(SolverKind::AStar));
+    assert_eq!((config.board.rows, config.board.cols), (4, 3));
+    assert_eq!(config.board.border, 10.0);
+    assert_eq!(config.animation.moves_per_second, 12.5);
+    assert_eq!(config.keys.quit, Key(KeyCode::X));
+    assert_eq!(config.keys.pause, Key(KeyCode::P));
+    assert_eq!(config.keys.up, Key(KeyCode::W));
+    assert_eq!(config.keys.down, Key(KeyCode::Down));
+    assert!(config.window.fullscreen);
+    assert_eq!(config.window.width, 1920);
+
+    assert_eq!(Config::parse("solver = \"auto\"").unwrap().solver, None);
+}
+
+#[test]
+fn keys_are_named_without_case() {
+    assert_eq!(Key::from_name("escape"), Some(Key(KeyCode::Escape)));
+    assert_eq!(Key::from_name("PageUp"), Some(Key(KeyCode::PageUp)));
+    assert_eq!(Key::from_name("7"), Some(Key(KeyCode::Key7)));
+    assert_eq!(Key::from_name("F5"), Some(Key(KeyCode::F5)));
+    assert_eq!(Key::from_name("key7"), None);
+    assert_eq!(Key::from_name("hyper"), None);
+
+    assert_eq!(Key(KeyCode::Key7).name(), "7");
+    assert_eq!(Key(KeyCode::Q).to_string(), "Q");
+    assert_eq!(Key(KeyCode::Escape).to_string(), "escape");
+}
+
+#[test]
+fn bad_settings_are_rejected() {
+    let parse_error = |text: &str| match Config::parse(text) {
+        Err(ConfigError::Parse(e)) => e.message().to_string(),
+        other => panic!("expected a parse error, got {:?}", other),
+    };
+    assert!(parse_error("theme = \"neon\"").contains("unknown theme `neon`"));
+    assert!(parse_error("solver = \"fast\"").contains("unknown solver `fast`"));
+    assert!(parse_error("[keys]\nquit = \"hyper\"").contains("unknown key `hyper`"));
+    assert!(parse_error("[board]\ntiles = 3").contains("unknown field `tiles`"));
+    assert!(parse_error("[board]\nrows = \"four\"").contains("invalid type"));
+
+    for text in [
+        "[board]\nrows = 11",
+        "[board]\nrows = 1\ncols = 1",
+        "[board]\nborder = -1.0",
+        "[animation]\nmoves_per_second = 0.0",
+        "[window]\nwidth = 0",
+    ] {
+        assert!(
+            matches!(Config::parse(text), Err(ConfigError::Invalid(_))),
+            "{}",
+            text
+        );
+    }
+}
+
+#[test]
+fn written_settings_read_back_the_same() {
+    let mut config = Config::parse("theme = \"dark\"\nsolver = \"bfs\"").unwrap();
+    config.keys.restart = Key(KeyCode::F2);
+    config.window.height = 720;
+    let text = config.to_toml();
+    assert!(text.contains("restart = \"f2\""), "{}", text);
+    assert_eq!(Config::parse(&text).unwrap(), config);
+}
+
+#[test]
+fn a_missing_file_means_the_defaults() {
+    let dir = std::env::temp_dir().join(format!("slide-puzzle-config-{}", std::process::id()));
+    let path = dir.join("config.toml");
+    assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(&path, "[board]\nrows = 3\n").unwrap();
+    assert_eq!(Config::load(&path).unwrap().board.rows, 3);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
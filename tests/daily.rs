@@ -0,0 +1,119 @@
+//! Dates, seeds and results of the daily puzzle.
+
+use slide_puzzle::daily::{self, DailyResult, DailyStats, Date};
+
+fn date(s: &str) -> Date {
+    s.parse().unwrap()
+}
+
+fn result(day: &str, moves: usize) -> DailyResult {
+    DailyResult {
+        date: date(day),
+        rows: 4,
+        cols: 4,
+        moves,
+        seconds: 83.4,
+        optimal: None,
+    }
+}
+
+#[test]
+fn days_round_trip() {
+    assert_eq!(Date::from_days(0), date("1970-01-01"));
+    assert_eq!(date("1969-12-31").to_days(), -1);
+    assert_eq!(date("2000-03-01").to_days(), 11017);
+    for (day, next) in [
+        ("2024-02-28", "2024-02-29"),
+        ("2024-02-29", "2024-03-01"),
+        ("2023-02-28", "2023-03-01"),
+        ("2000-02-28", "2000-02-29"),
+        ("1900-02-28", "1900-03-01"),
+        ("1899-12-31", "1900-01-01"),
+    ] {
+        assert_eq!(
+            Date::from_days(date(day).to_days() + 1),
+            date(next),
+            "{}",
+            day
+        );
+        assert_eq!(date(next).previous(), date(day));
+    }
+    for days in (-800_000..800_000).step_by(997) {
+        assert_eq!(Date::from_days(days).to_days(), days);
+    }
+}
+
+#[test]
+fn parsing_rejects_dates_that_dont_exist() {
+    assert_eq!(date("2025-01-31").to_string(), "2025-01-31");
+    for s in [
+        "2025-02-30",
+        "2023-02-29",
+        "2025-13-01",
+        "2025-00-10",
+        "2025-01-00",
+        "2025-01",
+        "2025/01/01",
+        // past the range of a year, not wrapped around to 2025
+        "4294969321-01-01",
+        "2025-4294967297-01",
+    ] {
+        assert!(s.parse::<Date>().is_err(), "{}", s);
+    }
+}
+
+#[test]
+fn seeds_are_stable() {
+    let seed = daily::seed(date("2026-01-01"), 4, 4);
+    // FNV-1a of "2026-01-01 4x4"; changing it changes every daily puzzle
+    assert_eq!(seed, 0x2557a95a25ef406b);
+    assert_ne!(daily::seed(date("2026-01-02"), 4, 4), seed);
+    assert_ne!(daily::seed(date("2026-01-01"), 3, 3), seed);
+    assert_eq!(
+        daily::puzzle(date("2026-01-01"), 4, 4).unwrap().state,
+        daily::puzzle(date("2026-01-01"), 4, 4).unwrap().state
+    );
+}
+
+#[test]
+fn only_the_first_solve_of_a_day_counts() {
+    let mut stats = DailyStats::default();
+    assert!(stats.record(result("2026-01-01", 60)));
+    assert!(!stats.record(result("2026-01-01", 40)));
+    assert_eq!(stats.get(date("2026-01-01"), 4, 4).unwrap().moves, 60);
+    // another size is another puzzle
+    assert!(stats.record(DailyResult {
+        rows: 3,
+        cols: 3,
+        ..result("2026-01-01", 20)
+    }));
+    assert_eq!(stats.results.len(), 2);
+}
+
+#[test]
+fn streaks_stop_at_a_missed_day() {
+    let mut stats = DailyStats::default();
+    for day in ["2025-12-30", "2025-12-31", "2026-01-02", "2026-01-03"] {
+        stats.record(result(day, 50));
+    }
+    assert_eq!(stats.streak(date("2026-01-03"), 4, 4), 2);
+    assert_eq!(stats.streak(date("2025-12-31"), 4, 4), 2);
+    assert_eq!(stats.streak(date("2026-01-01"), 4, 4), 0);
+    assert_eq!(stats.streak(date("2026-01-03"), 3, 3), 0);
+}
+
+#[test]
+fn share_text_sums_up_the_solve() {
+    assert_eq!(
+        daily::share_text(&result("2026-01-03", 57), 1),
+        "Slide Puzzle daily 2026-01-03 (4x4)\n57 moves in 1:23"
+    );
+    let result = DailyResult {
+        optimal: Some(48),
+        ..result("2026-01-03", 57)
+    };
+    assert_eq!(
+        daily::share_text(&result, 5),
+        "Slide Puzzle daily 2026-01-03 (4x4)\n57 moves in 1:23 (optimal 48)\nstreak: 5 days"
+    );
+}
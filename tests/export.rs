@@ -0,0 +1,40 @@
+//! Rendering a solution to GIF frames offscreen.
+
+use slide_puzzle::export;
+use slide_puzzle::{Cell, SolverKind, State, cell};
+
+#[test]
+fn a_gif_has_a_frame_per_position() {
+    let state = State::from_numbers(2, 3, &[1, 2, 3, 0, 4, 5]).unwrap();
+    let moves = SolverKind::Bfs.build(state.clone()).solve();
+    assert_eq!(moves, vec![cell!(1, 1), cell!(2, 1)]);
+    let frames = export::solution_frames(&state, &moves, 20.0);
+    assert_eq!(frames.len(), 3);
+
+    let mut bytes = vec![];
+    export::write_gif(&frames, 150, &mut bytes).unwrap();
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(&bytes[..]).unwrap();
+    assert_eq!(
+        (decoder.width() as usize, decoder.height() as usize),
+        (frames[0].width(), frames[0].height())
+    );
+    let mut decoded = vec![];
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        decoded.push((frame.delay, frame.buffer.to_vec()));
+    }
+    assert_eq!(decoded.len(), 3);
+    // the ends are held for a second, in hundredths
+    let delays = decoded.iter().map(|(delay, _)| *delay).collect::<Vec<_>>();
+    assert_eq!(delays, vec![100, 15, 100]);
+    for ((_, pixels), frame) in decoded.iter().zip(&frames) {
+        assert_eq!(&pixels[..], frame.pixels());
+    }
+}
+
+#[test]
+fn no_frames_is_an_error() {
+    assert!(export::write_gif(&[], 100, &mut vec![]).is_err());
+}
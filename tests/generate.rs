@@ -0,0 +1,87 @@
+//! Generating positions of a given difficulty.
+
+use std::collections::HashSet;
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use slide_puzzle::analysis::DistanceTable;
+use slide_puzzle::generate::{self, Difficulty, GenerateError, Target};
+use slide_puzzle::{Cell, State, cell};
+
+#[test]
+fn exact_lengths_must_be_reachable() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let puzzle = generate::generate(3, 3, Target::Length(1), &mut rng).unwrap();
+    assert_eq!(puzzle.optimal, Some(1));
+    assert!(!puzzle.state.is_finished());
+
+    // 31 moves is the hardest 3x3 position, and a solved board isn't a puzzle
+    for length in [0, 32] {
+        assert!(matches!(
+            generate::generate(3, 3, Target::Length(length), &mut rng),
+            Err(GenerateError::LengthOutOfRange { max: 31, .. })
+        ));
+    }
+}
+
+#[test]
+fn tiers_on_small_boards_are_rated_by_optimal_length() {
+    for difficulty in Difficulty::ALL {
+        let mut rng = StdRng::seed_from_u64(3);
+        let puzzle = generate::generate(3, 3, Target::Tier(difficulty), &mut rng).unwrap();
+        assert_eq!(puzzle.difficulty, difficulty);
+        assert_eq!(generate::rate(&puzzle.state), difficulty);
+        let optimal = puzzle.optimal.unwrap();
+        let table = DistanceTable::shared(3, 3).unwrap();
+        assert_eq!(table.distance(&puzzle.state), Some(optimal));
+    }
+}
+
+/// Sum of the tiles' distances to their goal cells.
+fn manhattan(state: &State) -> usize {
+    let cols = state.cols();
+    state
+        .to_numbers()
+        .iter()
+        .enumerate()
+        .filter(|&(_, &n)| n != 0)
+        .map(|(i, &n)| {
+            cell!(i % cols, i / cols).manhattan_distance(&cell!((n - 1) % cols, (n - 1) / cols))
+        })
+        .sum()
+}
+
+#[test]
+fn tiers_on_large_boards_spread_over_the_tier() {
+    for difficulty in Difficulty::ALL {
+        let mut sums = HashSet::new();
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let puzzle = generate::generate(4, 4, Target::Tier(difficulty), &mut rng).unwrap();
+            assert_eq!(puzzle.optimal, None);
+            assert_eq!(generate::rate(&puzzle.state), difficulty);
+            assert!(puzzle.state.is_solvable());
+            sums.insert(manhattan(&puzzle.state));
+        }
+        // not just the first value the walk reaches
+        assert!(sums.len() > 1, "{}: {:?}", difficulty, sums);
+    }
+}
+
+#[test]
+fn the_same_seed_gives_the_same_position() {
+    for (rows, cols, target) in [
+        (3, 3, Target::Tier(Difficulty::Hard)),
+        (3, 3, Target::Length(12)),
+        (5, 5, Target::Tier(Difficulty::Expert)),
+    ] {
+        let make = || {
+            let mut rng = StdRng::seed_from_u64(99);
+            generate::generate(rows, cols, target, &mut rng)
+                .unwrap()
+                .state
+        };
+        assert_eq!(make(), make());
+    }
+}
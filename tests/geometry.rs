@@ -0,0 +1,137 @@
+//! Cell shapes: neighbours, hit-testing and sliding on hex and triangle
+//! boards.
+
+use macroquad::math::vec2;
+use slide_puzzle::variant::Move;
+use slide_puzzle::{Board, Cell, Geometry, VariantState, cell};
+
+fn cells(rows: usize, cols: usize) -> impl Iterator<Item = Cell> {
+    (0..rows * cols).map(move |i| cell!(i, rows, cols))
+}
+
+#[test]
+fn neighbors_are_symmetric_and_share_an_edge() {
+    for geometry in Geometry::ALL {
+        for cell in cells(4, 5) {
+            for next in geometry.neighbors(cell, 4, 5) {
+                assert!(
+                    geometry.neighbors(next, 4, 5).contains(&cell),
+                    "{} {:?} {:?}",
+                    geometry.name(),
+                    cell,
+                    next
+                );
+                let shared = geometry
+                    .corners(cell)
+                    .iter()
+                    .filter(|a| geometry.corners(next).iter().any(|b| a.distance(*b) < 1e-4))
+                    .count();
+                assert_eq!(shared, 2, "{} {:?} {:?}", geometry.name(), cell, next);
+            }
+        }
+    }
+}
+
+#[test]
+fn interior_cells_have_a_full_set_of_neighbors() {
+    assert_eq!(Geometry::Square.neighbors(cell!(1, 1), 3, 3).len(), 4);
+    assert_eq!(Geometry::Hex.neighbors(cell!(1, 1), 3, 3).len(), 6);
+    assert_eq!(Geometry::Hex.neighbors(cell!(1, 2), 4, 3).len(), 6);
+    assert_eq!(Geometry::Triangle.neighbors(cell!(1, 1), 3, 3).len(), 3);
+    assert_eq!(Geometry::Triangle.neighbors(cell!(0, 0), 3, 3).len(), 2);
+}
+
+#[test]
+fn cells_are_hit_at_their_centers_and_fit_the_board() {
+    for geometry in Geometry::ALL {
+        let extent = geometry.extent(4, 5);
+        for cell in cells(4, 5) {
+            let center = geometry.center(cell);
+            assert_eq!(
+                geometry.cell_at(center, 4, 5),
+                Some(cell),
+                "{}",
+                geometry.name()
+            );
+            for corner in geometry.corners(cell) {
+                assert!(corner.x > -1e-4 && corner.x < extent.x + 1e-4);
+                assert!(corner.y > -1e-4 && corner.y < extent.y + 1e-4);
+            }
+        }
+        assert_eq!(geometry.cell_at(vec2(-0.1, -0.1), 4, 5), None);
+    }
+}
+
+#[test]
+fn points_on_a_shared_edge_hit_a_cell() {
+    for geometry in Geometry::ALL {
+        for cell in cells(4, 5) {
+            let corners = geometry.corners(cell);
+            for (i, &a) in corners.iter().enumerate() {
+                let b = corners[(i + 1) % corners.len()];
+                let midpoint = (a + b) / 2.0;
+                let extent = geometry.extent(4, 5);
+                let inside = midpoint.x > 1e-3
+                    && midpoint.y > 1e-3
+                    && midpoint.x < extent.x - 1e-3
+                    && midpoint.y < extent.y - 1e-3;
+                if inside && geometry.neighbors(cell, 4, 5).len() == corners.len() {
+                    assert!(
+                        geometry.cell_at(midpoint, 4, 5).is_some(),
+                        "{}",
+                        geometry.name()
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn board_hit_testing_follows_the_geometry() {
+    let mut board = Board::new(vec2(400.0, 300.0), 6.0, (4, 5));
+    for geometry in Geometry::ALL {
+        board.set_geometry(geometry);
+        for cell in cells(4, 5) {
+            let center = board.cell_center(cell);
+            assert_eq!(board.get_cell(center), Some(cell), "{}", geometry.name());
+        }
+    }
+}
+
+#[test]
+fn hex_boards_without_walls_solve_any_swap() {
+    // a hex grid has triangles of cells, so the checkerboard parity rule
+    // doesn't apply
+    let state = VariantState::parse("2 1 3\n4 5 6\n7 8 .")
+        .unwrap()
+        .with_geometry(Geometry::Hex);
+    assert!(state.is_solvable());
+    let moves = state.solve().unwrap();
+    assert_eq!(Some(moves.len()), state.solve_bfs().map(|m| m.len()));
+    let mut end = state.clone();
+    for mv in moves {
+        assert!(end.slide(mv), "illegal move {:?}", mv);
+    }
+    assert!(end.is_finished());
+}
+
+#[test]
+fn hex_tiles_slide_diagonally() {
+    let mut state = VariantState::parse("1 2\n3 .")
+        .unwrap()
+        .with_geometry(Geometry::Hex);
+    // the odd row is shifted right, so (1, 0) touches (1, 1) but (0, 0)
+    // doesn't
+    assert!(!state.slide(Move {
+        from: cell!(0, 0),
+        to: cell!(1, 1),
+    }));
+    let mv = Move {
+        from: cell!(1, 0),
+        to: cell!(1, 1),
+    };
+    let direction = state.direction(mv);
+    assert!(direction.x > 0.0 && direction.y > 0.0);
+    assert!(state.slide(mv));
+}
@@ -0,0 +1,89 @@
+//! Dragging tiles towards the blank.
+
+use macroquad::math::{Vec2, vec2};
+use slide_puzzle::input::TAP_DISTANCE;
+use slide_puzzle::{Board, Cell, Drag, PointerEvent, State, cell, drag_board};
+
+#[test]
+fn only_tiles_in_line_with_the_blank_can_be_grabbed() {
+    let blank = cell!(1, 1);
+    assert!(Drag::new(cell!(0, 0), blank, vec2(0.0, 0.0)).is_none());
+    assert!(Drag::new(blank, blank, vec2(0.0, 0.0)).is_none());
+
+    let drag = Drag::new(cell!(1, 3), blank, vec2(0.0, 0.0)).unwrap();
+    assert_eq!(drag.grabbed(), cell!(1, 3));
+    assert_eq!(drag.cells(), &[cell!(1, 3), cell!(1, 2)]);
+}
+
+#[test]
+fn the_offset_stays_within_one_cell_towards_the_blank() {
+    let cell_size = vec2(100.0, 50.0);
+    // the blank is to the right, so only rightward travel counts
+    let mut drag = Drag::new(cell!(0, 0), cell!(2, 0), vec2(10.0, 10.0)).unwrap();
+    drag.update(vec2(40.0, 30.0), cell_size);
+    assert_eq!(drag.offset(), vec2(30.0, 0.0));
+    drag.update(vec2(500.0, 10.0), cell_size);
+    assert_eq!(drag.offset(), vec2(100.0, 0.0));
+    drag.update(vec2(-200.0, 10.0), cell_size);
+    assert_eq!(drag.offset(), vec2(0.0, 0.0));
+
+    // upwards, a cell is only 50 high
+    let mut drag = Drag::new(cell!(0, 2), cell!(0, 0), vec2(0.0, 0.0)).unwrap();
+    drag.update(vec2(0.0, -80.0), cell_size);
+    assert_eq!(drag.offset(), vec2(0.0, -50.0));
+}
+
+#[test]
+fn release_snaps_on_a_tap_or_past_half_a_cell() {
+    let cell_size = vec2(100.0, 100.0);
+    let start = vec2(0.0, 0.0);
+    let drag = |to: &[f32]| {
+        let mut drag = Drag::new(cell!(0, 0), cell!(1, 0), start).unwrap();
+        for &x in to {
+            drag.update(vec2(x, 0.0), cell_size);
+        }
+        drag.release(cell_size)
+    };
+    assert!(drag(&[]));
+    assert!(drag(&[TAP_DISTANCE - 1.0]));
+    assert!(!drag(&[TAP_DISTANCE + 1.0]));
+    assert!(!drag(&[49.0]));
+    assert!(drag(&[50.0]));
+    // dragged out and back counts as a drag, not a tap
+    assert!(!drag(&[80.0, 0.0]));
+}
+
+#[test]
+fn a_board_drag_reports_the_cell_that_snaps() {
+    // 100 pixel cells, the blank in the bottom right, the board drawn at 50,50
+    let state = State::new(2, 2);
+    let mut board = Board::new(vec2(200.0, 200.0), 0.0, (2, 2));
+    board.apply_state(&state);
+    let origin = vec2(50.0, 50.0);
+    let blank = state.blank_cell();
+    let mut drag = None;
+    let mut feed =
+        |events: &[PointerEvent]| drag_board(&mut drag, events, &mut board, origin, blank);
+
+    // tile 3, left of the blank, dragged most of the way right
+    let grab = vec2(100.0, 200.0);
+    assert_eq!(feed(&[PointerEvent::Pressed(grab)]), None);
+    assert_eq!(feed(&[PointerEvent::Moved(grab + vec2(30.0, 0.0))]), None);
+    assert_eq!(
+        feed(&[PointerEvent::Released(grab + vec2(70.0, 0.0))]),
+        Some(cell!(0, 1))
+    );
+
+    // let go before half a cell, the tile slides back
+    feed(&[PointerEvent::Pressed(grab)]);
+    assert_eq!(
+        feed(&[PointerEvent::Released(grab + vec2(30.0, 0.0))]),
+        None
+    );
+
+    // tile 1 is not in line with the blank, and outside the board is nothing
+    for pos in [vec2(100.0, 100.0), Vec2::ZERO] {
+        feed(&[PointerEvent::Pressed(pos)]);
+        assert_eq!(feed(&[PointerEvent::Released(pos)]), None);
+    }
+}
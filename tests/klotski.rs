@@ -0,0 +1,135 @@
+//! Klotski rules, the level pack format and the built-in levels.
+
+use slide_puzzle::klotski::{
+    Block, Klotski, KlotskiError, KlotskiMove, builtin_levels, parse_levels,
+};
+use slide_puzzle::{Cell, Direction, cell};
+
+fn puzzle(grid: &str) -> Klotski {
+    Klotski::parse(grid, 'A', cell!(1, 1)).unwrap()
+}
+
+#[test]
+fn blocks_are_read_in_reading_order() {
+    let puzzle = puzzle("B A A\nB A A\n. C .");
+    assert_eq!(
+        puzzle.blocks(),
+        [
+            Block {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 2
+            },
+            Block {
+                x: 1,
+                y: 0,
+                width: 2,
+                height: 2
+            },
+            Block {
+                x: 1,
+                y: 2,
+                width: 1,
+                height: 1
+            },
+        ]
+    );
+    assert_eq!(puzzle.target(), 1);
+    assert_eq!(puzzle.block_at(cell!(2, 1)), Some(1));
+    assert_eq!(puzzle.block_at(cell!(0, 2)), None);
+}
+
+#[test]
+fn blocks_only_move_into_empty_cells() {
+    let mut puzzle = puzzle("B A A\nB A A\n. C .");
+    let mv = |block, direction| KlotskiMove { block, direction };
+
+    let mut moves = puzzle.moves();
+    moves.sort_by_key(|m| (m.block, m.direction as usize));
+    assert_eq!(
+        moves,
+        [
+            mv(0, Direction::Down),
+            mv(2, Direction::Left),
+            mv(2, Direction::Right),
+        ]
+    );
+    assert!(!puzzle.apply(mv(1, Direction::Down)));
+    assert!(!puzzle.apply(mv(0, Direction::Up)));
+
+    assert!(puzzle.apply(mv(2, Direction::Right)));
+    assert!(puzzle.apply(mv(0, Direction::Down)));
+    assert!(!puzzle.is_solved());
+    assert!(!puzzle.apply(mv(1, Direction::Down)), "C is still below A");
+}
+
+#[test]
+fn solver_finds_a_shortest_solution() {
+    let puzzle = puzzle("A A .\nA A .\nB . .");
+    let moves = puzzle.solve().unwrap();
+    // right, then down; going down first would need B out of the way
+    assert_eq!(moves.len(), 2);
+
+    let mut end = puzzle.clone();
+    for mv in moves {
+        assert!(end.apply(mv), "illegal move {:?}", mv);
+    }
+    assert!(end.is_solved());
+}
+
+#[test]
+fn stuck_puzzles_have_no_solution() {
+    assert_eq!(puzzle("B A A\nB A A\nC C C").solve(), None);
+}
+
+#[test]
+fn malformed_boards_are_rejected() {
+    let parse = |grid: &str| Klotski::parse(grid, 'A', cell!(0, 0));
+    assert_eq!(
+        parse("A B\nB A").unwrap_err(),
+        KlotskiError::NotRectangle { name: 'A' }
+    );
+    assert_eq!(
+        parse("A B\n.").unwrap_err().to_string(),
+        "line 2: rows have different lengths"
+    );
+    assert_eq!(parse("B B").unwrap_err(), KlotskiError::Goal);
+    assert_eq!(
+        Klotski::parse("A A .", 'A', cell!(2, 0)).unwrap_err(),
+        KlotskiError::Goal
+    );
+}
+
+#[test]
+fn level_errors_point_into_the_file() {
+    let text =
+        "# pack\n\nlevel One\ngoal A 0,0\nA .\n\nlevel Two\ngoal A 0,0\n# comment\nA B\nB AB\n";
+    assert_eq!(
+        parse_levels(text).unwrap_err().to_string(),
+        "line 11: `AB` is not a single letter"
+    );
+    assert_eq!(
+        parse_levels("level One\nA .").unwrap_err().to_string(),
+        "line 1: level `One` has no goal"
+    );
+}
+
+#[test]
+fn builtin_levels_match_their_optimal_solutions() {
+    let levels = builtin_levels();
+    assert!(!levels.is_empty());
+    for level in levels {
+        let puzzle = &level.puzzle;
+        assert_eq!((puzzle.rows(), puzzle.cols()), (5, 4), "{}", level.name);
+        assert!(!puzzle.is_solved(), "{}", level.name);
+
+        let moves = puzzle.solve().unwrap();
+        assert_eq!(Some(moves.len()), level.optimal, "{}", level.name);
+        let mut end = puzzle.clone();
+        for mv in moves {
+            assert!(end.apply(mv), "{}: illegal move {:?}", level.name, mv);
+        }
+        assert!(end.is_solved(), "{}", level.name);
+    }
+}
@@ -0,0 +1,53 @@
+//! Splitting the window into header, board and side panels.
+
+use macroquad::math::{Rect, Vec2, vec2};
+use slide_puzzle::{Layout, LayoutConfig};
+
+fn contains(outer: Rect, inner: Rect) -> bool {
+    inner.x >= outer.x
+        && inner.y >= outer.y
+        && inner.right() <= outer.right() + 1e-3
+        && inner.bottom() <= outer.bottom() + 1e-3
+}
+
+#[test]
+fn panels_are_dropped_when_the_board_gets_too_small() {
+    let config = LayoutConfig::default();
+    let wide = Layout::compute(vec2(1920.0, 1080.0), 4, 4, &config);
+    assert_eq!(wide.left_panel.w, config.side_panel_width);
+    assert_eq!(wide.right_panel.w, config.side_panel_width);
+    assert!(wide.left_panel.right() <= wide.board.x);
+    assert!(wide.board.right() <= wide.right_panel.x);
+
+    // with panels the board would be 240 wide, below the 400 minimum
+    let narrow = Layout::compute(vec2(880.0, 1080.0), 4, 4, &config);
+    assert_eq!(narrow.left_panel.w, 0.0);
+    assert_eq!(narrow.right_panel.w, 0.0);
+    assert_eq!(narrow.board.w, 840.0);
+}
+
+#[test]
+fn non_square_boards_are_centred_with_square_cells() {
+    let config = LayoutConfig::default();
+    let screen = vec2(1000.0, 800.0);
+    for (rows, cols) in [(2, 5), (5, 2), (1, 10), (3, 3)] {
+        let layout = Layout::compute(screen, rows, cols, &config);
+        let board = layout.board;
+        assert!((board.w / cols as f32 - board.h / rows as f32).abs() < 1e-3);
+        assert!(contains(Rect::new(0.0, 0.0, screen.x, screen.y), board));
+        assert!(board.y >= layout.header.bottom());
+
+        // the same space is left on both sides of the longer-fitting axis
+        let left = board.x - layout.left_panel.right();
+        let right = layout.right_panel.x - board.right();
+        assert!((left - right).abs() < 1e-3, "{}x{}", rows, cols);
+    }
+}
+
+#[test]
+fn a_zero_sized_screen_gets_an_empty_board() {
+    let layout = Layout::compute(Vec2::ZERO, 4, 4, &LayoutConfig::default());
+    assert_eq!(layout.board.size(), Vec2::ZERO);
+    assert_eq!(layout.left_panel.w, 0.0);
+    assert!(layout.board.x.is_finite() && layout.board.y.is_finite());
+}
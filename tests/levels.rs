@@ -0,0 +1,192 @@
+//! The level pack format, reachability of goals and saved progress.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use slide_puzzle::levels::{Completion, LevelError, LevelPack, Progress, can_reach};
+use slide_puzzle::{Cell, State, cell};
+
+const PACK: &str = "\
+# two levels
+level One
+size 2x2
+par 1
+1 2
+. 3
+
+level Two
+size 2x3
+1 2 3
+4 5 .
+goal
+. 1 2
+4 5 3
+";
+
+fn state(rows: usize, cols: usize, numbers: &[usize]) -> State {
+    State::from_numbers(rows, cols, numbers).unwrap()
+}
+
+/// Distance to every position reachable from `start`, by breadth-first
+/// search.
+fn distances(start: &State) -> HashMap<Vec<usize>, usize> {
+    let mut seen = HashMap::from([(start.to_numbers(), 0)]);
+    let mut queue = VecDeque::from([(start.clone(), 0)]);
+    while let Some((state, distance)) = queue.pop_front() {
+        let blank = state.blank_cell();
+        for i in 0..state.pieces().len() {
+            let cell = cell!(i, state.rows(), state.cols());
+            if cell.manhattan_distance(&blank) != 1 {
+                continue;
+            }
+            let mut next = state.clone();
+            next.swap(blank, cell);
+            if let Entry::Vacant(entry) = seen.entry(next.to_numbers()) {
+                entry.insert(distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    seen
+}
+
+#[test]
+fn levels_have_a_size_par_start_and_goal() {
+    let pack = LevelPack::parse("test", PACK).unwrap();
+    assert_eq!(pack.name, "test");
+    assert_eq!(pack.levels.len(), 2);
+
+    let one = &pack.levels[0];
+    assert_eq!(one.name, "One");
+    assert_eq!(one.par, Some(1));
+    assert_eq!(one.start, state(2, 2, &[1, 2, 0, 3]));
+    assert_eq!(one.goal, State::new(2, 2));
+
+    let two = &pack.levels[1];
+    assert_eq!(two.par, None);
+    assert_eq!(two.start, State::new(2, 3));
+    assert_eq!(two.goal, state(2, 3, &[0, 1, 2, 4, 5, 3]));
+}
+
+#[test]
+fn errors_point_at_the_line() {
+    let syntax_line = |text: &str| match LevelPack::parse("test", text) {
+        Err(LevelError::Syntax { line, .. }) => line,
+        other => panic!("expected a syntax error, got {:?}", other),
+    };
+    assert_eq!(syntax_line("size 2x2"), 1);
+    assert_eq!(syntax_line("level A\nsize 2by2\n1 2\n3 ."), 2);
+    assert_eq!(syntax_line("level A\n1 2\n3 ."), 1);
+    assert_eq!(syntax_line("level A\nsize 2x2\n1 2\n3 x"), 4);
+    assert_eq!(syntax_line("level A\nsize 2x2\n1 2 3\n."), 3);
+    assert_eq!(syntax_line("level A\nsize 2x2\n1 2"), 3);
+    assert_eq!(syntax_line("level A\nsize 2x2\n1 1\n3 ."), 3);
+    // already solved
+    assert_eq!(syntax_line("level A\nsize 2x2\n1 2\n3 ."), 1);
+
+    assert_eq!(
+        LevelPack::parse("test", "level A\nsize 2x2\n2 1\n3 ."),
+        Err(LevelError::Unreachable {
+            name: "A".to_string()
+        })
+    );
+    assert_eq!(
+        LevelPack::parse("test", "# nothing\n"),
+        Err(LevelError::Empty)
+    );
+}
+
+#[test]
+fn can_reach_agrees_with_a_search() {
+    for (rows, cols) in [(2, 3), (3, 2), (1, 4)] {
+        let start = State::new(rows, cols);
+        let found = distances(&start);
+        // every arrangement of the tiles, found or not
+        let mut numbers = (0..rows * cols).collect::<Vec<_>>();
+        let mut count = 0;
+        loop {
+            let goal = state(rows, cols, &numbers);
+            assert_eq!(
+                can_reach(&start, &goal),
+                found.contains_key(&numbers),
+                "{:?}",
+                numbers
+            );
+            count += 1;
+            if !next_permutation(&mut numbers) {
+                break;
+            }
+        }
+        assert_eq!(count, (1..=rows * cols).product::<usize>());
+    }
+}
+
+fn next_permutation(v: &mut [usize]) -> bool {
+    let Some(i) = (1..v.len()).rev().find(|&i| v[i - 1] < v[i]) else {
+        return false;
+    };
+    let j = (i..v.len()).rev().find(|&j| v[j] > v[i - 1]).unwrap();
+    v.swap(i - 1, j);
+    v[i..].reverse();
+    true
+}
+
+#[test]
+fn builtin_par_is_the_shortest_solution() {
+    let pack = LevelPack::builtin();
+    let names = pack.levels.iter().map(|l| &l.name).collect::<HashSet<_>>();
+    assert_eq!(names.len(), pack.levels.len());
+
+    // several levels share a goal
+    let mut searched = HashMap::new();
+    for level in pack.levels.iter().filter(|l| l.start.pieces().len() <= 9) {
+        let from_goal = searched
+            .entry(level.goal.to_numbers())
+            .or_insert_with(|| distances(&level.goal));
+        let distance = from_goal[&level.start.to_numbers()];
+        assert_eq!(level.par, Some(distance), "{}", level.name);
+    }
+}
+
+#[test]
+fn progress_keeps_the_best_result_and_unlocks_the_next_level() {
+    let pack = LevelPack::parse("test", PACK).unwrap();
+    let mut progress = Progress::default();
+    assert!(progress.is_unlocked(&pack, 0));
+    assert!(!progress.is_unlocked(&pack, 1));
+
+    let completion = |moves| Completion {
+        pack: "test".to_string(),
+        level: "One".to_string(),
+        moves,
+        seconds: 3.0,
+    };
+    assert!(progress.record(completion(5)));
+    assert!(!progress.record(completion(7)));
+    assert!(progress.record(completion(1)));
+    assert_eq!(progress.get("test", "One").unwrap().moves, 1);
+    assert!(progress.is_unlocked(&pack, 1));
+
+    // the same level name in another pack is a different level
+    let other = LevelPack::parse("other", PACK).unwrap();
+    assert!(!progress.is_unlocked(&other, 1));
+}
+
+#[test]
+fn progress_is_saved_and_loaded() {
+    let dir = std::env::temp_dir().join(format!("slide-puzzle-levels-{}", std::process::id()));
+    let path = dir.join("levels.json");
+    assert!(Progress::load(&path).unwrap().completed.is_empty());
+
+    let mut progress = Progress::default();
+    progress.record(Completion {
+        pack: "builtin".to_string(),
+        level: "First Slide".to_string(),
+        moves: 1,
+        seconds: 0.5,
+    });
+    progress.save(&path).unwrap();
+    let loaded = Progress::load(&path).unwrap();
+    assert_eq!(loaded.completed, progress.completed);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
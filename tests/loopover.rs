@@ -0,0 +1,160 @@
+//! Loopover rules and solver, and wrap-around boards.
+
+use std::collections::{HashSet, VecDeque};
+
+use macroquad::math::vec2;
+use proptest::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use slide_puzzle::loopover::{Line, Loopover, Rotation};
+use slide_puzzle::variant::{Move, Topology};
+use slide_puzzle::{Cell, VariantState, cell};
+
+/// Every arrangement reachable from the solved board.
+fn reachable(rows: usize, cols: usize) -> HashSet<Vec<usize>> {
+    let start = Loopover::new(rows, cols).unwrap();
+    let mut seen = HashSet::from([start.tiles().to_vec()]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(board) = queue.pop_front() {
+        for rotation in board.rotations() {
+            let mut next = board.clone();
+            next.rotate(rotation);
+            if seen.insert(next.tiles().to_vec()) {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen
+}
+
+/// All arrangements of `1..=n`.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    let mut all = vec![];
+    for rest in permutations(n - 1) {
+        for i in 0..=rest.len() {
+            let mut p = rest.clone();
+            p.insert(i, n);
+            all.push(p);
+        }
+    }
+    all
+}
+
+#[test]
+fn rotations_wrap_around() {
+    let mut board = Loopover::new(2, 3).unwrap();
+    board.rotate(Rotation {
+        line: Line::Row(0),
+        forward: true,
+    });
+    assert_eq!(board.tiles(), [3, 1, 2, 4, 5, 6]);
+    board.rotate(Rotation {
+        line: Line::Col(0),
+        forward: false,
+    });
+    assert_eq!(board.tiles(), [4, 1, 2, 3, 5, 6]);
+}
+
+#[test]
+fn solvability_matches_reachability() {
+    for (rows, cols) in [(1, 5), (5, 1), (2, 2), (2, 3), (3, 2), (1, 2)] {
+        let reachable = reachable(rows, cols);
+        for tiles in permutations(rows * cols) {
+            let board = Loopover::from_numbers(rows, cols, &tiles).unwrap();
+            assert_eq!(
+                board.is_solvable(),
+                reachable.contains(&tiles),
+                "{}x{} {:?}",
+                rows,
+                cols,
+                tiles
+            );
+        }
+    }
+}
+
+#[test]
+fn odd_boards_only_reach_even_permutations() {
+    let mut tiles = (1..=9).collect::<Vec<_>>();
+    tiles.swap(0, 1);
+    assert!(!Loopover::from_numbers(3, 3, &tiles).unwrap().is_solvable());
+    tiles.swap(1, 2);
+    assert!(Loopover::from_numbers(3, 3, &tiles).unwrap().is_solvable());
+    assert!(Loopover::from_numbers(3, 3, &[1, 1, 3, 4, 5, 6, 7, 8, 9]).is_err());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn loopover_solver_reaches_the_goal(rows in 1..=6usize, cols in 1..=6usize, seed: u64) {
+        prop_assume!(rows * cols >= 2);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tiles = (1..=rows * cols).collect::<Vec<_>>();
+        tiles.shuffle(&mut rng);
+        let board = Loopover::from_numbers(rows, cols, &tiles).unwrap();
+
+        match board.solve() {
+            Some(moves) => {
+                let mut end = board.clone();
+                for rotation in moves {
+                    end.rotate(rotation);
+                }
+                prop_assert!(end.is_finished());
+            }
+            None => prop_assert!(!board.is_solvable()),
+        }
+    }
+}
+
+#[test]
+fn torus_neighbors_wrap() {
+    let state = VariantState::goal(3, 3, &[], 1)
+        .unwrap()
+        .with_topology(Topology::Torus);
+    let mut neighbors = state.open_neighbors(cell!(0, 0));
+    neighbors.sort();
+    assert_eq!(
+        neighbors,
+        [cell!(0, 1), cell!(0, 2), cell!(1, 0), cell!(2, 0)]
+    );
+
+    // the blank is in the bottom-right corner; tile 7 slides left across the
+    // edge into it
+    let mut state = state;
+    let mv = Move {
+        from: cell!(0, 2),
+        to: cell!(2, 2),
+    };
+    assert_eq!(state.direction(mv), vec2(-1.0, 0.0));
+    assert!(state.slide(mv));
+    assert_eq!(state.square(cell!(2, 2)), slide_puzzle::Square::Tile(7));
+}
+
+#[test]
+fn odd_tori_are_always_solvable() {
+    // swapping two tiles is unsolvable on a flat 3x3 but solvable when the
+    // wrap makes the board impossible to color like a checkerboard
+    let text = "2 1 3\n4 5 6\n7 8 .";
+    let flat = VariantState::parse(text).unwrap();
+    assert!(!flat.is_solvable());
+    let torus = flat.with_topology(Topology::Torus);
+    assert!(torus.is_solvable());
+    let moves = torus.solve().unwrap();
+    let mut end = torus.clone();
+    for mv in moves {
+        assert!(end.slide(mv));
+    }
+    assert!(end.is_finished());
+
+    // with both sides even the checkerboard survives the wrap
+    let even = VariantState::parse("2 1 3 4\n5 6 7 8\n9 10 11 12\n13 14 15 .")
+        .unwrap()
+        .with_topology(Topology::Torus);
+    assert!(!even.is_solvable());
+}
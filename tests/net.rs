@@ -0,0 +1,165 @@
+//! Races over a local TCP connection.
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use slide_puzzle::net::{self, Connection, Host, Message, NetError, RaceStart};
+use slide_puzzle::{Cell, State, cell};
+
+fn start() -> RaceStart {
+    RaceStart {
+        state: State::from_numbers(3, 3, &[1, 2, 3, 4, 5, 6, 7, 0, 8]).unwrap(),
+        seed: 42,
+    }
+}
+
+/// A host and a player joined to it on localhost.
+fn connect() -> (Connection, Connection, RaceStart) {
+    let host = Host::bind("127.0.0.1:0").unwrap();
+    let addr = host.local_addr().unwrap();
+    let accepting = thread::spawn(move || host.accept(&start()).unwrap());
+    let (joined, received) = net::join(addr).unwrap();
+    (accepting.join().unwrap(), joined, received)
+}
+
+/// Polls until something arrives, or gives up after a few seconds.
+fn receive(connection: &mut Connection) -> Vec<Message> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while Instant::now() < deadline {
+        let messages = connection.poll().unwrap();
+        if !messages.is_empty() {
+            return messages;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("nothing arrived");
+}
+
+#[test]
+fn joining_receives_the_hosts_position() {
+    let (_, _, received) = connect();
+    assert_eq!(received, start());
+}
+
+#[test]
+fn slides_go_both_ways() {
+    let (mut host, mut player, _) = connect();
+    assert_eq!(host.poll().unwrap(), vec![]);
+
+    host.send(&Message::Slide {
+        cell: cell!(2, 2),
+        ms: 150,
+    })
+    .unwrap();
+    assert_eq!(
+        receive(&mut player),
+        vec![Message::Slide {
+            cell: cell!(2, 2),
+            ms: 150
+        }]
+    );
+
+    player
+        .send(&Message::Slide {
+            cell: cell!(0, 2),
+            ms: 300,
+        })
+        .unwrap();
+    player.send(&Message::Leave).unwrap();
+    let mut messages = receive(&mut host);
+    while messages.len() < 2 {
+        messages.extend(receive(&mut host));
+    }
+    assert_eq!(
+        messages,
+        vec![
+            Message::Slide {
+                cell: cell!(0, 2),
+                ms: 300
+            },
+            Message::Leave
+        ]
+    );
+}
+
+#[test]
+fn a_closed_connection_leaves_once() {
+    let (mut host, player, _) = connect();
+    drop(player);
+    assert_eq!(receive(&mut host), vec![Message::Leave]);
+    assert_eq!(host.poll().unwrap(), vec![]);
+}
+
+#[test]
+fn sends_the_socket_cant_take_yet_are_queued() {
+    let (mut host, mut player, _) = connect();
+    // far more than the socket buffers hold while nobody reads
+    let count = 200_000;
+    for ms in 0..count {
+        host.send(&Message::Slide {
+            cell: cell!(2, 2),
+            ms,
+        })
+        .unwrap();
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let mut received = vec![];
+    while (received.len() as u64) < count {
+        assert!(Instant::now() < deadline, "only {} arrived", received.len());
+        assert_eq!(host.poll().unwrap(), vec![]);
+        received.extend(player.poll().unwrap());
+    }
+    let expected = (0..count)
+        .map(|ms| Message::Slide {
+            cell: cell!(2, 2),
+            ms,
+        })
+        .collect::<Vec<_>>();
+    assert!(received == expected);
+}
+
+/// A host that answers a join with `line`.
+fn join_raw_host(line: &'static str) -> Result<(Connection, RaceStart), NetError> {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let host = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.write_all(line.as_bytes()).unwrap();
+    });
+    let joined = net::join(addr);
+    host.join().unwrap();
+    joined
+}
+
+#[test]
+fn joining_a_host_that_speaks_another_protocol_fails() {
+    let other_version = "{\"type\":\"start\",\"version\":99,\"rows\":2,\"cols\":2,\
+                         \"seed\":0,\"tiles\":[1,2,3,0]}\n";
+    assert!(matches!(
+        join_raw_host(other_version),
+        Err(NetError::Version(99))
+    ));
+    assert!(matches!(
+        join_raw_host("{\"type\":\"leave\"}\n"),
+        Err(NetError::Unexpected(Message::Leave))
+    ));
+    assert!(matches!(join_raw_host("hello\n"), Err(NetError::Format(_))));
+    assert!(matches!(join_raw_host(""), Err(NetError::Closed)));
+    // a line that never ends isn't buffered forever
+    let endless = "x".repeat(5000).leak();
+    assert!(matches!(join_raw_host(endless), Err(NetError::TooLong)));
+}
+
+#[test]
+fn messages_are_json_lines() {
+    let slide = Message::Slide {
+        cell: cell!(1, 0),
+        ms: 7,
+    };
+    let json = serde_json::to_string(&slide).unwrap();
+    assert!(json.contains("\"type\":\"slide\""), "{}", json);
+    assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), slide);
+}
@@ -0,0 +1,276 @@
+//! Property tests for `State` invariants, the solvers and the variant boards
+//! on every topology and geometry.
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+use slide_puzzle::analysis::DistanceTable;
+use slide_puzzle::variant::Topology;
+use slide_puzzle::{
+    Cell, Geometry, MAX_BOARD_SIZE, MySolver, SolverKind, Square, State, VariantState, cell,
+};
+
+/// Any playable board size.
+fn any_size() -> impl Strategy<Value = (usize, usize)> {
+    (1..=MAX_BOARD_SIZE, 1..=MAX_BOARD_SIZE).prop_filter("needs two cells", |(r, c)| r * c >= 2)
+}
+
+/// Board sizes every solver finishes quickly on, including line boards.
+fn small_size() -> impl Strategy<Value = (usize, usize)> {
+    prop_oneof![
+        (1..=3usize, 1..=3usize),
+        (Just(1), 2..=10usize),
+        (2..=10usize, Just(1)),
+        Just((2, 4)),
+        Just((4, 2)),
+    ]
+    .prop_filter("needs two cells", |(r, c)| r * c >= 2)
+}
+
+fn shuffled(rows: usize, cols: usize, seed: u64) -> State {
+    let mut state = State::new(rows, cols);
+    state.shuffle_with(&mut StdRng::seed_from_u64(seed));
+    state
+}
+
+fn blank_neighbors(state: &State) -> Vec<Cell> {
+    let blank = state.blank_cell();
+    let mut cells = vec![];
+    if blank.x > 0 {
+        cells.push(cell!(blank.x - 1, blank.y));
+    }
+    if blank.y > 0 {
+        cells.push(cell!(blank.x, blank.y - 1));
+    }
+    if blank.x + 1 < state.cols() {
+        cells.push(cell!(blank.x + 1, blank.y));
+    }
+    if blank.y + 1 < state.rows() {
+        cells.push(cell!(blank.x, blank.y + 1));
+    }
+    cells
+}
+
+/// Applies `moves`, failing on the first illegal one.
+fn apply(state: &State, moves: &[Cell]) -> Result<State, TestCaseError> {
+    let mut state = state.clone();
+    for (i, &mv) in moves.iter().enumerate() {
+        prop_assert!(
+            state.try_move(mv),
+            "move {} to {:?} is illegal in {:?}",
+            i,
+            mv,
+            state.to_numbers()
+        );
+    }
+    Ok(state)
+}
+
+proptest! {
+    #[test]
+    fn cell_index_round_trips(rows in 1..=MAX_BOARD_SIZE, cols in 1..=MAX_BOARD_SIZE, index: Index) {
+        let idx = index.index(rows * cols);
+        let cell = cell!(idx, rows, cols);
+        prop_assert!(cell.x < cols && cell.y < rows);
+        prop_assert_eq!(cell.as_index(rows, cols), idx);
+        prop_assert_eq!(cell!(cell.x, cell.y), cell);
+    }
+
+    #[test]
+    fn swap_keeps_exactly_one_blank((rows, cols) in any_size(), seed: u64, pick: Index) {
+        let mut state = shuffled(rows, cols, seed);
+        let neighbors = blank_neighbors(&state);
+        let next = neighbors[pick.index(neighbors.len())];
+        let moved = state.pieces()[next.as_index(rows, cols)];
+        let blank = state.blank_cell();
+
+        state.swap(blank, next);
+
+        prop_assert_eq!(state.pieces().iter().filter(|p| p.is_none()).count(), 1);
+        prop_assert_eq!(state.blank_cell(), next);
+        prop_assert!(state.pieces()[next.as_index(rows, cols)].is_none());
+        prop_assert_eq!(state.pieces()[blank.as_index(rows, cols)], moved);
+
+        let mut numbers = state.to_numbers();
+        numbers.sort();
+        prop_assert_eq!(numbers, (0..rows * cols).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn shuffled_states_are_solvable((rows, cols) in any_size(), seed: u64) {
+        prop_assert!(shuffled(rows, cols, seed).is_solvable());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn solver_outputs_reach_the_goal((rows, cols) in small_size(), seed: u64) {
+        let state = shuffled(rows, cols, seed);
+        for kind in SolverKind::ALL {
+            // the reduction solver only places its first targets, see
+            // reduction_solver_places_its_targets
+            if kind == SolverKind::My || !kind.supports(rows, cols) {
+                continue;
+            }
+            let moves = kind.build(state.clone()).solve();
+            let end = apply(&state, &moves)?;
+            prop_assert!(end.is_finished(), "{} did not reach the goal", kind.name());
+        }
+    }
+
+    #[test]
+    fn reduction_solver_makes_legal_moves((rows, cols) in small_size(), seed: u64) {
+        let state = shuffled(rows, cols, seed);
+        let moves = SolverKind::My.build(state.clone()).solve();
+        apply(&state, &moves)?;
+    }
+
+    #[test]
+    fn reduction_solver_places_its_targets((rows, cols) in any_size(), seed: u64) {
+        prop_assume!(rows >= 3 && cols >= 3);
+        let state = shuffled(rows, cols, seed);
+        let moves = SolverKind::My.build(state.clone()).solve();
+        let end = apply(&state, &moves)?.to_numbers();
+        // it may stop at the tile that completes the top row, when the
+        // placed tiles leave the blank no way around it, but not before
+        for x in 0..4.min(cols - 1) {
+            prop_assert_eq!(end[x], x + 1, "{:?}", end);
+        }
+    }
+
+    #[test]
+    fn reduction_plan_starts_the_solution((rows, cols) in any_size(), seed: u64) {
+        let state = shuffled(rows, cols, seed);
+        let plan = MySolver::new(state.clone()).plan();
+        for (i, &locked) in plan.locked.iter().enumerate() {
+            if locked {
+                prop_assert_eq!(state.to_numbers()[i], i + 1);
+            }
+        }
+        if let [tile, ..] = plan.tile_path[..] {
+            prop_assert!(!plan.locked[tile.as_index(rows, cols)]);
+        }
+        // the blank's path is where the solver sends it first
+        if let [blank, ref route @ ..] = plan.blank_path[..] {
+            prop_assert_eq!(blank, state.blank_cell());
+            prop_assert_eq!(plan.blank_path.last(), plan.tile_path.get(1));
+            let moves = SolverKind::My.build(state.clone()).solve();
+            prop_assert!(moves.starts_with(route), "{:?} vs {:?}", route, moves);
+        }
+    }
+
+    #[test]
+    fn search_solvers_take_any_size((rows, cols) in any_size(), picks in prop::collection::vec(any::<Index>(), 0..4)) {
+        // a few moves from solved, so the search stays short on large boards
+        let mut state = State::new(rows, cols);
+        for pick in picks {
+            let neighbors = blank_neighbors(&state);
+            state.try_move(neighbors[pick.index(neighbors.len())]);
+        }
+        for kind in [SolverKind::AStar, SolverKind::Bfs] {
+            let moves = kind.build(state.clone()).solve();
+            prop_assert!(apply(&state, &moves)?.is_finished(), "{}", kind.name());
+        }
+    }
+
+    #[test]
+    fn optimal_solvers_agree((rows, cols) in small_size(), seed: u64) {
+        let state = shuffled(rows, cols, seed);
+        let optimal = DistanceTable::shared(rows, cols).unwrap().distance(&state);
+        prop_assert!(optimal.is_some());
+        for kind in [SolverKind::Bfs, SolverKind::Oracle] {
+            let moves = kind.build(state.clone()).solve();
+            prop_assert_eq!(Some(moves.len()), optimal, "{}", kind.name());
+        }
+    }
+}
+
+/// Writes `squares` in the format read by `VariantState::parse`.
+fn variant_text(squares: &[Square], cols: usize) -> String {
+    let word = |square: &Square| match square {
+        Square::Tile(n) => n.to_string(),
+        Square::Blank => ".".to_string(),
+        Square::Wall => "#".to_string(),
+    };
+    squares
+        .chunks(cols)
+        .map(|row| row.iter().map(word).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A small board with up to two walls, one to three blanks and the tiles in a
+/// random arrangement (solvable or not): square (flat or wrapping), hex or
+/// triangle cells.
+fn any_variant() -> impl Strategy<Value = VariantState> {
+    let size = prop_oneof![Just((2, 2)), Just((2, 3)), Just((3, 2))];
+    let shape = prop_oneof![
+        Just((Geometry::Square, Topology::Flat)),
+        Just((Geometry::Square, Topology::Torus)),
+        Just((Geometry::Hex, Topology::Flat)),
+        Just((Geometry::Triangle, Topology::Flat)),
+    ];
+    (size, 0..=2usize, 1..=3usize, shape, any::<u64>()).prop_filter_map(
+        "walls leave room for the blanks",
+        |((rows, cols), wall_count, blanks, (geometry, topology), seed)| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut cells = (0..rows * cols)
+                .map(|i| cell!(i, rows, cols))
+                .collect::<Vec<_>>();
+            cells.shuffle(&mut rng);
+            let goal = VariantState::goal(rows, cols, &cells[..wall_count], blanks).ok()?;
+
+            // shuffle everything but the walls
+            let mut squares = goal.squares().to_vec();
+            let mut free = squares
+                .iter()
+                .copied()
+                .filter(|&s| s != Square::Wall)
+                .collect::<Vec<_>>();
+            free.shuffle(&mut rng);
+            let mut free = free.into_iter();
+            for square in squares.iter_mut().filter(|s| **s != Square::Wall) {
+                *square = free.next().unwrap();
+            }
+            VariantState::parse(&variant_text(&squares, cols))
+                .ok()
+                .map(|state| state.with_topology(topology).with_geometry(geometry))
+        },
+    )
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn variant_solvability_matches_search(state in any_variant()) {
+        let solution = state.solve_bfs();
+        if solution.is_some() {
+            prop_assert!(state.is_solvable());
+        }
+        if state.walls().is_empty() {
+            prop_assert_eq!(state.is_solvable(), solution.is_some());
+        }
+    }
+
+    #[test]
+    fn variant_solvers_reach_the_goal(state in any_variant(), seed: u64) {
+        let mut state = state.solved();
+        state.shuffle_with(&mut StdRng::seed_from_u64(seed));
+        prop_assert!(state.is_solvable());
+
+        let astar = state.solve().unwrap();
+        let bfs = state.solve_bfs().unwrap();
+        prop_assert_eq!(astar.len(), bfs.len());
+        let mut end = state.clone();
+        for mv in astar {
+            prop_assert!(end.slide(mv), "illegal move {:?}", mv);
+        }
+        prop_assert!(end.is_finished());
+    }
+}
@@ -0,0 +1,89 @@
+//! Two players racing on the same position.
+
+use slide_puzzle::race::{Outcome, Race};
+use slide_puzzle::{Cell, Direction, State, cell};
+
+/// One slide from solved: the blank is left of the last tile.
+fn almost_solved() -> State {
+    State::from_numbers(2, 2, &[1, 2, 0, 3]).unwrap()
+}
+
+#[test]
+fn both_players_start_from_the_same_position() {
+    let start = State::from_numbers(3, 3, &[0, 1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+    let mut race = Race::new(start.clone());
+    assert_eq!(race.racer(0).state, start);
+    assert_eq!(race.racer(1).state, start);
+
+    // a slide of two tiles counts two moves, on one board only
+    assert_eq!(race.slide(1, cell!(2, 0), 100), 2);
+    assert_eq!(race.racer(1).moves, 2);
+    assert_eq!(race.racer(0).moves, 0);
+    assert_eq!(race.racer(0).state, start);
+
+    // tiles out of line with the blank don't move
+    assert_eq!(race.slide(0, cell!(1, 1), 200), 0);
+    assert_eq!(race.outcome(), None);
+
+    race.restart();
+    assert_eq!(race.racer(1).state, start);
+    assert_eq!(race.racer(1).moves, 0);
+}
+
+#[test]
+fn directions_push_the_tile_next_to_the_blank() {
+    let mut race = Race::new(almost_solved());
+    // nothing is below or left of the blank to push up or right
+    assert!(!race.push(0, Direction::Up, 0));
+    assert!(!race.push(0, Direction::Right, 0));
+    assert!(race.push(0, Direction::Down, 0));
+    assert_eq!(race.racer(0).state.blank_cell(), cell!(0, 0));
+    assert!(race.push(0, Direction::Up, 0));
+    assert!(race.push(0, Direction::Left, 0));
+    assert!(race.racer(0).state.is_finished());
+}
+
+#[test]
+fn the_first_to_solve_wins_and_the_race_stops() {
+    let mut race = Race::new(almost_solved());
+    race.slide(0, cell!(0, 0), 500);
+    assert_eq!(race.slide(1, cell!(1, 1), 1200), 1);
+    assert_eq!(race.outcome(), Some(Outcome::Winner(1)));
+    assert_eq!(race.racer(1).finished_ms, Some(1200));
+
+    // the other board takes no more moves, and the winner can't move either
+    assert_eq!(race.slide(0, cell!(0, 1), 1300), 0);
+    assert_eq!(race.slide(1, cell!(0, 1), 1300), 0);
+    assert_eq!(race.outcome(), Some(Outcome::Winner(1)));
+}
+
+#[test]
+fn a_tie_in_time_goes_to_fewer_moves() {
+    let solve = |race: &mut Race, player: usize, cells: &[Cell]| {
+        for &cell in cells {
+            race.slide(player, cell, 900);
+        }
+    };
+    let mut race = Race::new(almost_solved());
+    solve(&mut race, 0, &[cell!(1, 1)]);
+    // solved in the same frame, after a detour
+    solve(&mut race, 1, &[cell!(0, 0), cell!(0, 1), cell!(1, 1)]);
+    assert_eq!(race.racer(1).moves, 3);
+    assert_eq!(race.outcome(), Some(Outcome::Winner(0)));
+
+    let mut race = Race::new(almost_solved());
+    solve(&mut race, 1, &[cell!(1, 1)]);
+    solve(&mut race, 0, &[cell!(1, 1)]);
+    assert_eq!(race.outcome(), Some(Outcome::Draw));
+}
+
+#[test]
+fn cells_off_the_board_move_nothing() {
+    let start = State::from_numbers(3, 3, &[1, 2, 3, 4, 5, 6, 7, 0, 8]).unwrap();
+    let mut race = Race::new(start.clone());
+    // in line with the blank, but past the last row or column
+    assert_eq!(race.slide(1, cell!(1, 100), 0), 0);
+    assert_eq!(race.slide(1, cell!(5, 2), 0), 0);
+    assert_eq!(race.racer(1).state, start);
+    assert_eq!(race.racer(1).moves, 0);
+}
@@ -0,0 +1,379 @@
+//! Golden tests for `Board::draw`.
+//!
+//! Boards are drawn into a `RecordingRenderer` and the draw calls are compared
+//! against `tests/snapshots/*.txt`. Run with `UPDATE_SNAPSHOTS=1` to rewrite
+//! the expectations after an intended change, and review the diff.
+
+use std::fs;
+use std::path::PathBuf;
+
+use macroquad::color::{self, Color};
+use macroquad::math::{Vec2, vec2};
+use slide_puzzle::export;
+use slide_puzzle::klotski::Klotski;
+use slide_puzzle::{
+    Board, Cell, DrawCall, Geometry, Loopover, MySolver, RecordingRenderer, Renderer, State, Theme,
+    VariantState, cell,
+};
+
+fn color_name(c: Color) -> String {
+    let named = [
+        (color::GREEN, "GREEN"),
+        (color::BROWN, "BROWN"),
+        (color::GRAY, "GRAY"),
+        (color::DARKGRAY, "DARKGRAY"),
+        (color::BLACK, "BLACK"),
+        (color::WHITE, "WHITE"),
+        (color::RED, "RED"),
+        (color::LIGHTGRAY, "LIGHTGRAY"),
+        (color::YELLOW, "YELLOW"),
+    ];
+    match named.iter().find(|(named, _)| *named == c) {
+        Some((_, name)) => name.to_string(),
+        None => format!("{:?}", <[u8; 4]>::from(c)),
+    }
+}
+
+fn describe(call: &DrawCall) -> String {
+    match call {
+        DrawCall::Rectangle { x, y, w, h, color } => {
+            format!(
+                "rect {:.1},{:.1} {:.1}x{:.1} {}",
+                x,
+                y,
+                w,
+                h,
+                color_name(*color)
+            )
+        }
+        DrawCall::RectangleLines {
+            x,
+            y,
+            w,
+            h,
+            thickness,
+            color,
+        } => format!(
+            "rect_lines {:.1},{:.1} {:.1}x{:.1} thickness {:.1} {}",
+            x,
+            y,
+            w,
+            h,
+            thickness,
+            color_name(*color)
+        ),
+        DrawCall::Line {
+            x1,
+            y1,
+            x2,
+            y2,
+            thickness,
+            color,
+        } => format!(
+            "line {:.1},{:.1} -> {:.1},{:.1} thickness {:.1} {}",
+            x1,
+            y1,
+            x2,
+            y2,
+            thickness,
+            color_name(*color)
+        ),
+        DrawCall::Triangle { v1, v2, v3, color } => format!(
+            "triangle {:.1},{:.1} {:.1},{:.1} {:.1},{:.1} {}",
+            v1.x,
+            v1.y,
+            v2.x,
+            v2.y,
+            v3.x,
+            v3.y,
+            color_name(*color)
+        ),
+        DrawCall::Text {
+            text,
+            x,
+            y,
+            font_size,
+            color,
+        } => format!(
+            "text {:?} {:.1},{:.1} size {} {}",
+            text,
+            x,
+            y,
+            font_size,
+            color_name(*color)
+        ),
+        DrawCall::Texture {
+            texture,
+            x,
+            y,
+            w,
+            h,
+        } => {
+            format!("texture {:?} {:.1},{:.1} {:.1}x{:.1}", texture, x, y, w, h)
+        }
+    }
+}
+
+fn draw(board: &Board, pos: Vec2) -> Vec<DrawCall> {
+    let mut renderer = RecordingRenderer::new();
+    board.draw(&mut renderer, pos);
+    renderer.calls
+}
+
+fn board(state: &State, size: Vec2) -> Board {
+    let mut board = Board::new(size, 10.0, (state.rows(), state.cols()));
+    board.apply_state(state);
+    board
+}
+
+fn state(rows: usize, cols: usize, numbers: &[usize]) -> State {
+    State::from_numbers(rows, cols, numbers).unwrap()
+}
+
+fn assert_snapshot(name: &str, calls: &[DrawCall]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{}.txt", name));
+    let actual = calls.iter().map(|c| describe(c) + "\n").collect::<String>();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {} (run with UPDATE_SNAPSHOTS=1 to create it)",
+            path.display(),
+            e
+        )
+    });
+    assert!(
+        expected == actual,
+        "{} differs from the drawn board (run with UPDATE_SNAPSHOTS=1 to update)\n--- expected\n{}--- actual\n{}",
+        path.display(),
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn solved_3x3() {
+    let board = board(&State::new(3, 3), vec2(300.0, 300.0));
+    assert_snapshot("solved_3x3", &draw(&board, vec2(20.0, 80.0)));
+}
+
+#[test]
+fn hardest_3x3() {
+    let state = state(3, 3, &[8, 6, 7, 2, 5, 4, 3, 0, 1]);
+    let board = board(&state, vec2(300.0, 300.0));
+    assert_snapshot("hardest_3x3", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn line_1x4() {
+    let state = state(1, 4, &[1, 0, 2, 3]);
+    let board = board(&state, vec2(400.0, 100.0));
+    assert_snapshot("line_1x4", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn two_digit_tiles_4x4() {
+    let state = state(
+        4,
+        4,
+        &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 15, 14, 0],
+    );
+    let board = board(&state, vec2(200.0, 200.0));
+    assert_snapshot("two_digit_tiles_4x4", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn dragged_tile() {
+    let state = state(2, 2, &[1, 2, 3, 0]);
+    let mut board = board(&state, vec2(200.0, 200.0));
+    board.set_offset(cell!(0, 1), vec2(37.5, 0.0));
+    assert_snapshot("dragged_tile", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn walls_and_two_blanks() {
+    let state = VariantState::parse("4 # 3\n1 . .").unwrap();
+    let mut board = Board::new(vec2(300.0, 200.0), 10.0, (2, 3));
+    board.apply_variant(&state);
+    assert_snapshot("walls_and_two_blanks", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn klotski_blocks() {
+    let puzzle = Klotski::parse("B A A\nB A A\n. C .", 'A', cell!(1, 1)).unwrap();
+    let mut renderer = RecordingRenderer::new();
+    puzzle.draw(&mut renderer, vec2(10.0, 10.0), vec2(300.0, 300.0), Some(2));
+    assert_snapshot("klotski_blocks", &renderer.calls);
+}
+
+#[test]
+fn loopover_shows_the_wrap() {
+    let puzzle = Loopover::from_numbers(2, 2, &[2, 1, 3, 4]).unwrap();
+    let mut board = Board::new(vec2(200.0, 200.0), 10.0, (2, 2));
+    board.apply_loopover(&puzzle);
+    board.set_wrap(true);
+    assert_snapshot("loopover_wrap", &draw(&board, vec2(20.0, 20.0)));
+}
+
+#[test]
+fn hex_cells() {
+    let state = VariantState::parse("2 1\n3 .")
+        .unwrap()
+        .with_geometry(Geometry::Hex);
+    let mut board = Board::new(vec2(300.0, 200.0), 4.0, (2, 2));
+    board.set_geometry(Geometry::Hex);
+    board.apply_variant(&state);
+    assert_snapshot("hex_cells", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn triangle_cells() {
+    let state = VariantState::parse("1 # 3\n4 5 .")
+        .unwrap()
+        .with_geometry(Geometry::Triangle);
+    let mut board = Board::new(vec2(200.0, 200.0), 4.0, (2, 3));
+    board.set_geometry(Geometry::Triangle);
+    board.apply_variant(&state);
+    assert_snapshot("triangle_cells", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn framebuffer_fills_triangles() {
+    let mut frame = export::Frame::new(10, 10, color::WHITE);
+    frame.draw_triangle(vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 10.0), color::RED);
+    let red = <[u8; 4]>::from(color::RED);
+    assert_eq!(frame.pixel(1, 1), red);
+    assert_eq!(frame.pixel(8, 0), red);
+    assert_eq!(frame.pixel(8, 8), <[u8; 4]>::from(color::WHITE));
+    // the winding doesn't matter
+    frame.draw_triangle(
+        vec2(10.0, 10.0),
+        vec2(0.0, 10.0),
+        vec2(10.0, 0.0),
+        color::BLUE,
+    );
+    assert_eq!(frame.pixel(8, 8), <[u8; 4]>::from(color::BLUE));
+}
+
+#[test]
+fn tiles_are_green_only_in_their_goal_cell() {
+    let state = state(2, 3, &[1, 5, 3, 4, 2, 0]);
+    let calls = draw(&board(&state, vec2(300.0, 200.0)), Vec2::ZERO);
+    let colors = calls
+        .iter()
+        .filter_map(|call| match call {
+            DrawCall::Rectangle { color, .. } => Some(color_name(*color)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(colors, ["GREEN", "BROWN", "GREEN", "GREEN", "BROWN"]);
+}
+
+#[test]
+fn tiles_are_green_in_the_cells_of_another_goal() {
+    let mut board = board(&state(2, 2, &[0, 1, 2, 3]), vec2(200.0, 200.0));
+    board.set_goal(&state(2, 2, &[0, 1, 3, 2]));
+    let colors = draw(&board, Vec2::ZERO)
+        .iter()
+        .filter_map(|call| match call {
+            DrawCall::Rectangle { color, .. } => Some(color_name(*color)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(colors, ["GREEN", "BROWN", "BROWN"]);
+}
+
+#[test]
+fn a_theme_changes_only_the_colors() {
+    let state = state(2, 2, &[1, 0, 3, 2]);
+    let light = draw(&board(&state, vec2(200.0, 200.0)), Vec2::ZERO);
+    let mut dark = board(&state, vec2(200.0, 200.0));
+    dark.set_theme(Theme::DARK);
+    let dark = draw(&dark, Vec2::ZERO);
+
+    assert_eq!(light.len(), dark.len());
+    let fills = |calls: &[DrawCall]| {
+        calls
+            .iter()
+            .filter_map(|call| match call {
+                DrawCall::Rectangle { color, .. } => Some(*color),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(fills(&light), [color::GREEN, color::GREEN, color::BROWN]);
+    assert_eq!(
+        fills(&dark),
+        [
+            Theme::DARK.tile_in_place,
+            Theme::DARK.tile_in_place,
+            Theme::DARK.tile
+        ]
+    );
+}
+
+#[test]
+fn solve_plan_overlay() {
+    // 1 is in place; the blank moves up next to 2, which then comes left
+    let state = state(3, 3, &[1, 8, 2, 4, 0, 3, 7, 6, 5]);
+    let mut board = board(&state, vec2(300.0, 300.0));
+    board.set_plan(Some(MySolver::new(state.clone()).plan()));
+    assert_snapshot("solve_plan_overlay", &draw(&board, Vec2::ZERO));
+}
+
+#[test]
+fn font_scales_with_the_smaller_cell_side() {
+    for (size, expected) in [
+        (vec2(200.0, 200.0), 75),
+        (vec2(400.0, 100.0), 37),
+        (vec2(100.0, 400.0), 37),
+    ] {
+        let calls = draw(&board(&State::new(2, 2), size), Vec2::ZERO);
+        for call in &calls {
+            if let DrawCall::Text { font_size, .. } = call {
+                assert_eq!(*font_size, expected, "board size {:?}", size);
+            }
+        }
+    }
+}
+
+#[test]
+fn grid_lines_are_drawn_after_every_row_and_column() {
+    let calls = draw(
+        &board(&State::new(3, 4), vec2(400.0, 300.0)),
+        vec2(5.0, 5.0),
+    );
+    let lines = calls
+        .iter()
+        .filter_map(|call| match call {
+            DrawCall::Line { x1, y1, x2, y2, .. } => Some((*x1, *y1, *x2, *y2)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let vertical = lines.iter().filter(|l| l.0 == l.2).count();
+    let horizontal = lines.iter().filter(|l| l.1 == l.3).count();
+    assert_eq!((vertical, horizontal), (4, 3));
+    assert!(lines.contains(&(105.0, 5.0, 105.0, 305.0)));
+    assert!(lines.contains(&(5.0, 205.0, 405.0, 205.0)));
+}
+
+#[test]
+fn framebuffer_colors_match_tiles() {
+    let state = state(2, 2, &[1, 3, 2, 0]);
+    let frame = export::render(&state, 40.0, 4.0);
+    let at = |cell: Cell| {
+        let x = 4 + cell.x * 40 + 20;
+        let y = 4 + cell.y * 40 + 6;
+        frame.pixel(x, y)
+    };
+    assert_eq!((frame.width(), frame.height()), (88, 88));
+    assert_eq!(at(cell!(0, 0)), <[u8; 4]>::from(color::GREEN));
+    assert_eq!(at(cell!(1, 0)), <[u8; 4]>::from(color::BROWN));
+    assert_eq!(at(cell!(1, 1)), <[u8; 4]>::from(color::WHITE));
+}
@@ -0,0 +1,71 @@
+//! Recording, checking and reloading replays.
+
+use std::fs;
+
+use slide_puzzle::replay::{Replay, ReplayError};
+use slide_puzzle::{Cell, State, cell};
+
+/// One move from solved, solved after a detour.
+fn solved_replay() -> Replay {
+    let start = State::from_numbers(2, 2, &[1, 2, 0, 3]).unwrap();
+    let mut replay = Replay::new(&start, Some(7));
+    replay.push(120, cell!(1, 1));
+    replay.push(400, cell!(1, 0));
+    replay.push(900, cell!(1, 1));
+    replay.finish(true, 0.9);
+    replay
+}
+
+#[test]
+fn a_valid_replay_steps_through_its_moves() {
+    let replay = solved_replay();
+    replay.validate().unwrap();
+    assert_eq!(replay.state_at(0).unwrap().to_numbers(), vec![1, 2, 0, 3]);
+    assert_eq!(replay.state_at(1).unwrap().to_numbers(), vec![1, 2, 3, 0]);
+    assert_eq!(replay.state_at(2).unwrap().to_numbers(), vec![1, 0, 3, 2]);
+    assert!(replay.state_at(3).unwrap().is_finished());
+    // counts past the end stop at the last move
+    assert_eq!(replay.state_at(100).unwrap(), replay.state_at(3).unwrap());
+    assert_eq!(replay.result.unwrap().moves, 3);
+}
+
+#[test]
+fn an_illegal_move_is_reported_with_its_index() {
+    let mut replay = solved_replay();
+    // diagonal from the blank
+    replay.moves[1].cell = cell!(0, 0);
+    assert!(matches!(
+        replay.validate(),
+        Err(ReplayError::IllegalMove { index: 1, cell }) if cell == cell!(0, 0)
+    ));
+    // positions before the bad move are still there
+    assert!(replay.state_at(1).is_ok());
+    assert!(replay.state_at(2).is_err());
+}
+
+#[test]
+fn replays_round_trip_through_json() {
+    let replay = solved_replay();
+    let path =
+        std::env::temp_dir().join(format!("slide-puzzle-replay-{}.json", std::process::id()));
+    replay.save(&path).unwrap();
+    let loaded = Replay::load(&path);
+
+    // another version is refused when loading
+    let text = fs::read_to_string(&path)
+        .unwrap()
+        .replace("\"version\": 1", "\"version\": 2");
+    fs::write(&path, text).unwrap();
+    let other_version = Replay::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.unwrap(), replay);
+    assert!(matches!(other_version, Err(ReplayError::Version(2))));
+}
+
+#[test]
+fn a_bad_initial_position_is_rejected() {
+    let mut replay = solved_replay();
+    replay.initial = vec![1, 1, 2, 0];
+    assert!(matches!(replay.validate(), Err(ReplayError::State(_))));
+}
@@ -0,0 +1,166 @@
+//! Moving between the menus and games.
+
+use macroquad::math::{Vec2, vec2};
+use slide_puzzle::scene::{Choice, GameEnd, GameResult, GameSetup, Menu, Mode, Scene};
+use slide_puzzle::{DrawCall, MAX_BOARD_SIZE, RecordingRenderer, Theme};
+
+fn picker(rows: usize, cols: usize) -> Scene {
+    Scene::SizePicker {
+        mode: Mode::Play,
+        rows,
+        cols,
+    }
+}
+
+fn result() -> GameResult {
+    GameResult {
+        moves: 31,
+        seconds: 75.0,
+        optimal: Some(21),
+        share: None,
+    }
+}
+
+#[test]
+fn menus_lead_to_a_game_and_back() {
+    let scene = Scene::MainMenu.choose(Choice::NewGame);
+    assert_eq!(scene, Scene::ModeSelect);
+    let scene = scene.choose(Choice::Mode(Mode::Watch));
+    assert_eq!(
+        scene,
+        Scene::SizePicker {
+            mode: Mode::Watch,
+            rows: 4,
+            cols: 4
+        }
+    );
+    let setup = GameSetup {
+        mode: Mode::Watch,
+        rows: 4,
+        cols: 4,
+    };
+    assert_eq!(scene.choose(Choice::Start), Scene::Game(setup));
+
+    let results = Scene::after_game(setup, GameEnd::Finished(result()));
+    assert_eq!(
+        results.clone().choose(Choice::PlayAgain),
+        Scene::Game(setup)
+    );
+    assert_eq!(results.choose(Choice::MainMenu), Scene::MainMenu);
+    assert_eq!(Scene::after_game(setup, GameEnd::Menu), Scene::MainMenu);
+    assert_eq!(Scene::after_game(setup, GameEnd::Quit), Scene::Quit);
+
+    assert_eq!(
+        Scene::ModeSelect.choose(Choice::Mode(Mode::Levels)),
+        Scene::Levels
+    );
+    assert_eq!(picker(3, 3).choose(Choice::Back), Scene::ModeSelect);
+    assert_eq!(Scene::ModeSelect.choose(Choice::Back), Scene::MainMenu);
+    assert_eq!(Scene::ModeSelect.choose(Choice::Quit), Scene::Quit);
+}
+
+#[test]
+fn every_menu_item_leads_somewhere() {
+    let scenes = [
+        Scene::MainMenu,
+        Scene::ModeSelect,
+        picker(3, 3),
+        Scene::after_game(
+            GameSetup {
+                mode: Mode::Daily,
+                rows: 3,
+                cols: 3,
+            },
+            GameEnd::Finished(result()),
+        ),
+    ];
+    for scene in scenes {
+        let menu = scene.menu().unwrap();
+        for (label, choice) in &menu.items {
+            let next = scene.clone().choose(*choice);
+            assert_ne!(next, scene, "{}", label);
+        }
+    }
+    assert_eq!(Scene::Levels.menu(), None);
+}
+
+#[test]
+fn size_picker_wraps_and_skips_a_single_cell() {
+    assert_eq!(picker(3, 4).adjust(Choice::Rows, 1), picker(4, 4));
+    assert_eq!(picker(3, 4).adjust(Choice::Cols, -1), picker(3, 3));
+    assert_eq!(
+        picker(MAX_BOARD_SIZE, 4).adjust(Choice::Rows, 1),
+        picker(1, 4)
+    );
+    assert_eq!(
+        picker(2, 1).adjust(Choice::Rows, -1),
+        picker(MAX_BOARD_SIZE, 1)
+    );
+    assert_eq!(
+        picker(1, MAX_BOARD_SIZE).adjust(Choice::Cols, 1),
+        picker(1, 2)
+    );
+    // clicking steps up
+    assert_eq!(picker(3, 4).choose(Choice::Cols), picker(3, 5));
+    // only the picker has a size
+    assert_eq!(Scene::MainMenu.adjust(Choice::Rows, 1), Scene::MainMenu);
+}
+
+#[test]
+fn menu_cursor_wraps_and_follows_clicks() {
+    let mut menu = Menu::pause();
+    menu.up();
+    assert_eq!(menu.selected(), Choice::Quit);
+    menu.down();
+    assert_eq!(menu.selected(), Choice::Resume);
+    assert_eq!(menu.escape(), Some(Choice::Resume));
+    assert_eq!(Scene::MainMenu.menu().unwrap().escape(), None);
+
+    // the items are drawn below the title, one highlighted
+    let origin = vec2(100.0, 50.0);
+    let mut renderer = RecordingRenderer::new();
+    menu.draw(&mut renderer, origin, &Theme::LIGHT);
+    let item_tops = renderer
+        .calls
+        .iter()
+        .filter_map(|call| match call {
+            DrawCall::Rectangle { y, .. } => Some(*y),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(item_tops.len(), 1);
+
+    let first = vec2(origin.x + 10.0, item_tops[0] + 1.0);
+    let height = menu.size().y - (item_tops[0] - origin.y);
+    let step = height / menu.items.len() as f32;
+    assert_eq!(menu.item_at(origin, first), Some(0));
+    assert_eq!(menu.item_at(origin, first + vec2(0.0, step * 2.0)), Some(2));
+    assert_eq!(menu.item_at(origin, first + vec2(0.0, height)), None);
+    assert_eq!(menu.item_at(origin, Vec2::ZERO), None);
+}
+
+#[test]
+fn results_show_the_game() {
+    let share = "Slide Puzzle daily 2026-10-18 (4x4)\n31 moves in 1:15".to_string();
+    let scene = Scene::Results {
+        setup: GameSetup {
+            mode: Mode::Daily,
+            rows: 4,
+            cols: 4,
+        },
+        result: GameResult {
+            share: Some(share),
+            ..result()
+        },
+    };
+    let menu = scene.menu().unwrap();
+    assert_eq!(
+        menu.text,
+        [
+            "4x4  31 moves in 1:15",
+            "optimal: 21 moves",
+            "Slide Puzzle daily 2026-10-18 (4x4)",
+            "31 moves in 1:15",
+        ]
+    );
+}
@@ -0,0 +1,92 @@
+//! Terminal board drawing and key mapping.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use slide_puzzle::tui::{self, Action};
+use slide_puzzle::{Cell, State, cell};
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+#[test]
+fn two_digit_tiles_get_wider_cells() {
+    let state = State::from_numbers(3, 4, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 0, 11]).unwrap();
+    let expected = "\
+┌────┬────┬────┬────┐
+│ 1  │ 2  │ 3  │ 4  │
+├────┼────┼────┼────┤
+│ 5  │ 6  │ 7  │ 8  │
+├────┼────┼────┼────┤
+│ 9  │ 10 │    │ 11 │
+└────┴────┴────┴────┘
+";
+    assert_eq!(tui::render(&state, false), expected);
+}
+
+#[test]
+fn every_key_set_slides_towards_the_blank() {
+    // the blank in the middle of the top row
+    let state = State::from_numbers(2, 3, &[1, 0, 2, 3, 4, 5]).unwrap();
+    let slides = [
+        (
+            [KeyCode::Left, KeyCode::Char('a'), KeyCode::Char('h')],
+            Some(cell!(2, 0)),
+        ),
+        (
+            [KeyCode::Right, KeyCode::Char('d'), KeyCode::Char('l')],
+            Some(cell!(0, 0)),
+        ),
+        (
+            [KeyCode::Up, KeyCode::Char('w'), KeyCode::Char('k')],
+            Some(cell!(1, 1)),
+        ),
+        // nothing above the blank to pull down
+        (
+            [KeyCode::Down, KeyCode::Char('s'), KeyCode::Char('j')],
+            None,
+        ),
+    ];
+    for (codes, cell) in slides {
+        for code in codes {
+            assert_eq!(
+                tui::key_action(&state, key(code)),
+                cell.map(Action::Slide),
+                "{:?}",
+                code
+            );
+        }
+    }
+
+    // in the bottom right corner, only down and right have a tile to move
+    let corner = State::new(2, 3);
+    assert_eq!(tui::key_action(&corner, key(KeyCode::Left)), None);
+    assert_eq!(tui::key_action(&corner, key(KeyCode::Up)), None);
+    assert_eq!(
+        tui::key_action(&corner, key(KeyCode::Char('j'))),
+        Some(Action::Slide(cell!(2, 0)))
+    );
+    assert_eq!(
+        tui::key_action(&corner, key(KeyCode::Char('l'))),
+        Some(Action::Slide(cell!(1, 1)))
+    );
+}
+
+#[test]
+fn quitting_and_undoing() {
+    let state = State::new(2, 2);
+    let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert_eq!(tui::key_action(&state, ctrl_c), Some(Action::Quit));
+    assert_eq!(tui::key_action(&state, key(KeyCode::Char('c'))), None);
+    assert_eq!(
+        tui::key_action(&state, key(KeyCode::Char('q'))),
+        Some(Action::Quit)
+    );
+    assert_eq!(
+        tui::key_action(&state, key(KeyCode::Esc)),
+        Some(Action::Quit)
+    );
+    assert_eq!(
+        tui::key_action(&state, key(KeyCode::Char('u'))),
+        Some(Action::Undo)
+    );
+}
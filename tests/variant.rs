@@ -0,0 +1,40 @@
+//! The AI on boards with walls, several blanks or other shapes.
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use slide_puzzle::variant::{AI_MAX_CELLS, OPTIMAL_MAX_CELLS, VariantState};
+use slide_puzzle::{Cell, cell};
+
+fn shuffled(rows: usize, cols: usize, walls: &[Cell], blanks: usize) -> VariantState {
+    let mut state = VariantState::goal(rows, cols, walls, blanks).unwrap();
+    state.shuffle_with(&mut StdRng::seed_from_u64(1));
+    state
+}
+
+#[test]
+fn small_boards_get_the_shortest_solution() {
+    let state = shuffled(3, 3, &[cell!(1, 1)], 1);
+    assert!(state.open_cells() <= OPTIMAL_MAX_CELLS);
+    assert_eq!(
+        state.solve_ai().map(|moves| moves.len()),
+        state.solve_bfs().map(|moves| moves.len())
+    );
+}
+
+#[test]
+fn larger_boards_get_a_quick_solution() {
+    let state = shuffled(4, 4, &[], 1);
+    assert!(state.open_cells() > OPTIMAL_MAX_CELLS);
+    let mut end = state.clone();
+    for mv in state.solve_ai().unwrap() {
+        assert!(end.slide(mv), "illegal move {:?}", mv);
+    }
+    assert!(end.is_finished());
+}
+
+#[test]
+fn the_ai_refuses_boards_past_its_size() {
+    let state = shuffled(5, 5, &[cell!(2, 2)], 1);
+    assert!(state.open_cells() > AI_MAX_CELLS);
+    assert_eq!(state.solve_ai(), None);
+}